@@ -48,4 +48,7 @@ pub enum Subcommand {
 
     /// Db meta columns information.
     ChainInfo(sc_cli::ChainInfoCmd),
+
+    /// Simulate subnet epochs natively and print the resulting emissions.
+    SimulateEpoch(crate::simulate::SimulateEpochCmd),
 }
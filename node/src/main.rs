@@ -10,6 +10,7 @@ mod benchmarking;
 mod cli;
 mod command;
 mod rpc;
+mod simulate;
 
 fn main() -> sc_cli::Result<()> {
     command::run()
@@ -37,9 +37,11 @@ where
     C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
     C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
     C::Api: subspace_rpc::SubspaceRuntimeApi<Block>,
+    C::Api: governance_rpc::GovernanceRuntimeApi<Block>,
     C::Api: BlockBuilder<Block>,
     P: TransactionPool + 'static,
 {
+    use governance_rpc::{GovernanceApiServer, GovernancePallet};
     use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
     use subspace_rpc::{SubspaceApiServer, SubspacePallet};
     use substrate_frame_rpc_system::{System, SystemApiServer};
@@ -54,6 +56,7 @@ where
     module.merge(System::new(client.clone(), pool, deny_unsafe).into_rpc())?;
     module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
     module.merge(SubspacePallet::new(client.clone()).into_rpc())?;
+    module.merge(GovernancePallet::new(client.clone()).into_rpc())?;
     // Extend this RPC with a custom API by using the following syntax.
     // `YourRpcStruct` should have a reference to a client, which is needed
     // to call into the runtime.
@@ -0,0 +1,169 @@
+use std::fmt::Write as _;
+
+use node_subspace_runtime::{
+    pallet_subspace::{self, subnet::SubnetChangeset},
+    Runtime, SubspaceModule, System,
+};
+use sc_cli::{CliConfiguration, SharedParams};
+use serde::Serialize;
+use sp_core::crypto::Ss58Codec;
+
+/// Output format of the simulation results.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum OutputFormat {
+    Json,
+    Csv,
+}
+
+/// Runs the subnet epochs natively on top of a chain spec or exported state, without
+/// networking, and prints the resulting per-module emission, dividends and incentive.
+#[derive(Debug, clap::Parser)]
+pub struct SimulateEpochCmd {
+    /// Number of blocks to step through.
+    #[arg(long, default_value_t = 100)]
+    pub blocks: u64,
+
+    /// Only simulate and print this subnet.
+    #[arg(long)]
+    pub netuid: Option<u16>,
+
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+    pub format: OutputFormat,
+
+    /// Overrides the subnet founder share before simulating. Requires `--netuid`.
+    #[arg(long, requires = "netuid")]
+    pub founder_share: Option<u16>,
+
+    /// Overrides the subnet incentive ratio before simulating. Requires `--netuid`.
+    #[arg(long, requires = "netuid")]
+    pub incentive_ratio: Option<u16>,
+
+    /// Overrides the subnet trust ratio before simulating. Requires `--netuid`.
+    #[arg(long, requires = "netuid")]
+    pub trust_ratio: Option<u16>,
+
+    /// Overrides the subnet bonds moving average before simulating. Requires `--netuid`.
+    #[arg(long, requires = "netuid")]
+    pub bonds_ma: Option<u64>,
+
+    #[clap(flatten)]
+    pub shared_params: SharedParams,
+}
+
+#[derive(Debug, Serialize)]
+struct SimulatedModule {
+    netuid: u16,
+    uid: u16,
+    key: String,
+    name: String,
+    stake: u64,
+    emission: u64,
+    dividends: u16,
+    incentive: u16,
+}
+
+impl SimulateEpochCmd {
+    /// Runs the simulation on the storage of the given chain spec.
+    pub fn run(&self, chain_spec: Box<dyn sc_service::ChainSpec>) -> sc_cli::Result<()> {
+        let storage = chain_spec.as_storage_builder().build_storage()?;
+        let mut ext = sp_io::TestExternalities::new(storage);
+
+        let modules = ext.execute_with(|| -> sc_cli::Result<Vec<SimulatedModule>> {
+            if let Some(netuid) = self.netuid {
+                self.apply_overrides(netuid)?;
+            }
+
+            for _ in 0..self.blocks {
+                System::set_block_number(System::block_number().saturating_add(1));
+                SubspaceModule::block_step();
+                System::reset_events();
+            }
+
+            Ok(self.collect_modules())
+        })?;
+
+        let output = match self.format {
+            OutputFormat::Json => serde_json::to_string_pretty(&modules)
+                .map_err(|err| format!("failed to serialize simulation results: {err}"))?,
+            OutputFormat::Csv => to_csv(&modules),
+        };
+        println!("{output}");
+
+        Ok(())
+    }
+
+    fn apply_overrides(&self, netuid: u16) -> sc_cli::Result<()> {
+        if !pallet_subspace::N::<Runtime>::contains_key(netuid) {
+            return Err(format!("subnet {netuid} does not exist").into());
+        }
+
+        let mut params = SubspaceModule::subnet_params(netuid);
+        if let Some(founder_share) = self.founder_share {
+            params.founder_share = founder_share;
+        }
+        if let Some(incentive_ratio) = self.incentive_ratio {
+            params.incentive_ratio = incentive_ratio;
+        }
+        if let Some(trust_ratio) = self.trust_ratio {
+            params.trust_ratio = trust_ratio;
+        }
+        if let Some(bonds_ma) = self.bonds_ma {
+            params.bonds_ma = bonds_ma;
+        }
+
+        SubnetChangeset::<Runtime>::update(netuid, params)
+            .and_then(|changeset| changeset.apply(netuid))
+            .map_err(|err| format!("invalid subnet params: {err:?}").into())
+    }
+
+    fn collect_modules(&self) -> Vec<SimulatedModule> {
+        let netuids: Vec<u16> = match self.netuid {
+            Some(netuid) => vec![netuid],
+            None => pallet_subspace::N::<Runtime>::iter_keys().collect(),
+        };
+
+        netuids
+            .into_iter()
+            .flat_map(|netuid| {
+                SubspaceModule::get_subnet_metagraph(netuid, 0, u16::MAX).into_iter().map(
+                    move |module| SimulatedModule {
+                        netuid,
+                        uid: module.uid,
+                        key: module.key.to_ss58check(),
+                        name: String::from_utf8_lossy(&module.name).into_owned(),
+                        stake: module.stake,
+                        emission: module.emission,
+                        dividends: module.dividends,
+                        incentive: module.incentive,
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+fn to_csv(modules: &[SimulatedModule]) -> String {
+    let mut csv = String::from("netuid,uid,key,name,stake,emission,dividends,incentive\n");
+    for module in modules {
+        let _ = writeln!(
+            csv,
+            "{},{},{},\"{}\",{},{},{},{}",
+            module.netuid,
+            module.uid,
+            module.key,
+            module.name.replace('"', "\"\""),
+            module.stake,
+            module.emission,
+            module.dividends,
+            module.incentive,
+        );
+    }
+    csv
+}
+
+impl CliConfiguration for SimulateEpochCmd {
+    fn shared_params(&self) -> &SharedParams {
+        &self.shared_params
+    }
+}
@@ -5,10 +5,26 @@ use parity_scale_codec::{Decode, Encode, MaxEncodedLen};
 use scale_info::TypeInfo;
 use sp_runtime::{DispatchResult, Percent};
 
+/// Maximum number of explicit voting power delegations, across every scope. Checking a global
+/// delegation or rotating a key goes through all of them.
+pub const MAX_VOTING_POWER_DELEGATIONS: u32 = 512;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, TypeInfo, Decode, Encode, MaxEncodedLen)]
 pub enum VoteMode {
     Authority = 0,
     Vote = 1,
+    /// Voters are weighted by the square root of their stake.
+    Quadratic = 2,
+    /// Voters can lock their stake for longer periods to multiply their vote weight.
+    Conviction = 3,
+}
+
+impl VoteMode {
+    /// Whether proposals are decided by stakers voting on them.
+    #[must_use]
+    pub fn is_voting(&self) -> bool {
+        !matches!(self, Self::Authority)
+    }
 }
 
 #[derive(Clone, TypeInfo, Decode, Encode, PartialEq, Eq, DebugNoBound, MaxEncodedLen)]
@@ -19,6 +35,8 @@ pub struct GovernanceConfiguration {
     pub proposal_reward_treasury_allocation: Percent,
     pub max_proposal_reward_treasury_allocation: u64,
     pub proposal_reward_interval: u64,
+    pub proposal_enactment_delay: u64,
+    pub conviction_lock_period: u64,
 }
 
 impl Default for GovernanceConfiguration {
@@ -32,6 +50,10 @@ impl Default for GovernanceConfiguration {
             // Maximum reward allocation 10K.
             max_proposal_reward_treasury_allocation: 10_000_000_000_000,
             proposal_reward_interval: 75_600,
+            // Accepted proposals are enacted right away unless a delay is configured.
+            proposal_enactment_delay: 0,
+            // Each conviction level locks the stake for another week.
+            conviction_lock_period: 75_600,
         }
     }
 }
@@ -68,9 +90,16 @@ pub trait GovernanceApi<AccountId> {
     /// global.
     fn update_delegating_voting_power(delegator: &AccountId, delegating: bool) -> DispatchResult;
 
+    /// Returns the amount of stake the account has locked through conviction votes on the given
+    /// subnet, or on global proposals if `None`.
+    fn get_locked_stake(staker: &AccountId, subnet_id: Option<u16>) -> u64;
+
     /// Handles the deregistration of a subnet.
     fn handle_subnet_removal(subnet_id: u16);
 
+    /// Moves the governance entries of a rotated module key to its new key.
+    fn handle_key_rotation(key: &AccountId, new_key: &AccountId) -> DispatchResult;
+
     /// Executes the application in DAO.
     fn execute_application(user_id: &AccountId) -> DispatchResult;
 
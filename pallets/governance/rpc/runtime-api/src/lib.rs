@@ -0,0 +1,79 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+use sp_runtime::{
+    sp_std::prelude::Vec,
+    traits::{IdentifyAccount, Verify},
+    MultiSignature,
+};
+
+type Signature = MultiSignature;
+pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
+
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Copy, Debug, TypeInfo, Serialize, Deserialize)]
+pub enum ProposalStatus {
+    Open,
+    Accepted,
+    Refused,
+    Expired,
+    Enacting,
+    Canceled,
+    EnactmentFailed,
+}
+
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Copy, Debug, TypeInfo, Serialize, Deserialize)]
+pub enum ProposalKind {
+    GlobalCustom,
+    GlobalParams,
+    SubnetCustom,
+    SubnetParams,
+    TransferDaoTreasury,
+    RuntimeCall,
+}
+
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct ProposalInfo<AccountId> {
+    pub id: u64,
+    pub proposer: AccountId,
+    /// Subnet the proposal impacts, `None` for global proposals.
+    pub subnet_id: Option<u16>,
+    pub kind: ProposalKind,
+    /// SCALE encoded proposal data, as stored in the pallet.
+    pub data: Vec<u8>,
+    pub metadata: Vec<u8>,
+    pub status: ProposalStatus,
+    pub proposal_cost: u64,
+    pub creation_block: u64,
+    pub expiration_block: u64,
+    pub votes_for: Vec<AccountId>,
+    pub votes_against: Vec<AccountId>,
+    /// Stake for and against as of the last tick, or when the proposal was decided.
+    pub stake_for: u64,
+    pub stake_against: u64,
+}
+
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct ProposalTally {
+    pub proposal_id: u64,
+    /// Stake for the proposal with the current stake, weighted by the vote mode.
+    pub stake_for: u64,
+    /// Stake against the proposal with the current stake, weighted by the vote mode.
+    pub stake_against: u64,
+    /// Raw stake of every voter, which has to reach `minimal_stake_to_execute`.
+    pub voted_stake: u64,
+    pub minimal_stake_to_execute: u64,
+}
+
+sp_api::decl_runtime_apis! {
+    pub trait GovernanceRuntimeApi {
+        /// Returns the proposals with the given status on the given subnet, or all of them when
+        /// a filter is `None`, ordered by id.
+        fn get_proposals(status: Option<ProposalStatus>, subnet_id: Option<u16>) -> Vec<ProposalInfo<AccountId>>;
+
+        /// Projects the tally of an open proposal with the current stake. Returns `None` for
+        /// unknown or finished proposals.
+        fn get_proposal_tally(proposal_id: u64) -> Option<ProposalTally>;
+    }
+}
@@ -0,0 +1,81 @@
+use jsonrpsee::{
+    core::RpcResult,
+    proc_macros::rpc,
+    types::error::{ErrorObject, ErrorObjectOwned},
+};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::Block as BlockT;
+use std::sync::Arc;
+
+pub use governance_runtime_api::GovernanceRuntimeApi;
+use governance_runtime_api::{AccountId, ProposalInfo, ProposalStatus, ProposalTally};
+
+#[rpc(client, server)]
+pub trait GovernanceApi<BlockHash> {
+    #[method(name = "governance_getProposals")]
+    fn get_proposals(
+        &self,
+        status: Option<ProposalStatus>,
+        subnet_id: Option<u16>,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<ProposalInfo<AccountId>>>;
+
+    #[method(name = "governance_getProposalTally")]
+    fn get_proposal_tally(
+        &self,
+        proposal_id: u64,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<ProposalTally>>;
+}
+
+pub struct GovernancePallet<C, Block> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, Block> GovernancePallet<C, Block> {
+    pub fn new(client: Arc<C>) -> Self {
+        Self {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<C, Block> GovernanceApiServer<<Block as BlockT>::Hash> for GovernancePallet<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: GovernanceRuntimeApi<Block>,
+{
+    fn get_proposals(
+        &self,
+        status: Option<ProposalStatus>,
+        subnet_id: Option<u16>,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Vec<ProposalInfo<AccountId>>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_proposals(at, status, subnet_id).map_err(runtime_error_into_rpc_err)
+    }
+
+    fn get_proposal_tally(
+        &self,
+        proposal_id: u64,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<ProposalTally>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_proposal_tally(at, proposal_id).map_err(runtime_error_into_rpc_err)
+    }
+}
+
+const RUNTIME_ERROR: i32 = 1;
+
+/// Converts a runtime trap into an RPC error.
+fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> ErrorObjectOwned {
+    ErrorObject::owned(RUNTIME_ERROR, "Runtime error", Some(format!("{:?}", err)))
+}
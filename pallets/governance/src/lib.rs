@@ -16,7 +16,11 @@ use sp_std::vec::Vec;
 
 pub use pallet::*;
 pub use pallet_governance_api::*;
-pub use proposal::{Proposal, ProposalData, ProposalId, ProposalStatus, UnrewardedProposal};
+pub use proposal::{
+    CallStakeThreshold, Proposal, ProposalData, ProposalId, ProposalStatus, UnrewardedProposal,
+    VotingPower, MAX_PROPOSAL_CALL_SIZE,
+};
+pub use voting::{StakeLock, MAX_CONVICTION};
 
 type SubnetId = u16;
 
@@ -26,15 +30,22 @@ pub mod pallet {
 
     use crate::{dao::CuratorApplication, *};
     use frame_support::{
+        dispatch::{GetDispatchInfo, PostDispatchInfo},
         pallet_prelude::{ValueQuery, *},
-        traits::{Currency, StorageInstance},
+        traits::{Contains, Currency, StorageInstance},
         PalletId,
     };
     use frame_system::pallet_prelude::{ensure_signed, BlockNumberFor};
-    use pallet_subspace::DefaultKey;
-    use sp_runtime::traits::AccountIdConversion;
+    use pallet_subspace::{
+        global::{
+            BurnDestination, FeeConfiguration, RateLimitConfiguration, SubnetAuctionConfiguration,
+        },
+        DefaultKey, SubnetConsensus,
+    };
+    use sp_runtime::traits::{AccountIdConversion, Dispatchable};
+    use sp_std::boxed::Box;
 
-    const STORAGE_VERSION: StorageVersion = StorageVersion::new(0);
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
 
     #[pallet::pallet]
     #[pallet::storage_version(STORAGE_VERSION)]
@@ -52,8 +63,38 @@ pub mod pallet {
 
         /// Currency type that will be used to place deposits on modules
         type Currency: Currency<Self::AccountId> + Send + Sync;
+
+        /// The origin accepted runtime call proposals are dispatched with.
+        #[pallet::no_default]
+        type ProposalOrigin: From<RawOrigin> + From<frame_system::RawOrigin<Self::AccountId>>;
+
+        /// The runtime call carried by runtime call proposals.
+        #[pallet::no_default]
+        type ProposalCall: Parameter
+            + Dispatchable<RuntimeOrigin = Self::ProposalOrigin, PostInfo = PostDispatchInfo>
+            + GetDispatchInfo;
+
+        /// The stake required for each kind of runtime call proposal to pass.
+        #[pallet::no_default]
+        type CallStakeThreshold: CallStakeThreshold<Self::ProposalCall>;
+
+        /// The only calls accepted proposals dispatch as root. Every other call is dispatched
+        /// with the [`RawOrigin::Governance`] origin, which pallets accept through
+        /// [`EnsureGovernance`].
+        #[pallet::no_default]
+        type RootCalls: Contains<Self::ProposalCall>;
+    }
+
+    /// The origin runtime call proposals are dispatched with.
+    #[derive(Clone, PartialEq, Eq, RuntimeDebug, Encode, Decode, TypeInfo, MaxEncodedLen)]
+    pub enum RawOrigin {
+        /// The call was accepted by a governance vote.
+        Governance,
     }
 
+    #[pallet::origin]
+    pub type Origin = RawOrigin;
+
     #[pallet::hooks]
     impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
         fn on_initialize(block_number: BlockNumberFor<T>) -> Weight {
@@ -61,6 +102,8 @@ pub mod pallet {
                 block_number.try_into().ok().expect("blockchain won't pass 2 ^ 64 blocks");
 
             proposal::tick_proposals::<T>(block_number);
+            proposal::tick_enactments::<T>(block_number);
+            voting::clear_expired_stake_locks::<T>(block_number);
             proposal::tick_proposal_rewards::<T>(block_number);
 
             Weight::zero()
@@ -105,6 +148,20 @@ pub mod pallet {
     #[pallet::storage]
     pub type Proposals<T: Config> = StorageMap<_, Identity, ProposalId, Proposal<T>>;
 
+    /// The encoded calls of runtime call proposals, removed once the proposal is finished.
+    #[pallet::storage]
+    pub type ProposalCalls<T: Config> =
+        StorageMap<_, Identity, ProposalId, BoundedVec<u8, ConstU32<MAX_PROPOSAL_CALL_SIZE>>>;
+
+    /// Accepted proposals waiting for their enactment, indexed by the block they are enacted at.
+    #[pallet::storage]
+    pub type EnactmentQueue<T: Config> = StorageMap<
+        _,
+        Identity,
+        u64,
+        BoundedVec<ProposalId, ConstU32<{ proposal::MAX_ENACTMENTS_PER_BLOCK }>>,
+    >;
+
     /// A map relating all modules and the stakers that are currently **NOT** delegating their
     /// voting power.
     ///
@@ -114,6 +171,28 @@ pub mod pallet {
     pub type NotDelegatingVotingPower<T: Config> =
         StorageValue<_, BoundedBTreeSet<T::AccountId, ConstU32<{ u32::MAX }>>, ValueQuery>;
 
+    /// Explicit voting power delegations, indexed by the subnet the delegation applies to
+    /// (`None` for a global delegation) and the delegator. Subnet delegations take precedence
+    /// over the global one.
+    #[pallet::storage]
+    pub type VotingPowerDelegations<T: Config> =
+        StorageDoubleMap<_, Identity, Option<SubnetId>, Identity, T::AccountId, T::AccountId>;
+
+    /// Number of entries in [`VotingPowerDelegations`], bounded by
+    /// [`MAX_VOTING_POWER_DELEGATIONS`](voting::MAX_VOTING_POWER_DELEGATIONS).
+    #[pallet::storage]
+    pub type VotingPowerDelegationCount<T> = StorageValue<_, u32, ValueQuery>;
+
+    /// Stake locked by conviction votes, indexed by the voter and the proposal voted on.
+    #[pallet::storage]
+    pub type StakeLocks<T: Config> =
+        StorageDoubleMap<_, Identity, T::AccountId, Identity, ProposalId, StakeLock>;
+
+    /// The conviction locks indexed by the block they expire at.
+    #[pallet::storage]
+    pub type StakeLockExpirations<T: Config> =
+        StorageDoubleMap<_, Identity, u64, Identity, (T::AccountId, ProposalId), ()>;
+
     #[pallet::storage]
     pub type UnrewardedProposals<T: Config> =
         StorageMap<_, Identity, ProposalId, UnrewardedProposal<T>>;
@@ -183,6 +262,13 @@ pub mod pallet {
             proposal_cost: u64,
             proposal_expiration: u32,
             general_subnet_application_cost: u64,
+            unbonding_period: u64,
+            proposal_enactment_delay: u64,
+            subnet_auction_period: u64,
+            burn_destination: BurnDestination,
+            rate_limit_config: RateLimitConfiguration,
+            fee_config: FeeConfiguration,
+            subnet_auction_config: SubnetAuctionConfiguration,
         ) -> DispatchResult {
             let mut params = pallet_subspace::Pallet::<T>::global_params();
             params.max_name_length = max_name_length;
@@ -198,10 +284,17 @@ pub mod pallet {
             params.subnet_stake_threshold = subnet_stake_threshold;
             params.governance_config.proposal_cost = proposal_cost;
             params.governance_config.proposal_expiration = proposal_expiration;
+            params.governance_config.proposal_enactment_delay = proposal_enactment_delay;
             params.general_subnet_application_cost = general_subnet_application_cost;
+            params.unbonding_period = unbonding_period;
+            params.subnet_auction_period = subnet_auction_period;
+            params.rate_limit_config = rate_limit_config;
+            params.fee_config = fee_config;
+            params.subnet_auction_config = subnet_auction_config;
 
             params.burn_config.min_burn = min_burn;
             params.burn_config.max_burn = max_burn;
+            params.burn_config.destination = burn_destination;
 
             Self::do_add_global_params_proposal(origin, data, params)
         }
@@ -231,6 +324,17 @@ pub mod pallet {
             target_registrations_per_interval: u16,
             max_registrations_per_interval: u16,
             adjustment_alpha: u64,
+            unbonding_period: u64,
+            slash_ratio: Percent,
+            slash_threshold: Percent,
+            commit_reveal_weights: bool,
+            weight_reveal_period: u16,
+            consensus: SubnetConsensus,
+            weight_history: bool,
+            reward_history: bool,
+            liquid_alpha: bool,
+            alpha_low: u16,
+            alpha_high: u16,
         ) -> DispatchResult {
             let mut params = pallet_subspace::Pallet::subnet_params(subnet_id);
             params.founder = founder;
@@ -252,6 +356,17 @@ pub mod pallet {
             params.target_registrations_per_interval = target_registrations_per_interval;
             params.max_registrations_per_interval = max_registrations_per_interval;
             params.adjustment_alpha = adjustment_alpha;
+            params.unbonding_period = unbonding_period;
+            params.slash_ratio = slash_ratio;
+            params.slash_threshold = slash_threshold;
+            params.commit_reveal_weights = commit_reveal_weights;
+            params.weight_reveal_period = weight_reveal_period;
+            params.consensus = consensus;
+            params.weight_history = weight_history;
+            params.reward_history = reward_history;
+            params.liquid_alpha = liquid_alpha;
+            params.alpha_low = alpha_low;
+            params.alpha_high = alpha_high;
 
             Self::do_add_subnet_params_proposal(origin, subnet_id, data, params)
         }
@@ -304,6 +419,20 @@ pub mod pallet {
             Self::do_remove_vote_proposal(origin, proposal_id)
         }
 
+        #[pallet::call_index(18)]
+        #[pallet::weight(Weight::from_parts(22_732_000, 6825)
+        .saturating_add(T::DbWeight::get().reads(4_u64))
+        .saturating_add(T::DbWeight::get().writes(2_u64))
+        )]
+        pub fn vote_proposal_with_conviction(
+            origin: OriginFor<T>,
+            proposal_id: u64,
+            agree: bool,
+            conviction: u8,
+        ) -> DispatchResult {
+            Self::do_vote_proposal_with_conviction(origin, proposal_id, agree, conviction)
+        }
+
         #[pallet::call_index(7)]
         #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
         pub fn enable_vote_power_delegation(origin: OriginFor<T>) -> DispatchResult {
@@ -318,6 +447,51 @@ pub mod pallet {
             Self::update_delegating_voting_power(&key, false)
         }
 
+        // A global delegation reads every delegation, so it pays for all of them.
+        #[pallet::call_index(13)]
+        #[pallet::weight(T::DbWeight::get().reads_writes(
+            u64::from(voting::MAX_VOTING_POWER_DELEGATIONS).saturating_add(2),
+            2,
+        ))]
+        pub fn delegate_voting_power(
+            origin: OriginFor<T>,
+            subnet_id: Option<SubnetId>,
+            delegate: T::AccountId,
+        ) -> DispatchResult {
+            Self::do_delegate_voting_power(origin, subnet_id, delegate)
+        }
+
+        #[pallet::call_index(14)]
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn undelegate_voting_power(
+            origin: OriginFor<T>,
+            subnet_id: Option<SubnetId>,
+        ) -> DispatchResult {
+            Self::do_undelegate_voting_power(origin, subnet_id)
+        }
+
+        #[pallet::call_index(15)]
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn cancel_proposal(origin: OriginFor<T>, proposal_id: u64) -> DispatchResult {
+            Self::do_cancel_proposal(origin, proposal_id)
+        }
+
+        #[pallet::call_index(16)]
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn vote_cancel_proposal(origin: OriginFor<T>, proposal_id: u64) -> DispatchResult {
+            Self::do_vote_cancel_proposal(origin, proposal_id)
+        }
+
+        #[pallet::call_index(17)]
+        #[pallet::weight((Weight::zero(), DispatchClass::Normal, Pays::No))]
+        pub fn add_runtime_call_proposal(
+            origin: OriginFor<T>,
+            data: Vec<u8>,
+            call: Box<T::ProposalCall>,
+        ) -> DispatchResult {
+            Self::do_add_runtime_call_proposal(origin, data, *call)
+        }
+
         // ---------------------------------
         // Subnet 0 DAO
         // ---------------------------------
@@ -368,15 +542,31 @@ pub mod pallet {
         ProposalAccepted(ProposalId),
         ProposalRefused(ProposalId),
         ProposalExpired(ProposalId),
+        /// (proposal_id, enactment_block)
+        ProposalEnacting(ProposalId, u64),
+        ProposalEnacted(ProposalId),
+        ProposalCanceled(ProposalId),
+        /// (proposal_id, error) Event created when an accepted proposal failed to be enacted.
+        ProposalEnactmentFailed(ProposalId, DispatchError),
+        /// (proposal_id, result) Event created when the call of a runtime call proposal has been
+        /// dispatched.
+        ProposalCallDispatched(ProposalId, DispatchResult),
 
         ProposalVoted(u64, T::AccountId, bool),
         ProposalVoteUnregistered(u64, T::AccountId),
+        /// (proposal_id, voter)
+        ProposalCancelVoted(u64, T::AccountId),
 
         WhitelistModuleAdded(T::AccountId), /* --- Event created when a module account has been
                                              * added to the whitelist. */
         WhitelistModuleRemoved(T::AccountId), /* --- Event created when a module account has
                                                * been removed from the whitelist. */
         ApplicationCreated(u64),
+
+        /// (delegator, subnet_id, delegate)
+        VotingPowerDelegated(T::AccountId, Option<SubnetId>, T::AccountId),
+        /// (delegator, subnet_id)
+        VotingPowerUndelegated(T::AccountId, Option<SubnetId>),
     }
 
     #[pallet::error]
@@ -424,6 +614,30 @@ pub mod pallet {
         VoteModeIsNotAuthority,
         /// An internal error occurred, probably relating to the size of the bounded sets.
         InternalError,
+        /// An account cannot delegate its voting power to itself.
+        DelegatingToSelf,
+        /// The delegation would create a cycle of delegates.
+        DelegationCycle,
+        /// The delegation chain would be longer than the maximum delegation depth.
+        DelegationChainTooLong,
+        /// The maximum number of voting power delegations was reached.
+        TooManyVotingPowerDelegations,
+        /// The new key of a rotated module already delegates voting power or is delegated to.
+        DelegationKeyInUse,
+        /// The account is not delegating its voting power on the given subnet.
+        NotDelegating,
+        /// The proposal is not waiting for its enactment.
+        ProposalNotEnacting,
+        /// The encoded runtime call is bigger than the maximum proposal call size.
+        ProposalCallTooLarge,
+        /// The stored runtime call could not be decoded.
+        InvalidProposalCall,
+        /// The runtime call weighs more than a single extrinsic may.
+        ProposalCallTooHeavy,
+        /// The proposal is not decided by conviction voting.
+        NotConvictionMode,
+        /// The conviction is higher than the maximum conviction.
+        InvalidConviction,
 
         // DAO / Governance
         ApplicationTooSmall,
@@ -497,5 +711,28 @@ impl<T: Config> Pallet<T> {
 
     pub fn handle_subnet_removal(subnet_id: u16) {
         SubnetGovernanceConfig::<T>::remove(subnet_id);
+        let removed = VotingPowerDelegations::<T>::clear_prefix(Some(subnet_id), u32::MAX, None);
+        VotingPowerDelegationCount::<T>::mutate(|count| {
+            *count = count.saturating_sub(removed.unique)
+        });
+    }
+}
+
+/// Ensures the origin is the [`RawOrigin::Governance`] origin, i.e. the call comes from an
+/// accepted runtime call proposal.
+pub struct EnsureGovernance;
+
+impl<O: Into<Result<RawOrigin, O>> + From<RawOrigin>> frame_support::traits::EnsureOrigin<O>
+    for EnsureGovernance
+{
+    type Success = ();
+
+    fn try_origin(o: O) -> Result<Self::Success, O> {
+        o.into().map(|RawOrigin::Governance| ())
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn try_successful_origin() -> Result<O, ()> {
+        Ok(O::from(RawOrigin::Governance))
     }
 }
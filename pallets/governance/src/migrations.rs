@@ -2,14 +2,15 @@ use core::marker::PhantomData;
 
 use frame_support::{
     migrations::VersionedMigration,
-    traits::{OnRuntimeUpgrade, StorageVersion, UncheckedOnRuntimeUpgrade},
-    BoundedVec,
+    traits::{ConstU32, OnRuntimeUpgrade, StorageVersion, UncheckedOnRuntimeUpgrade},
+    BoundedBTreeMap, BoundedVec,
 };
 
 use crate::{
     proposal::{ProposalData, ProposalStatus},
     *,
 };
+use frame_support::traits::Get;
 
 use pallet_subspace::Pallet as PalletSubspace;
 
@@ -20,7 +21,9 @@ impl<T: Config + pallet_subspace::Config> OnRuntimeUpgrade for InitialMigration<
     fn on_runtime_upgrade() -> frame_support::weights::Weight {
         use pallet_subspace::migrations::v11::old_storage as old;
 
-        if StorageVersion::get::<Pallet<T>>() != 0 {
+        // Chains that already imported the old storage still report version 0 until `MigrationV1`
+        // has run, but they have a governance configuration stored.
+        if StorageVersion::get::<Pallet<T>>() != 0 || GlobalGovernanceConfig::<T>::exists() {
             return frame_support::weights::Weight::zero();
         }
 
@@ -194,6 +197,10 @@ impl<T: Config + pallet_subspace::Config> OnRuntimeUpgrade for InitialMigration<
 
         log::info!("Migrated global governance config");
 
+        // Everything above is written in the current layout, so `MigrationV1` must not translate
+        // it again.
+        StorageVersion::new(1).put::<Pallet<T>>();
+
         frame_support::weights::Weight::zero()
     }
 }
@@ -201,13 +208,254 @@ impl<T: Config + pallet_subspace::Config> OnRuntimeUpgrade for InitialMigration<
 pub type MigrationV1<T> =
     VersionedMigration<0, 1, _MigrationV1<T>, Pallet<T>, <T as frame_system::Config>::DbWeight>;
 
+pub mod v1 {
+    use super::*;
+
+    pub mod old_storage {
+        use super::*;
+        use frame_support::sp_runtime::Percent;
+        use parity_scale_codec::{Decode, Encode};
+        use scale_info::TypeInfo;
+
+        #[derive(TypeInfo, Decode, Encode)]
+        pub struct GovernanceConfiguration {
+            pub proposal_cost: u64,
+            pub proposal_expiration: u32,
+            pub vote_mode: VoteMode,
+            pub proposal_reward_treasury_allocation: Percent,
+            pub max_proposal_reward_treasury_allocation: u64,
+            pub proposal_reward_interval: u64,
+        }
+
+        #[derive(TypeInfo, Decode, Encode)]
+        pub struct BurnConfiguration {
+            pub min_burn: u64,
+            pub max_burn: u64,
+        }
+
+        #[derive(TypeInfo, Decode, Encode)]
+        #[scale_info(skip_type_params(T))]
+        pub struct GlobalParams<T: Config> {
+            pub max_name_length: u16,
+            pub min_name_length: u16,
+            pub max_allowed_subnets: u16,
+            pub max_allowed_modules: u16,
+            pub max_registrations_per_block: u16,
+            pub max_allowed_weights: u16,
+            pub floor_delegation_fee: Percent,
+            pub floor_founder_share: u8,
+            pub min_weight_stake: u64,
+            pub curator: T::AccountId,
+            pub general_subnet_application_cost: u64,
+            pub subnet_stake_threshold: Percent,
+            pub burn_config: BurnConfiguration,
+            pub governance_config: GovernanceConfiguration,
+        }
+
+        #[derive(TypeInfo, Decode, Encode)]
+        #[scale_info(skip_type_params(T))]
+        pub struct SubnetParams<T: Config> {
+            pub founder: T::AccountId,
+            pub founder_share: u16,
+            pub immunity_period: u16,
+            pub incentive_ratio: u16,
+            pub max_allowed_uids: u16,
+            pub max_allowed_weights: u16,
+            pub min_allowed_weights: u16,
+            pub max_weight_age: u64,
+            pub min_stake: u64,
+            pub name: BoundedVec<u8, ConstU32<256>>,
+            pub tempo: u16,
+            pub trust_ratio: u16,
+            pub maximum_set_weight_calls_per_epoch: u16,
+            pub bonds_ma: u64,
+            pub target_registrations_interval: u16,
+            pub target_registrations_per_interval: u16,
+            pub max_registrations_per_interval: u16,
+            pub adjustment_alpha: u64,
+            pub governance_config: GovernanceConfiguration,
+        }
+
+        #[derive(TypeInfo, Decode, Encode)]
+        #[scale_info(skip_type_params(T))]
+        pub enum ProposalData<T: Config> {
+            GlobalCustom,
+            GlobalParams(GlobalParams<T>),
+            SubnetCustom {
+                subnet_id: SubnetId,
+            },
+            SubnetParams {
+                subnet_id: SubnetId,
+                params: SubnetParams<T>,
+            },
+            TransferDaoTreasury {
+                account: T::AccountId,
+                amount: u64,
+            },
+        }
+
+        #[derive(TypeInfo, Decode, Encode)]
+        #[scale_info(skip_type_params(T))]
+        pub struct Proposal<T: Config> {
+            pub id: ProposalId,
+            pub proposer: T::AccountId,
+            pub expiration_block: u64,
+            pub data: ProposalData<T>,
+            pub status: ProposalStatus<T>,
+            pub metadata: BoundedVec<u8, ConstU32<256>>,
+            pub proposal_cost: u64,
+            pub creation_block: u64,
+        }
+
+        #[derive(TypeInfo, Decode, Encode)]
+        #[scale_info(skip_type_params(T))]
+        pub struct UnrewardedProposal<T: Config> {
+            pub subnet_id: Option<SubnetId>,
+            pub block: u64,
+            pub votes_for: BoundedBTreeMap<T::AccountId, u64, ConstU32<{ u32::MAX }>>,
+            pub votes_against: BoundedBTreeMap<T::AccountId, u64, ConstU32<{ u32::MAX }>>,
+        }
+    }
+
+    /// Votes were only weighted by the total stake of the voter, which is kept as direct power.
+    pub fn migrate_votes<T: Config>(
+        votes: BoundedBTreeMap<T::AccountId, u64, ConstU32<{ u32::MAX }>>,
+    ) -> BoundedBTreeMap<T::AccountId, VotingPower, ConstU32<{ u32::MAX }>> {
+        votes.map_collect(|_, direct| VotingPower {
+            direct,
+            delegated: 0,
+        })
+    }
+
+    /// Fills the governance settings added after the old configuration with their defaults.
+    pub fn migrate_governance_config(
+        old: old_storage::GovernanceConfiguration,
+    ) -> GovernanceConfiguration {
+        GovernanceConfiguration {
+            proposal_cost: old.proposal_cost,
+            proposal_expiration: old.proposal_expiration,
+            vote_mode: old.vote_mode,
+            proposal_reward_treasury_allocation: old.proposal_reward_treasury_allocation,
+            max_proposal_reward_treasury_allocation: old.max_proposal_reward_treasury_allocation,
+            proposal_reward_interval: old.proposal_reward_interval,
+            ..Default::default()
+        }
+    }
+
+    /// Translates the parameters carried by a proposal. Parameters the proposal could not have
+    /// set keep their current value, so accepting it leaves them unchanged.
+    pub fn migrate_proposal_data<T: Config>(data: old_storage::ProposalData<T>) -> ProposalData<T> {
+        use old_storage::ProposalData as Old;
+
+        match data {
+            Old::GlobalCustom => ProposalData::GlobalCustom,
+            Old::GlobalParams(old) => {
+                let current = PalletSubspace::<T>::global_params();
+                ProposalData::GlobalParams(pallet_subspace::GlobalParams {
+                    max_name_length: old.max_name_length,
+                    min_name_length: old.min_name_length,
+                    max_allowed_subnets: old.max_allowed_subnets,
+                    max_allowed_modules: old.max_allowed_modules,
+                    max_registrations_per_block: old.max_registrations_per_block,
+                    max_allowed_weights: old.max_allowed_weights,
+                    floor_delegation_fee: old.floor_delegation_fee,
+                    floor_founder_share: old.floor_founder_share,
+                    min_weight_stake: old.min_weight_stake,
+                    curator: old.curator,
+                    general_subnet_application_cost: old.general_subnet_application_cost,
+                    subnet_stake_threshold: old.subnet_stake_threshold,
+                    burn_config: pallet_subspace::global::BurnConfiguration {
+                        min_burn: old.burn_config.min_burn,
+                        max_burn: old.burn_config.max_burn,
+                        ..current.burn_config
+                    },
+                    governance_config: migrate_governance_config(old.governance_config),
+                    ..current
+                })
+            }
+            Old::SubnetCustom { subnet_id } => ProposalData::SubnetCustom { subnet_id },
+            Old::SubnetParams { subnet_id, params } => ProposalData::SubnetParams {
+                subnet_id,
+                params: pallet_subspace::SubnetParams {
+                    founder: params.founder,
+                    founder_share: params.founder_share,
+                    immunity_period: params.immunity_period,
+                    incentive_ratio: params.incentive_ratio,
+                    max_allowed_uids: params.max_allowed_uids,
+                    max_allowed_weights: params.max_allowed_weights,
+                    min_allowed_weights: params.min_allowed_weights,
+                    max_weight_age: params.max_weight_age,
+                    min_stake: params.min_stake,
+                    name: params.name,
+                    tempo: params.tempo,
+                    trust_ratio: params.trust_ratio,
+                    maximum_set_weight_calls_per_epoch: params.maximum_set_weight_calls_per_epoch,
+                    bonds_ma: params.bonds_ma,
+                    target_registrations_interval: params.target_registrations_interval,
+                    target_registrations_per_interval: params.target_registrations_per_interval,
+                    max_registrations_per_interval: params.max_registrations_per_interval,
+                    adjustment_alpha: params.adjustment_alpha,
+                    governance_config: migrate_governance_config(params.governance_config),
+                    ..PalletSubspace::<T>::subnet_params(subnet_id)
+                },
+            },
+            Old::TransferDaoTreasury { account, amount } => {
+                ProposalData::TransferDaoTreasury { account, amount }
+            }
+        }
+    }
+}
+
 #[derive(Default)]
 #[doc(hidden)]
 pub struct _MigrationV1<T>(PhantomData<T>);
 
 impl<T: Config + pallet_subspace::Config> UncheckedOnRuntimeUpgrade for _MigrationV1<T> {
     fn on_runtime_upgrade() -> frame_support::weights::Weight {
-        // TODO: add migrations
-        frame_support::weights::Weight::zero()
+        // The configurations are migrated first, the params proposals are filled with them.
+        let _ = GlobalGovernanceConfig::<T>::translate(
+            |old: Option<v1::old_storage::GovernanceConfiguration>| {
+                old.map(v1::migrate_governance_config)
+            },
+        );
+        let mut configs = 1u64;
+        SubnetGovernanceConfig::<T>::translate(
+            |_, old: v1::old_storage::GovernanceConfiguration| {
+                configs = configs.saturating_add(1);
+                Some(v1::migrate_governance_config(old))
+            },
+        );
+        log::info!("Migrated {configs} governance configurations to v1");
+
+        let mut proposals = 0u64;
+        Proposals::<T>::translate(|_, old: v1::old_storage::Proposal<T>| {
+            proposals = proposals.saturating_add(1);
+            Some(Proposal {
+                id: old.id,
+                proposer: old.proposer,
+                expiration_block: old.expiration_block,
+                data: v1::migrate_proposal_data::<T>(old.data),
+                status: old.status,
+                metadata: old.metadata,
+                proposal_cost: old.proposal_cost,
+                creation_block: old.creation_block,
+            })
+        });
+        log::info!("Migrated {proposals} proposals to v1");
+
+        let mut unrewarded = 0u64;
+        UnrewardedProposals::<T>::translate(|_, old: v1::old_storage::UnrewardedProposal<T>| {
+            unrewarded = unrewarded.saturating_add(1);
+            Some(UnrewardedProposal {
+                subnet_id: old.subnet_id,
+                block: old.block,
+                votes_for: v1::migrate_votes::<T>(old.votes_for),
+                votes_against: v1::migrate_votes::<T>(old.votes_against),
+            })
+        });
+        log::info!("Migrated {unrewarded} unrewarded proposals to v1");
+
+        let translated = configs.saturating_add(proposals).saturating_add(unrewarded);
+        T::DbWeight::get().reads_writes(translated, translated)
     }
 }
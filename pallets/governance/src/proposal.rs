@@ -1,10 +1,16 @@
-use crate::*;
+use crate::{
+    voting::{conviction_multiplier, MAX_DELEGATION_DEPTH},
+    *,
+};
 use frame_support::{
-    dispatch::DispatchResult,
+    dispatch::{DispatchClass, DispatchResult, GetDispatchInfo},
     ensure,
-    sp_runtime::{DispatchError, SaturatedConversion},
+    sp_runtime::{
+        traits::{Dispatchable, IntegerSquareRoot},
+        DispatchError, SaturatedConversion,
+    },
     storage::with_storage_layer,
-    traits::ConstU32,
+    traits::{ConstU32, Contains, Get},
     BoundedBTreeMap, BoundedBTreeSet, BoundedVec, DebugNoBound,
 };
 use frame_system::ensure_signed;
@@ -14,11 +20,28 @@ use pallet_subspace::{
 };
 use parity_scale_codec::{Decode, Encode, MaxEncodedLen};
 use scale_info::TypeInfo;
-use sp_std::{collections::btree_set::BTreeSet, vec::Vec};
+use sp_std::{
+    collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+    vec::Vec,
+};
 use substrate_fixed::types::I92F36;
 
 pub type ProposalId = u64;
 
+/// Maximum size of the encoded call stored in a runtime call proposal. Runtime upgrades only
+/// carry the hash of the new code, so they fit well within it.
+pub const MAX_PROPOSAL_CALL_SIZE: u32 = 64 * 1024;
+
+/// Maximum amount of proposals enacted in a single block. Proposals accepted once a block is full
+/// are enacted on the next block with room left.
+pub const MAX_ENACTMENTS_PER_BLOCK: u32 = 64;
+
+/// Decides how much stake a runtime call proposal needs in order to pass, depending on the
+/// call it dispatches.
+pub trait CallStakeThreshold<Call> {
+    fn required_stake(call: &Call) -> Percent;
+}
+
 #[derive(DebugNoBound, TypeInfo, Decode, Encode, MaxEncodedLen)]
 #[scale_info(skip_type_params(T))]
 pub struct Proposal<T: Config> {
@@ -49,10 +72,142 @@ impl<T: Config> Proposal<T> {
         }
     }
 
-    /// Marks a proposal as accepted and overrides the storage value.
+    /// Summarizes the proposal for the runtime API. Voters are only listed while the proposal is
+    /// open, the stake for and against is the one it was decided with.
+    #[must_use]
+    pub fn info(&self) -> ProposalInfo<T::AccountId> {
+        let (status, votes_for, votes_against, stake_for, stake_against) = match &self.status {
+            ProposalStatus::Open {
+                votes_for,
+                votes_against,
+                stake_for,
+                stake_against,
+            } => (
+                ProposalState::Open,
+                votes_for.iter().cloned().collect(),
+                votes_against.iter().cloned().collect(),
+                *stake_for,
+                *stake_against,
+            ),
+            ProposalStatus::Accepted {
+                stake_for,
+                stake_against,
+                ..
+            } => (
+                ProposalState::Accepted,
+                Vec::new(),
+                Vec::new(),
+                *stake_for,
+                *stake_against,
+            ),
+            ProposalStatus::Refused {
+                stake_for,
+                stake_against,
+                ..
+            } => (
+                ProposalState::Refused,
+                Vec::new(),
+                Vec::new(),
+                *stake_for,
+                *stake_against,
+            ),
+            ProposalStatus::Expired => (ProposalState::Expired, Vec::new(), Vec::new(), 0, 0),
+            ProposalStatus::Enacting {
+                stake_for,
+                stake_against,
+                ..
+            } => (
+                ProposalState::Enacting,
+                Vec::new(),
+                Vec::new(),
+                *stake_for,
+                *stake_against,
+            ),
+            ProposalStatus::Canceled { .. } => {
+                (ProposalState::Canceled, Vec::new(), Vec::new(), 0, 0)
+            }
+            ProposalStatus::EnactmentFailed {
+                stake_for,
+                stake_against,
+                ..
+            } => (
+                ProposalState::EnactmentFailed,
+                Vec::new(),
+                Vec::new(),
+                *stake_for,
+                *stake_against,
+            ),
+        };
+
+        let kind = match &self.data {
+            ProposalData::GlobalCustom => ProposalKind::GlobalCustom,
+            ProposalData::GlobalParams(_) => ProposalKind::GlobalParams,
+            ProposalData::SubnetCustom { .. } => ProposalKind::SubnetCustom,
+            ProposalData::SubnetParams { .. } => ProposalKind::SubnetParams,
+            ProposalData::TransferDaoTreasury { .. } => ProposalKind::TransferDaoTreasury,
+            ProposalData::RuntimeCall { .. } => ProposalKind::RuntimeCall,
+        };
+
+        ProposalInfo {
+            id: self.id,
+            proposer: self.proposer.clone(),
+            subnet_id: self.subnet_id(),
+            kind,
+            data: self.data.encode(),
+            metadata: self.metadata.to_vec(),
+            status,
+            proposal_cost: self.proposal_cost,
+            creation_block: self.creation_block,
+            expiration_block: self.expiration_block,
+            votes_for,
+            votes_against,
+            stake_for,
+            stake_against,
+        }
+    }
+
+    /// The governance configuration of the subnet this proposal impacts, or the global one.
+    #[must_use]
+    pub fn governance_config(&self) -> GovernanceConfiguration {
+        match self.subnet_id() {
+            Some(subnet_id) => SubnetGovernanceConfig::<T>::get(subnet_id),
+            None => GlobalGovernanceConfig::<T>::get(),
+        }
+    }
+
+    /// The amount of blocks an accepted proposal waits before being enacted.
+    #[must_use]
+    pub fn enactment_delay(&self) -> u64 {
+        self.governance_config().proposal_enactment_delay
+    }
+
+    /// Marks a proposal as accepted and overrides the storage value. The proposal is executed
+    /// right away if there is no enactment delay, otherwise it is queued for enactment.
     pub fn accept(mut self, block: u64, stake_for: u64, stake_against: u64) -> DispatchResult {
         ensure!(self.is_active(), Error::<T>::ProposalIsFinished);
 
+        let delay = match self.enactment_delay() {
+            // A call the current block can't cover waits for the next one.
+            0 if !self.fits_in_block() => 1,
+            delay => delay,
+        };
+        if delay > 0 {
+            let enactment_block = queue_enactment::<T>(self.id, block.saturating_add(delay));
+            self.status = ProposalStatus::Enacting {
+                block,
+                enactment_block,
+                stake_for,
+                stake_against,
+                cancel_votes: BoundedBTreeSet::new(),
+            };
+
+            Proposals::<T>::insert(self.id, &self);
+            Pallet::<T>::deposit_event(Event::ProposalAccepted(self.id));
+            Pallet::<T>::deposit_event(Event::ProposalEnacting(self.id, enactment_block));
+
+            return Ok(());
+        }
+
         self.status = ProposalStatus::Accepted {
             block,
             stake_for,
@@ -62,17 +217,139 @@ impl<T: Config> Proposal<T> {
         Proposals::<T>::insert(self.id, &self);
         Pallet::<T>::deposit_event(Event::ProposalAccepted(self.id));
 
+        self.refund_proposal_cost();
         self.execute_proposal()?;
 
         Ok(())
     }
 
-    fn execute_proposal(self) -> DispatchResult {
+    /// Executes a proposal whose enactment delay has ended and marks it as accepted.
+    pub fn enact(mut self, block_number: u64) -> DispatchResult {
+        let ProposalStatus::Enacting {
+            block,
+            enactment_block,
+            stake_for,
+            stake_against,
+            ..
+        } = self.status
+        else {
+            return Err(Error::<T>::ProposalNotEnacting.into());
+        };
+        ensure!(
+            block_number >= enactment_block,
+            Error::<T>::InvalidProposalFinalizationParameters
+        );
+
+        self.status = ProposalStatus::Accepted {
+            block,
+            stake_for,
+            stake_against,
+        };
+
+        Proposals::<T>::insert(self.id, &self);
+        dequeue_enactment::<T>(self.id, enactment_block);
+        Pallet::<T>::deposit_event(Event::ProposalEnacted(self.id));
+
+        self.refund_proposal_cost();
+        self.execute_proposal()?;
+
+        Ok(())
+    }
+
+    /// Moves the enactment of a proposal to the next block with room left, as the current block
+    /// can't cover its call.
+    pub fn defer_enactment(mut self, block_number: u64) -> DispatchResult {
+        let ProposalStatus::Enacting {
+            enactment_block, ..
+        } = &mut self.status
+        else {
+            return Err(Error::<T>::ProposalNotEnacting.into());
+        };
+
+        dequeue_enactment::<T>(self.id, *enactment_block);
+        *enactment_block = queue_enactment::<T>(self.id, block_number.saturating_add(1));
+        let enactment_block = *enactment_block;
+
+        Proposals::<T>::insert(self.id, &self);
+        Pallet::<T>::deposit_event(Event::ProposalEnacting(self.id, enactment_block));
+
+        Ok(())
+    }
+
+    /// Whether what is left of the current block can cover the call of a runtime call proposal.
+    /// Calls that can't be decoded fit, as they fail once executed.
+    fn fits_in_block(&self) -> bool {
+        let ProposalData::RuntimeCall { .. } = self.data else {
+            return true;
+        };
+        let Some(call) = ProposalCalls::<T>::get(self.id)
+            .and_then(|call| T::ProposalCall::decode(&mut &call[..]).ok())
+        else {
+            return true;
+        };
+
+        let max_block = <T as frame_system::Config>::BlockWeights::get().max_block;
+        let consumed = frame_system::Pallet::<T>::block_weight().total();
+        max_block.saturating_sub(consumed).all_gte(call.get_dispatch_info().weight)
+    }
+
+    /// Marks a proposal whose enactment failed and removes it from the enactment queue.
+    pub fn fail_enactment(mut self, block_number: u64, error: DispatchError) -> DispatchResult {
+        let ProposalStatus::Enacting {
+            enactment_block,
+            stake_for,
+            stake_against,
+            ..
+        } = self.status
+        else {
+            return Err(Error::<T>::ProposalNotEnacting.into());
+        };
+
+        self.status = ProposalStatus::EnactmentFailed {
+            block: block_number,
+            stake_for,
+            stake_against,
+        };
+
+        Proposals::<T>::insert(self.id, &self);
+        dequeue_enactment::<T>(self.id, enactment_block);
+        ProposalCalls::<T>::remove(self.id);
+        Pallet::<T>::deposit_event(Event::ProposalEnactmentFailed(self.id, error));
+
+        Ok(())
+    }
+
+    /// Cancels a proposal that is waiting for its enactment and overrides the storage value. The
+    /// proposal cost is not refunded.
+    pub fn cancel(mut self, block_number: u64) -> DispatchResult {
+        let ProposalStatus::Enacting {
+            enactment_block, ..
+        } = self.status
+        else {
+            return Err(Error::<T>::ProposalNotEnacting.into());
+        };
+
+        self.status = ProposalStatus::Canceled {
+            block: block_number,
+        };
+
+        Proposals::<T>::insert(self.id, &self);
+        dequeue_enactment::<T>(self.id, enactment_block);
+        ProposalCalls::<T>::remove(self.id);
+        Pallet::<T>::deposit_event(Event::ProposalCanceled(self.id));
+
+        Ok(())
+    }
+
+    /// Gives the proposal cost back to the proposer once the proposal is executed.
+    fn refund_proposal_cost(&self) {
         PalletSubspace::<T>::add_balance_to_account(
             &self.proposer,
             PalletSubspace::<T>::u64_to_balance(self.proposal_cost).unwrap(),
         );
+    }
 
+    fn execute_proposal(self) -> DispatchResult {
         match self.data {
             ProposalData::GlobalCustom | ProposalData::SubnetCustom { .. } => {
                 // No specific action needed for custom proposals
@@ -94,6 +371,31 @@ impl<T: Config> Proposal<T> {
                     amount,
                 )?;
             }
+            ProposalData::RuntimeCall { .. } => {
+                let call = ProposalCalls::<T>::take(self.id)
+                    .and_then(|call| T::ProposalCall::decode(&mut &call[..]).ok())
+                    .ok_or(Error::<T>::InvalidProposalCall)?;
+                let info = call.get_dispatch_info();
+                let origin = if T::RootCalls::contains(&call) {
+                    frame_system::RawOrigin::Root.into()
+                } else {
+                    RawOrigin::Governance.into()
+                };
+                let result = call.dispatch(origin);
+
+                // The call runs inside `on_initialize`, so its weight is added to the block here.
+                let post_info = match &result {
+                    Ok(post_info) => *post_info,
+                    Err(err) => err.post_info,
+                };
+                frame_system::Pallet::<T>::register_extra_weight_unchecked(
+                    post_info.calc_actual_weight(&info),
+                    DispatchClass::Mandatory,
+                );
+
+                let result = result.map(|_| ()).map_err(|err| err.error);
+                Pallet::<T>::deposit_event(Event::ProposalCallDispatched(self.id, result));
+            }
         }
 
         Ok(())
@@ -110,6 +412,7 @@ impl<T: Config> Proposal<T> {
         };
 
         Proposals::<T>::insert(self.id, &self);
+        ProposalCalls::<T>::remove(self.id);
         Pallet::<T>::deposit_event(Event::ProposalRefused(self.id));
 
         Ok(())
@@ -126,6 +429,7 @@ impl<T: Config> Proposal<T> {
         self.status = ProposalStatus::Expired;
 
         Proposals::<T>::insert(self.id, &self);
+        ProposalCalls::<T>::remove(self.id);
         Pallet::<T>::deposit_event(Event::ProposalExpired(self.id));
 
         Ok(())
@@ -152,6 +456,24 @@ pub enum ProposalStatus<T: Config> {
         stake_against: u64,
     },
     Expired,
+    /// The proposal was accepted and waits until `enactment_block` to be executed.
+    Enacting {
+        block: u64,
+        enactment_block: u64,
+        stake_for: u64,
+        stake_against: u64,
+        cancel_votes: BoundedBTreeSet<T::AccountId, ConstU32<{ u32::MAX }>>,
+    },
+    /// The proposal was accepted but canceled before being enacted.
+    Canceled {
+        block: u64,
+    },
+    /// The proposal was accepted but failed to be executed at its enactment block.
+    EnactmentFailed {
+        block: u64,
+        stake_for: u64,
+        stake_against: u64,
+    },
 }
 
 #[derive(DebugNoBound, TypeInfo, Decode, Encode, MaxEncodedLen, PartialEq, Eq)]
@@ -170,6 +492,12 @@ pub enum ProposalData<T: Config> {
         account: T::AccountId,
         amount: u64,
     },
+    /// A runtime call, dispatched with the governance origin once accepted. The stake it
+    /// requires is computed when the proposal is created, and the encoded call is kept in
+    /// [`ProposalCalls`] until the proposal is finished.
+    RuntimeCall {
+        required_stake: Percent,
+    },
 }
 
 impl<T: Config> ProposalData<T> {
@@ -181,20 +509,134 @@ impl<T: Config> ProposalData<T> {
                 Percent::from_parts(50)
             }
             Self::GlobalParams(_) | Self::SubnetParams { .. } => Percent::from_parts(40),
+            Self::RuntimeCall { required_stake, .. } => *required_stake,
         }
     }
 }
 
+/// The voting power a voter carried on a proposal.
+#[derive(Clone, Copy, Debug, Default, TypeInfo, Decode, Encode, MaxEncodedLen, PartialEq, Eq)]
+pub struct VotingPower {
+    /// Power coming from the voter's own stake.
+    pub direct: u64,
+    /// Power delegated to the voter, either by staking on it or through explicit delegation.
+    pub delegated: u64,
+}
+
+impl VotingPower {
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.direct.saturating_add(self.delegated)
+    }
+}
+
 #[derive(DebugNoBound, TypeInfo, Decode, Encode, MaxEncodedLen, PartialEq, Eq)]
 #[scale_info(skip_type_params(T))]
 pub struct UnrewardedProposal<T: Config> {
     pub subnet_id: Option<SubnetId>,
     pub block: u64,
-    pub votes_for: BoundedBTreeMap<T::AccountId, u64, ConstU32<{ u32::MAX }>>,
-    pub votes_against: BoundedBTreeMap<T::AccountId, u64, ConstU32<{ u32::MAX }>>,
+    pub votes_for: BoundedBTreeMap<T::AccountId, VotingPower, ConstU32<{ u32::MAX }>>,
+    pub votes_against: BoundedBTreeMap<T::AccountId, VotingPower, ConstU32<{ u32::MAX }>>,
+}
+
+/// Status of a proposal summary, without the data each status carries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProposalState {
+    Open,
+    Accepted,
+    Refused,
+    Expired,
+    Enacting,
+    Canceled,
+    EnactmentFailed,
+}
+
+/// What a proposal changes, without the data it changes it with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProposalKind {
+    GlobalCustom,
+    GlobalParams,
+    SubnetCustom,
+    SubnetParams,
+    TransferDaoTreasury,
+    RuntimeCall,
+}
+
+/// Summary of a proposal, as the runtime API returns it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProposalInfo<AccountId> {
+    pub id: ProposalId,
+    pub proposer: AccountId,
+    /// Subnet the proposal impacts, `None` for global proposals.
+    pub subnet_id: Option<SubnetId>,
+    pub kind: ProposalKind,
+    /// SCALE encoded proposal data, as stored in the pallet.
+    pub data: Vec<u8>,
+    pub metadata: Vec<u8>,
+    pub status: ProposalState,
+    pub proposal_cost: u64,
+    pub creation_block: u64,
+    pub expiration_block: u64,
+    pub votes_for: Vec<AccountId>,
+    pub votes_against: Vec<AccountId>,
+    /// Stake for and against as of the last tick, or when the proposal was decided.
+    pub stake_for: u64,
+    pub stake_against: u64,
+}
+
+/// How an open proposal would be decided if it was ticked with the current stake.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProposalTally {
+    /// Stake for the proposal, weighted by the vote mode.
+    pub stake_for: u64,
+    /// Stake against the proposal, weighted by the vote mode.
+    pub stake_against: u64,
+    /// Raw stake of every voter, compared against `minimal_stake_to_execute`.
+    pub voted_stake: u64,
+    pub minimal_stake_to_execute: u64,
 }
 
 impl<T: Config> Pallet<T> {
+    /// Returns the proposals that impact the given subnet, or every proposal, ordered by id.
+    pub fn get_proposals(subnet_id: Option<SubnetId>) -> Vec<Proposal<T>> {
+        let mut proposals: Vec<_> = Proposals::<T>::iter_values()
+            .filter(|proposal| subnet_id.is_none() || proposal.subnet_id() == subnet_id)
+            .collect();
+        proposals.sort_by_key(|proposal| proposal.id);
+        proposals
+    }
+
+    /// Returns the summaries of the proposals with the given status that impact the given subnet,
+    /// or of every proposal when a filter is `None`, ordered by id.
+    pub fn get_proposal_infos(
+        status: Option<ProposalState>,
+        subnet_id: Option<SubnetId>,
+    ) -> Vec<ProposalInfo<T::AccountId>> {
+        Self::get_proposals(subnet_id)
+            .iter()
+            .map(Proposal::info)
+            .filter(|info| status.is_none() || status == Some(info.status))
+            .collect()
+    }
+
+    /// Projects the tally of an open proposal with the current stake, counted the same way
+    /// `tick_proposal` does. Returns `None` for unknown or finished proposals.
+    pub fn proposal_tally(proposal_id: ProposalId) -> Option<ProposalTally> {
+        let proposal = Proposals::<T>::get(proposal_id)?;
+        let not_delegating = NotDelegatingVotingPower::<T>::get().into_inner();
+        let tally = tally_votes(&not_delegating, &proposal).ok()?;
+
+        Some(ProposalTally {
+            stake_for: tally.stake_for,
+            stake_against: tally.stake_against,
+            voted_stake: tally.raw_stake,
+            minimal_stake_to_execute: get_minimal_stake_to_execute_with_percentage::<T>(
+                proposal.data.required_stake(),
+                proposal.subnet_id(),
+            ),
+        })
+    }
+
     fn get_next_proposal_id() -> u64 {
         match Proposals::<T>::iter_keys().max() {
             Some(id) => id.saturating_add(1),
@@ -330,6 +772,38 @@ impl<T: Config> Pallet<T> {
         Self::add_proposal(key, BoundedVec::truncate_from(data), proposal_data)
     }
 
+    pub fn do_add_runtime_call_proposal(
+        origin: T::RuntimeOrigin,
+        data: Vec<u8>,
+        call: T::ProposalCall,
+    ) -> DispatchResult {
+        let key = ensure_signed(origin)?;
+
+        ensure!(!data.is_empty(), Error::<T>::ProposalDataTooSmall);
+        ensure!(data.len() <= 256, Error::<T>::ProposalDataTooLarge);
+
+        let block_weights = <T as frame_system::Config>::BlockWeights::get();
+        let max_call_weight = block_weights
+            .get(DispatchClass::Normal)
+            .max_extrinsic
+            .unwrap_or(block_weights.max_block);
+        ensure!(
+            call.get_dispatch_info().weight.all_lte(max_call_weight),
+            Error::<T>::ProposalCallTooHeavy
+        );
+
+        let required_stake = T::CallStakeThreshold::required_stake(&call);
+        let call: BoundedVec<_, _> =
+            BoundedVec::try_from(call.encode()).map_err(|_| Error::<T>::ProposalCallTooLarge)?;
+
+        let proposal_id = Self::get_next_proposal_id();
+        let proposal_data = ProposalData::RuntimeCall { required_stake };
+        Self::add_proposal(key, BoundedVec::truncate_from(data), proposal_data)?;
+        ProposalCalls::<T>::insert(proposal_id, call);
+
+        Ok(())
+    }
+
     pub fn do_add_subnet_params_proposal(
         origin: T::RuntimeOrigin,
         subnet_id: u16,
@@ -339,10 +813,7 @@ impl<T: Config> Pallet<T> {
         let key = ensure_signed(origin)?;
 
         ensure!(
-            matches!(
-                SubnetGovernanceConfig::<T>::get(subnet_id).vote_mode,
-                VoteMode::Vote
-            ),
+            SubnetGovernanceConfig::<T>::get(subnet_id).vote_mode.is_voting(),
             Error::<T>::NotVoteMode
         );
 
@@ -358,14 +829,13 @@ impl<T: Config> Pallet<T> {
 }
 
 pub fn tick_proposals<T: Config>(block_number: u64) {
-    let not_delegating = NotDelegatingVotingPower::<T>::get().into_inner();
-
-    let proposals = Proposals::<T>::iter().filter(|(_, p)| p.is_active());
-
     if block_number % 100 != 0 {
         return;
     }
 
+    let not_delegating = NotDelegatingVotingPower::<T>::get().into_inner();
+    let proposals = Proposals::<T>::iter().filter(|(_, p)| p.is_active());
+
     for (id, proposal) in proposals {
         let res = with_storage_layer(|| tick_proposal(&not_delegating, block_number, proposal));
         if let Err(err) = res {
@@ -391,11 +861,22 @@ pub fn get_minimal_stake_to_execute_with_percentage<T: Config>(
         .unwrap_or(0) as u64
 }
 
-fn tick_proposal<T: Config>(
+/// The stake for and against an open proposal, as counted when it is ticked.
+struct VoteTally<T: Config> {
+    votes_for: Vec<(T::AccountId, VotingPower)>,
+    votes_against: Vec<(T::AccountId, VotingPower)>,
+    /// Stake for the proposal, weighted by the vote mode.
+    stake_for: u64,
+    /// Stake against the proposal, weighted by the vote mode.
+    stake_against: u64,
+    /// Raw stake of every voter, which is what the quorum counts.
+    raw_stake: u64,
+}
+
+fn tally_votes<T: Config>(
     not_delegating: &BTreeSet<T::AccountId>,
-    block_number: u64,
-    mut proposal: Proposal<T>,
-) -> DispatchResult {
+    proposal: &Proposal<T>,
+) -> Result<VoteTally<T>, DispatchError> {
     let subnet_id = proposal.subnet_id();
 
     let ProposalStatus::Open {
@@ -407,25 +888,65 @@ fn tick_proposal<T: Config>(
         return Err(Error::<T>::ProposalIsFinished.into());
     };
 
-    let votes_for: Vec<(T::AccountId, u64)> = votes_for
+    let voters: BTreeSet<T::AccountId> = votes_for.iter().chain(votes_against).cloned().collect();
+    let powers = compute_voting_powers::<T>(not_delegating, &voters, subnet_id);
+
+    let votes_for: Vec<(T::AccountId, VotingPower)> = votes_for
         .iter()
-        .cloned()
-        .map(|id| {
-            let stake = calc_stake::<T>(not_delegating, &id, subnet_id);
-            (id, stake)
-        })
+        .map(|id| (id.clone(), powers.get(id).copied().unwrap_or_default()))
         .collect();
-    let votes_against: Vec<(T::AccountId, u64)> = votes_against
+    let votes_against: Vec<(T::AccountId, VotingPower)> = votes_against
         .iter()
-        .cloned()
-        .map(|id| {
-            let stake = calc_stake::<T>(not_delegating, &id, subnet_id);
-            (id, stake)
-        })
+        .map(|id| (id.clone(), powers.get(id).copied().unwrap_or_default()))
         .collect();
 
-    let stake_for_sum: u64 = votes_for.iter().map(|(_, stake)| stake).sum();
-    let stake_against_sum: u64 = votes_against.iter().map(|(_, stake)| stake).sum();
+    // The outcome is decided by the weighted votes, while the quorum counts the raw stake.
+    let vote_mode = proposal.governance_config().vote_mode;
+    let proposal_id = proposal.id;
+    let weigh = |(id, power): &(T::AccountId, VotingPower)| -> u64 {
+        let stake = power.total();
+        match vote_mode {
+            VoteMode::Quadratic => stake.integer_sqrt(),
+            VoteMode::Conviction => {
+                // Only the stake locked by the vote is multiplied, not the stake delegated to the
+                // voter or added after voting.
+                let (locked, conviction) = StakeLocks::<T>::get(id, proposal_id)
+                    .map_or((0, 0), |lock| (lock.amount.min(stake), lock.conviction));
+                stake
+                    .saturating_sub(locked)
+                    .saturating_add(locked.saturating_mul(conviction_multiplier(conviction)))
+            }
+            VoteMode::Authority | VoteMode::Vote => stake,
+        }
+    };
+
+    let stake_for = votes_for.iter().map(weigh).sum();
+    let stake_against = votes_against.iter().map(weigh).sum();
+    let raw_stake = votes_for.iter().chain(&votes_against).map(|(_, power)| power.total()).sum();
+
+    Ok(VoteTally {
+        votes_for,
+        votes_against,
+        stake_for,
+        stake_against,
+        raw_stake,
+    })
+}
+
+fn tick_proposal<T: Config>(
+    not_delegating: &BTreeSet<T::AccountId>,
+    block_number: u64,
+    mut proposal: Proposal<T>,
+) -> DispatchResult {
+    let subnet_id = proposal.subnet_id();
+
+    let VoteTally {
+        votes_for,
+        votes_against,
+        stake_for: stake_for_sum,
+        stake_against: stake_against_sum,
+        raw_stake: raw_stake_sum,
+    } = tally_votes(not_delegating, &proposal)?;
 
     if block_number < proposal.expiration_block {
         if let ProposalStatus::Open {
@@ -441,7 +962,6 @@ fn tick_proposal<T: Config>(
         return Ok(());
     }
 
-    let total_stake = stake_for_sum.saturating_add(stake_against_sum);
     let minimal_stake_to_execute = get_minimal_stake_to_execute_with_percentage::<T>(
         proposal.data.required_stake(),
         subnet_id,
@@ -452,8 +972,11 @@ fn tick_proposal<T: Config>(
         reward_votes_for.try_insert(key, value).expect("this wont exceed u32::MAX");
     }
 
-    let mut reward_votes_against: BoundedBTreeMap<T::AccountId, u64, ConstU32<{ u32::MAX }>> =
-        BoundedBTreeMap::new();
+    let mut reward_votes_against: BoundedBTreeMap<
+        T::AccountId,
+        VotingPower,
+        ConstU32<{ u32::MAX }>,
+    > = BoundedBTreeMap::new();
     for (key, value) in votes_against {
         reward_votes_against
             .try_insert(key, value)
@@ -470,7 +993,7 @@ fn tick_proposal<T: Config>(
         },
     );
 
-    if total_stake >= minimal_stake_to_execute {
+    if raw_stake_sum >= minimal_stake_to_execute {
         if stake_against_sum > stake_for_sum {
             proposal.refuse(block_number, stake_for_sum, stake_against_sum)
         } else {
@@ -481,6 +1004,85 @@ fn tick_proposal<T: Config>(
     }
 }
 
+/// Computes the voting power of each voter, including the stake delegated to them.
+pub(crate) fn compute_voting_powers<T: Config>(
+    not_delegating: &BTreeSet<T::AccountId>,
+    voters: &BTreeSet<T::AccountId>,
+    subnet_id: Option<SubnetId>,
+) -> BTreeMap<T::AccountId, VotingPower> {
+    let delegations = Pallet::<T>::voting_power_delegations(subnet_id);
+
+    let mut powers: BTreeMap<T::AccountId, VotingPower> = voters
+        .iter()
+        .map(|id| {
+            let power = calc_stake::<T>(not_delegating, &delegations, id, subnet_id);
+            (id.clone(), power)
+        })
+        .collect();
+
+    // Explicit delegators that did not vote themselves pass their stake down the chain.
+    for delegator in delegations.keys().filter(|delegator| !voters.contains(*delegator)) {
+        let Some(voter) = resolve_delegated_voter(&delegations, voters, delegator) else {
+            continue;
+        };
+
+        let stake = PalletSubspace::<T>::get_account_stake(delegator, subnet_id);
+        if let Some(power) = powers.get_mut(voter) {
+            power.delegated = power.delegated.saturating_add(stake);
+        }
+    }
+
+    powers
+}
+
+/// Queues a proposal for enactment on the first block from `enactment_block` that still has
+/// room, returning that block.
+fn queue_enactment<T: Config>(id: ProposalId, enactment_block: u64) -> u64 {
+    let mut enactment_block = enactment_block;
+    while EnactmentQueue::<T>::try_mutate(enactment_block, |ids| {
+        ids.get_or_insert_with(BoundedVec::new).try_push(id)
+    })
+    .is_err()
+    {
+        enactment_block = enactment_block.saturating_add(1);
+    }
+
+    enactment_block
+}
+
+fn dequeue_enactment<T: Config>(id: ProposalId, enactment_block: u64) {
+    EnactmentQueue::<T>::mutate_exists(enactment_block, |ids| {
+        if let Some(queued) = ids {
+            queued.retain(|queued| *queued != id);
+            if queued.is_empty() {
+                *ids = None;
+            }
+        }
+    });
+}
+
+/// Executes the accepted proposals whose enactment delay ends on this block.
+pub fn tick_enactments<T: Config>(block_number: u64) {
+    let due = EnactmentQueue::<T>::take(block_number).unwrap_or_default();
+
+    for id in due {
+        let res = with_storage_layer(|| {
+            let proposal = Proposals::<T>::get(id).ok_or(Error::<T>::ProposalNotFound)?;
+            if !proposal.fits_in_block() {
+                return proposal.defer_enactment(block_number);
+            }
+            proposal.enact(block_number)
+        });
+        if let Err(err) = res {
+            log::error!("failed to enact proposal {id}: {err:?}");
+
+            if let Some(proposal) = Proposals::<T>::get(id) {
+                let _ = proposal.fail_enactment(block_number, err);
+            }
+        }
+    }
+}
+
 pub fn tick_proposal_rewards<T: Config>(block_number: u64) {
     let mut to_tick: Vec<_> = pallet_subspace::N::<T>::iter_keys()
         .map(|subnet_id| (Some(subnet_id), SubnetGovernanceConfig::<T>::get(subnet_id)))
@@ -492,32 +1094,54 @@ pub fn tick_proposal_rewards<T: Config>(block_number: u64) {
     });
 }
 
+/// Follows the explicit delegations starting at `delegator` until it reaches an account that
+/// voted, giving up after [`MAX_DELEGATION_DEPTH`] hops.
+fn resolve_delegated_voter<'a, A: Ord>(
+    delegations: &'a BTreeMap<A, A>,
+    voters: &BTreeSet<A>,
+    delegator: &A,
+) -> Option<&'a A> {
+    let mut current = delegations.get(delegator)?;
+    for _ in 0..MAX_DELEGATION_DEPTH {
+        if voters.contains(current) {
+            return Some(current);
+        }
+        current = delegations.get(current)?;
+    }
+    None
+}
+
 fn calc_stake<T: Config>(
     not_delegating: &BTreeSet<T::AccountId>,
+    delegations: &BTreeMap<T::AccountId, T::AccountId>,
     voter: &T::AccountId,
     subnet_id: Option<SubnetId>,
-) -> u64 {
-    let own_stake = if !not_delegating.contains(voter) {
+) -> VotingPower {
+    // Voting overrides an explicit delegation, so the voter keeps its own stake.
+    let direct = if !not_delegating.contains(voter) && !delegations.contains_key(voter) {
         0
     } else {
         PalletSubspace::<T>::get_account_stake(voter, subnet_id)
     };
 
+    // Stakers that delegate explicitly are accounted for through their delegation chain.
     let calculate_delegated = |subnet_id: u16| -> u64 {
         PalletSubspace::<T>::get_stake_from_vector(subnet_id, voter)
             .into_iter()
-            .filter(|(staker, _)| !not_delegating.contains(staker))
+            .filter(|(staker, _)| {
+                !not_delegating.contains(staker) && !delegations.contains_key(staker)
+            })
             .map(|(_, stake)| stake)
             .sum()
     };
 
-    let delegated_stake = if let Some(subnet_id) = subnet_id {
+    let delegated = if let Some(subnet_id) = subnet_id {
         calculate_delegated(subnet_id)
     } else {
         pallet_subspace::N::<T>::iter_keys().map(calculate_delegated).sum()
     };
 
-    own_stake.saturating_add(delegated_stake)
+    VotingPower { direct, delegated }
 }
 
 pub fn execute_proposal_rewards<T: Config>(
@@ -547,14 +1171,14 @@ pub fn execute_proposal_rewards<T: Config>(
             continue;
         }
 
-        for (acc_id, stake) in unrewarded_proposal
+        for (acc_id, power) in unrewarded_proposal
             .votes_for
             .into_iter()
             .chain(unrewarded_proposal.votes_against.into_iter())
         {
             let curr_stake = *account_stakes.get(&acc_id).unwrap_or(&0u64);
             account_stakes
-                .try_insert(acc_id, curr_stake.saturating_add(stake))
+                .try_insert(acc_id, curr_stake.saturating_add(power.total()))
                 .expect("infallible");
         }
 
@@ -623,8 +1247,6 @@ fn distribute_proposal_rewards<T: crate::Config>(
     account_stakes: BoundedBTreeMap<T::AccountId, u64, ConstU32<{ u32::MAX }>>,
     total_allocation: I92F36,
 ) {
-    use frame_support::sp_runtime::traits::IntegerSquareRoot;
-
     let account_sqrt_stakes: Vec<_> = account_stakes
         .into_iter()
         .map(|(acc_id, stake)| (acc_id, stake.integer_sqrt()))
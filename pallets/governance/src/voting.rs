@@ -1,7 +1,49 @@
-use crate::{proposal::ProposalStatus, *};
+use crate::{
+    proposal::{
+        compute_voting_powers, get_minimal_stake_to_execute_with_percentage, ProposalStatus,
+    },
+    *,
+};
 use frame_support::pallet_prelude::DispatchResult;
 use frame_system::ensure_signed;
 use pallet_subspace::Pallet as PalletSubspace;
+use parity_scale_codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_std::collections::{btree_map::BTreeMap, btree_set::BTreeSet};
+
+/// Maximum number of hops voting power travels through a chain of explicit delegations.
+pub const MAX_DELEGATION_DEPTH: u32 = 8;
+
+pub use pallet_governance_api::MAX_VOTING_POWER_DELEGATIONS;
+
+/// Highest conviction a voter can lock its stake with.
+pub const MAX_CONVICTION: u8 = 6;
+
+/// Stake locked by a conviction vote.
+#[derive(Clone, Copy, Debug, TypeInfo, Decode, Encode, MaxEncodedLen, PartialEq, Eq)]
+pub struct StakeLock {
+    /// The subnet the proposal belongs to, `None` for global proposals.
+    pub subnet_id: Option<SubnetId>,
+    /// The amount of stake locked.
+    pub amount: u64,
+    /// The conviction of the vote, multiplying its weight by `conviction + 1`.
+    pub conviction: u8,
+    /// The block the stake can be removed at.
+    pub unlock_block: u64,
+}
+
+/// The factor a vote is multiplied by for the given conviction.
+#[must_use]
+pub fn conviction_multiplier(conviction: u8) -> u64 {
+    u64::from(conviction).saturating_add(1)
+}
+
+/// Removes the conviction locks expiring at this block.
+pub fn clear_expired_stake_locks<T: Config>(block_number: u64) {
+    for ((key, proposal_id), ()) in StakeLockExpirations::<T>::drain_prefix(block_number) {
+        StakeLocks::<T>::remove(key, proposal_id);
+    }
+}
 
 impl<T: Config> Pallet<T> {
     /// Votes on proposals,
@@ -40,7 +82,11 @@ impl<T: Config> Pallet<T> {
                 .any(|(_, k, stakes)| k == key && !stakes.is_empty())
         };
 
-        if !NotDelegatingVotingPower::<T>::get().contains(&key) && !has_stake_from() {
+        // Explicit delegators can always vote, which overrides their delegate on this proposal.
+        if !NotDelegatingVotingPower::<T>::get().contains(&key)
+            && Self::voting_power_delegate(&key, subnet_id).is_none()
+            && !has_stake_from()
+        {
             return Err(Error::<T>::VoterIsDelegatingVotingPower.into());
         }
 
@@ -81,9 +127,332 @@ impl<T: Config> Pallet<T> {
         // Check if the voter has actually voted on the proposal
         ensure!(removed, Error::<T>::NotVoted);
 
+        // The vote no longer counts, so neither does its conviction.
+        if let Some(lock) = StakeLocks::<T>::take(&key, proposal_id) {
+            StakeLockExpirations::<T>::remove(lock.unlock_block, (&key, proposal_id));
+        }
+
         // Update the proposal in storage
         Proposals::<T>::insert(proposal.id, proposal);
         Self::deposit_event(Event::<T>::ProposalVoteUnregistered(proposal_id, key));
         Ok(())
     }
+
+    /// Votes on a proposal decided by conviction voting, locking the voter's stake for
+    /// `conviction` lock periods after the proposal expires.
+    pub fn do_vote_proposal_with_conviction(
+        origin: T::RuntimeOrigin,
+        proposal_id: u64,
+        agree: bool,
+        conviction: u8,
+    ) -> DispatchResult {
+        let key = ensure_signed(origin.clone())?;
+
+        let Ok(proposal) = Proposals::<T>::try_get(proposal_id) else {
+            return Err(Error::<T>::ProposalNotFound.into());
+        };
+
+        let config = proposal.governance_config();
+        ensure!(
+            matches!(config.vote_mode, VoteMode::Conviction),
+            Error::<T>::NotConvictionMode
+        );
+        ensure!(conviction <= MAX_CONVICTION, Error::<T>::InvalidConviction);
+
+        Self::do_vote_proposal(origin, proposal_id, agree)?;
+
+        if conviction > 0 {
+            let subnet_id = proposal.subnet_id();
+            let unlock_block = config
+                .conviction_lock_period
+                .saturating_mul(conviction.into())
+                .saturating_add(proposal.expiration_block);
+
+            StakeLocks::<T>::insert(
+                &key,
+                proposal_id,
+                StakeLock {
+                    subnet_id,
+                    amount: PalletSubspace::<T>::get_account_stake(&key, subnet_id),
+                    conviction,
+                    unlock_block,
+                },
+            );
+            StakeLockExpirations::<T>::insert(unlock_block, (&key, proposal_id), ());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the amount of stake the account has locked through conviction votes on the given
+    /// subnet, or on global proposals if `None`. Locks on the same scope don't add up.
+    pub fn get_locked_stake(staker: &T::AccountId, subnet_id: Option<SubnetId>) -> u64 {
+        let block_number = PalletSubspace::<T>::get_current_block_number();
+        StakeLocks::<T>::iter_prefix_values(staker)
+            .filter(|lock| lock.subnet_id == subnet_id && lock.unlock_block > block_number)
+            .map(|lock| lock.amount)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Cancels a proposal waiting for its enactment. Only the curator can do this.
+    pub fn do_cancel_proposal(origin: T::RuntimeOrigin, proposal_id: u64) -> DispatchResult {
+        let key = ensure_signed(origin)?;
+        ensure!(Curator::<T>::get() == key, Error::<T>::NotCurator);
+
+        let Ok(proposal) = Proposals::<T>::try_get(proposal_id) else {
+            return Err(Error::<T>::ProposalNotFound.into());
+        };
+
+        proposal.cancel(PalletSubspace::<T>::get_current_block_number())
+    }
+
+    /// Votes to cancel a proposal waiting for its enactment. The proposal is canceled as soon as
+    /// the stake voting for the cancellation reaches the stake the proposal needed to pass.
+    pub fn do_vote_cancel_proposal(origin: T::RuntimeOrigin, proposal_id: u64) -> DispatchResult {
+        let key = ensure_signed(origin)?;
+
+        let Ok(mut proposal) = Proposals::<T>::try_get(proposal_id) else {
+            return Err(Error::<T>::ProposalNotFound.into());
+        };
+
+        let subnet_id = proposal.subnet_id();
+        let ProposalStatus::Enacting { cancel_votes, .. } = &mut proposal.status else {
+            return Err(Error::<T>::ProposalNotEnacting.into());
+        };
+
+        ensure!(!cancel_votes.contains(&key), Error::<T>::AlreadyVoted);
+        ensure!(
+            PalletSubspace::<T>::get_account_stake(&key, subnet_id) > 0,
+            Error::<T>::InsufficientStake
+        );
+
+        cancel_votes
+            .try_insert(key.clone())
+            .map_err(|_| Error::<T>::InvalidProposalVotingParameters)?;
+
+        let voters: BTreeSet<T::AccountId> = cancel_votes.iter().cloned().collect();
+        let not_delegating = NotDelegatingVotingPower::<T>::get().into_inner();
+        let cancel_stake: u64 = compute_voting_powers::<T>(&not_delegating, &voters, subnet_id)
+            .values()
+            .map(|power| power.total())
+            .sum();
+        let minimal_stake = get_minimal_stake_to_execute_with_percentage::<T>(
+            proposal.data.required_stake(),
+            subnet_id,
+        );
+
+        Proposals::<T>::insert(proposal_id, &proposal);
+        Self::deposit_event(Event::<T>::ProposalCancelVoted(proposal_id, key));
+
+        if cancel_stake >= minimal_stake {
+            proposal.cancel(PalletSubspace::<T>::get_current_block_number())?;
+        }
+
+        Ok(())
+    }
+
+    /// Delegates the voting power of the caller to `delegate`, either on a single subnet or
+    /// globally. Replaces any previous delegation on the same scope.
+    pub fn do_delegate_voting_power(
+        origin: T::RuntimeOrigin,
+        subnet_id: Option<SubnetId>,
+        delegate: T::AccountId,
+    ) -> DispatchResult {
+        let key = ensure_signed(origin)?;
+
+        ensure!(key != delegate, Error::<T>::DelegatingToSelf);
+
+        let is_new = !VotingPowerDelegations::<T>::contains_key(subnet_id, &key);
+        ensure!(
+            !is_new || VotingPowerDelegationCount::<T>::get() < MAX_VOTING_POWER_DELEGATIONS,
+            Error::<T>::TooManyVotingPowerDelegations
+        );
+
+        // Every delegation is read once, a global delegation also applies to every subnet the
+        // caller did not delegate on.
+        let global: BTreeMap<_, _> =
+            VotingPowerDelegations::<T>::iter_prefix(None::<SubnetId>).collect();
+        let mut subnets: BTreeMap<SubnetId, BTreeMap<_, _>> = BTreeMap::new();
+        match subnet_id {
+            Some(subnet_id) => {
+                subnets.insert(
+                    subnet_id,
+                    VotingPowerDelegations::<T>::iter_prefix(Some(subnet_id)).collect(),
+                );
+            }
+            None => {
+                for (scope, delegator, delegate) in VotingPowerDelegations::<T>::iter() {
+                    if let Some(scope) = scope {
+                        subnets.entry(scope).or_default().insert(delegator, delegate);
+                    }
+                }
+            }
+        }
+
+        let scopes = subnet_id
+            .is_none()
+            .then(|| (None, BTreeMap::new()))
+            .into_iter()
+            .chain(subnets.into_iter().map(|(scope, delegations)| (Some(scope), delegations)));
+        for (scope, scope_delegations) in scopes {
+            let delegates_on_scope = scope_delegations.contains_key(&key);
+            let mut delegations = global.clone();
+            delegations.extend(scope_delegations);
+            if scope == subnet_id || !delegates_on_scope {
+                delegations.insert(key.clone(), delegate.clone());
+            }
+            Self::ensure_delegation_chain_fits(&delegations, &key)?;
+        }
+
+        VotingPowerDelegations::<T>::insert(subnet_id, &key, &delegate);
+        if is_new {
+            VotingPowerDelegationCount::<T>::mutate(|count| *count = count.saturating_add(1));
+        }
+        Self::deposit_event(Event::<T>::VotingPowerDelegated(key, subnet_id, delegate));
+        Ok(())
+    }
+
+    /// Makes sure the chain going through the delegation of `key` stays acyclic and within
+    /// [`MAX_DELEGATION_DEPTH`] hops, counting the delegators upstream of `key` as well as the
+    /// delegates downstream of it.
+    fn ensure_delegation_chain_fits(
+        delegations: &BTreeMap<T::AccountId, T::AccountId>,
+        key: &T::AccountId,
+    ) -> DispatchResult {
+        let mut downstream: u32 = 0;
+        let mut current = key;
+        while let Some(next) = delegations.get(current) {
+            ensure!(next != key, Error::<T>::DelegationCycle);
+            downstream = downstream.saturating_add(1);
+            ensure!(
+                downstream <= MAX_DELEGATION_DEPTH,
+                Error::<T>::DelegationChainTooLong
+            );
+            current = next;
+        }
+
+        // A delegator further than the maximum depth from `key` has one exactly at it on its way.
+        let upstream = delegations
+            .keys()
+            .filter_map(|delegator| {
+                let mut current = delegator;
+                for hops in 1..=MAX_DELEGATION_DEPTH {
+                    current = delegations.get(current)?;
+                    if current == key {
+                        return Some(hops);
+                    }
+                }
+                None
+            })
+            .max()
+            .unwrap_or_default();
+
+        ensure!(
+            upstream.saturating_add(downstream) <= MAX_DELEGATION_DEPTH,
+            Error::<T>::DelegationChainTooLong
+        );
+        Ok(())
+    }
+
+    /// Removes the explicit delegation of the caller on the given scope.
+    pub fn do_undelegate_voting_power(
+        origin: T::RuntimeOrigin,
+        subnet_id: Option<SubnetId>,
+    ) -> DispatchResult {
+        let key = ensure_signed(origin)?;
+
+        ensure!(
+            VotingPowerDelegations::<T>::contains_key(subnet_id, &key),
+            Error::<T>::NotDelegating
+        );
+
+        VotingPowerDelegations::<T>::remove(subnet_id, &key);
+        VotingPowerDelegationCount::<T>::mutate(|count| *count = count.saturating_sub(1));
+        Self::deposit_event(Event::<T>::VotingPowerUndelegated(key, subnet_id));
+        Ok(())
+    }
+
+    /// Returns the explicit delegate of the account for proposals on the given subnet, falling
+    /// back to the global delegation.
+    pub fn voting_power_delegate(
+        delegator: &T::AccountId,
+        subnet_id: Option<SubnetId>,
+    ) -> Option<T::AccountId> {
+        subnet_id
+            .and_then(|_| VotingPowerDelegations::<T>::get(subnet_id, delegator))
+            .or_else(|| VotingPowerDelegations::<T>::get(None::<SubnetId>, delegator))
+    }
+
+    /// Returns every explicit delegation that applies to proposals on the given subnet.
+    pub fn voting_power_delegations(
+        subnet_id: Option<SubnetId>,
+    ) -> BTreeMap<T::AccountId, T::AccountId> {
+        let mut delegations: BTreeMap<_, _> =
+            VotingPowerDelegations::<T>::iter_prefix(None::<SubnetId>).collect();
+        if subnet_id.is_some() {
+            delegations.extend(VotingPowerDelegations::<T>::iter_prefix(subnet_id));
+        }
+        delegations
+    }
+
+    /// Moves the voting power delegations, conviction locks and delegation setting of a rotated
+    /// module key to its new key. The new key must not take part in any delegation, so the
+    /// delegation chains keep their shape.
+    pub fn rotate_voting_key(key: &T::AccountId, new_key: &T::AccountId) -> DispatchResult {
+        let delegations: Vec<_> = VotingPowerDelegations::<T>::iter().collect();
+        ensure!(
+            delegations
+                .iter()
+                .all(|(_, delegator, delegate)| delegator != new_key && delegate != new_key),
+            Error::<T>::DelegationKeyInUse
+        );
+
+        for (scope, delegator, delegate) in delegations {
+            if &delegator == key {
+                VotingPowerDelegations::<T>::remove(scope, key);
+                VotingPowerDelegations::<T>::insert(scope, new_key, delegate);
+            } else if &delegate == key {
+                VotingPowerDelegations::<T>::insert(scope, delegator, new_key);
+            }
+        }
+
+        // Locks on the same proposal keep the latest unlock block and the lowest conviction, so
+        // the rotation can neither free stake early nor raise the weight of a vote.
+        let locks: Vec<_> = StakeLocks::<T>::drain_prefix(key).collect();
+        for (proposal_id, lock) in locks {
+            StakeLockExpirations::<T>::remove(lock.unlock_block, (key, proposal_id));
+
+            let lock = match StakeLocks::<T>::get(new_key, proposal_id) {
+                Some(existing) => {
+                    StakeLockExpirations::<T>::remove(
+                        existing.unlock_block,
+                        (new_key, proposal_id),
+                    );
+                    StakeLock {
+                        subnet_id: lock.subnet_id,
+                        amount: lock.amount.saturating_add(existing.amount),
+                        conviction: lock.conviction.min(existing.conviction),
+                        unlock_block: lock.unlock_block.max(existing.unlock_block),
+                    }
+                }
+                None => lock,
+            };
+
+            StakeLocks::<T>::insert(new_key, proposal_id, lock);
+            StakeLockExpirations::<T>::insert(lock.unlock_block, (new_key, proposal_id), ());
+        }
+
+        NotDelegatingVotingPower::<T>::try_mutate(|not_delegating| {
+            if not_delegating.remove(key) {
+                not_delegating
+                    .try_insert(new_key.clone())
+                    .map(|_| ())
+                    .map_err(|_| Error::<T>::InternalError.into())
+            } else {
+                not_delegating.remove(new_key);
+                Ok(())
+            }
+        })
+    }
 }
@@ -0,0 +1,210 @@
+use frame_support::{
+    storage::unhashed,
+    traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion, UncheckedOnRuntimeUpgrade},
+    BoundedBTreeMap, BoundedVec,
+};
+use mock::*;
+use pallet_governance::migrations::{_MigrationV1, v1::old_storage, InitialMigration, MigrationV1};
+use pallet_subspace::migrations::v11::old_storage as subspace_old_storage;
+use parity_scale_codec::Encode;
+use sp_runtime::Percent;
+
+mod mock;
+
+fn old_governance_config() -> old_storage::GovernanceConfiguration {
+    old_storage::GovernanceConfiguration {
+        proposal_cost: 1_000,
+        proposal_expiration: 200,
+        vote_mode: VoteMode::Authority,
+        proposal_reward_treasury_allocation: Percent::from_percent(5),
+        max_proposal_reward_treasury_allocation: 1_000,
+        proposal_reward_interval: 100,
+    }
+}
+
+#[test]
+fn migration_v1_translates_params_proposals() {
+    new_test_ext().execute_with(|| {
+        let params = old_storage::SubnetParams::<Test> {
+            founder: 0,
+            founder_share: 16,
+            immunity_period: 0,
+            incentive_ratio: 50,
+            max_allowed_uids: 420,
+            max_allowed_weights: 420,
+            min_allowed_weights: 1,
+            max_weight_age: 3_600,
+            min_stake: 0,
+            name: BoundedVec::truncate_from(b"migrated".to_vec()),
+            tempo: 150,
+            trust_ratio: 0,
+            maximum_set_weight_calls_per_epoch: 0,
+            bonds_ma: 900_000,
+            target_registrations_interval: 200,
+            target_registrations_per_interval: 100,
+            max_registrations_per_interval: 42,
+            adjustment_alpha: 0,
+            governance_config: old_governance_config(),
+        };
+        let proposal = old_storage::Proposal::<Test> {
+            id: 0,
+            proposer: 0,
+            expiration_block: 200,
+            data: old_storage::ProposalData::SubnetParams {
+                subnet_id: 0,
+                params,
+            },
+            status: ProposalStatus::Expired,
+            metadata: BoundedVec::default(),
+            proposal_cost: 1_000,
+            creation_block: 0,
+        };
+        unhashed::put_raw(&Proposals::<Test>::hashed_key_for(0), &proposal.encode());
+
+        _MigrationV1::<Test>::on_runtime_upgrade();
+
+        let proposal = Proposals::<Test>::get(0).expect("proposal was not migrated");
+        let ProposalData::SubnetParams {
+            subnet_id: 0,
+            params,
+        } = proposal.data
+        else {
+            panic!("proposal data changed kind");
+        };
+
+        assert_eq!(params.tempo, 150);
+        assert_eq!(params.name.into_inner(), b"migrated".to_vec());
+        assert_eq!(params.governance_config.vote_mode, VoteMode::Authority);
+        assert_eq!(params.governance_config.proposal_cost, 1_000);
+        assert_eq!(params.governance_config.proposal_enactment_delay, 0);
+        // Parameters added later keep the value of the subnet.
+        assert_eq!(
+            params.unbonding_period,
+            Subspace::subnet_params(0).unbonding_period
+        );
+        assert_eq!(params.consensus, Subspace::subnet_params(0).consensus);
+    });
+}
+
+#[test]
+fn migration_v1_keeps_unrewarded_votes_as_direct_power() {
+    new_test_ext().execute_with(|| {
+        let mut votes_for = BoundedBTreeMap::new();
+        votes_for.try_insert(0, to_nano(5)).unwrap();
+        let mut votes_against = BoundedBTreeMap::new();
+        votes_against.try_insert(1, to_nano(3)).unwrap();
+        let unrewarded = old_storage::UnrewardedProposal::<Test> {
+            subnet_id: Some(0),
+            block: 100,
+            votes_for,
+            votes_against,
+        };
+        unhashed::put_raw(
+            &UnrewardedProposals::<Test>::hashed_key_for(0),
+            &unrewarded.encode(),
+        );
+
+        _MigrationV1::<Test>::on_runtime_upgrade();
+
+        let unrewarded = UnrewardedProposals::<Test>::get(0).expect("proposal was not migrated");
+        assert_eq!(unrewarded.subnet_id, Some(0));
+        assert_eq!(unrewarded.block, 100);
+        assert_eq!(
+            unrewarded.votes_for.get(&0),
+            Some(&VotingPower {
+                direct: to_nano(5),
+                delegated: 0
+            })
+        );
+        assert_eq!(
+            unrewarded.votes_against.get(&1),
+            Some(&VotingPower {
+                direct: to_nano(3),
+                delegated: 0
+            })
+        );
+    });
+}
+
+#[test]
+fn migration_v1_keeps_governance_configurations() {
+    new_test_ext().execute_with(|| {
+        let global = old_storage::GovernanceConfiguration {
+            vote_mode: VoteMode::Vote,
+            ..old_governance_config()
+        };
+        unhashed::put_raw(
+            &GlobalGovernanceConfig::<Test>::hashed_key(),
+            &global.encode(),
+        );
+        unhashed::put_raw(
+            &SubnetGovernanceConfig::<Test>::hashed_key_for(1),
+            &old_governance_config().encode(),
+        );
+
+        _MigrationV1::<Test>::on_runtime_upgrade();
+
+        let global = GlobalGovernanceConfig::<Test>::get();
+        assert_eq!(global.vote_mode, VoteMode::Vote);
+        assert_eq!(global.proposal_cost, 1_000);
+        assert_eq!(global.proposal_expiration, 200);
+        assert_eq!(global.proposal_enactment_delay, 0);
+
+        let subnet = SubnetGovernanceConfig::<Test>::get(1);
+        assert_eq!(subnet.vote_mode, VoteMode::Authority);
+        assert_eq!(subnet.proposal_reward_interval, 100);
+        assert_eq!(
+            subnet.proposal_reward_treasury_allocation,
+            Percent::from_percent(5)
+        );
+    });
+}
+
+#[test]
+fn initial_migration_imports_in_the_current_layout() {
+    new_test_ext().execute_with(|| {
+        subspace_old_storage::ProposalCost::<Test>::put(5_000);
+        subspace_old_storage::GeneralSubnetApplicationCost::<Test>::put(1_234);
+
+        InitialMigration::<Test>::on_runtime_upgrade();
+        assert_eq!(
+            Governance::on_chain_storage_version(),
+            StorageVersion::new(1)
+        );
+
+        // The imported configuration is already current, translating it again would drop it.
+        MigrationV1::<Test>::on_runtime_upgrade();
+        assert_eq!(GlobalGovernanceConfig::<Test>::get().proposal_cost, 5_000);
+        assert_eq!(GeneralSubnetApplicationCost::<Test>::get(), 1_234);
+    });
+}
+
+#[test]
+fn initial_migration_skips_imported_chains() {
+    new_test_ext().execute_with(|| {
+        subspace_old_storage::ProposalCost::<Test>::put(5_000);
+        let global = old_storage::GovernanceConfiguration {
+            vote_mode: VoteMode::Vote,
+            ..old_governance_config()
+        };
+        unhashed::put_raw(
+            &GlobalGovernanceConfig::<Test>::hashed_key(),
+            &global.encode(),
+        );
+
+        InitialMigration::<Test>::on_runtime_upgrade();
+        assert_eq!(
+            Governance::on_chain_storage_version(),
+            StorageVersion::new(0)
+        );
+
+        MigrationV1::<Test>::on_runtime_upgrade();
+        assert_eq!(
+            Governance::on_chain_storage_version(),
+            StorageVersion::new(1)
+        );
+        let global = GlobalGovernanceConfig::<Test>::get();
+        assert_eq!(global.vote_mode, VoteMode::Vote);
+        assert_eq!(global.proposal_cost, 1_000);
+    });
+}
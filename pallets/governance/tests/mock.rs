@@ -7,7 +7,7 @@ use frame_support::{
         testing::H256,
         traits::{BlakeTwo256, IdentityLookup},
     },
-    traits::{Currency, Everything, OnFinalize, OnInitialize},
+    traits::{Contains, Currency, Everything, OnFinalize, OnInitialize},
     PalletId,
 };
 use pallet_subspace::BurnConfig;
@@ -89,6 +89,8 @@ impl frame_system::Config for Test {
 
 parameter_types! {
     pub const SubspacePalletId: PalletId = PalletId(*b"py/subsp");
+    pub const RewardHistoryDepth: u32 = 3;
+    pub const WeightHistoryDepth: u32 = 3;
 }
 
 impl pallet_subspace::Config for Test {
@@ -96,12 +98,40 @@ impl pallet_subspace::Config for Test {
     type Currency = Balances;
     type WeightInfo = ();
     type PalletId = SubspacePalletId;
+    type RewardHistoryDepth = RewardHistoryDepth;
+    type WeightHistoryDepth = WeightHistoryDepth;
+}
+
+pub struct TestCallStakeThreshold;
+
+impl CallStakeThreshold<RuntimeCall> for TestCallStakeThreshold {
+    fn required_stake(call: &RuntimeCall) -> Percent {
+        match call {
+            RuntimeCall::System(_) => Percent::from_percent(75),
+            _ => Percent::from_percent(50),
+        }
+    }
+}
+
+pub struct TestRootCalls;
+
+impl Contains<RuntimeCall> for TestRootCalls {
+    fn contains(call: &RuntimeCall) -> bool {
+        matches!(
+            call,
+            RuntimeCall::System(frame_system::Call::set_storage { .. })
+        )
+    }
 }
 
 impl pallet_governance::Config for Test {
     type PalletId = SubspacePalletId;
     type RuntimeEvent = RuntimeEvent;
     type Currency = Balances;
+    type ProposalOrigin = RuntimeOrigin;
+    type ProposalCall = RuntimeCall;
+    type CallStakeThreshold = TestCallStakeThreshold;
+    type RootCalls = TestRootCalls;
 }
 
 impl GovernanceApi<<Test as frame_system::Config>::AccountId> for Test {
@@ -117,6 +147,10 @@ impl GovernanceApi<<Test as frame_system::Config>::AccountId> for Test {
         false
     }
 
+    fn get_locked_stake(staker: &AccountId, subnet_id: Option<u16>) -> u64 {
+        pallet_governance::Pallet::<Test>::get_locked_stake(staker, subnet_id)
+    }
+
     fn update_delegating_voting_power(_delegator: &AccountId, _delegating: bool) -> DispatchResult {
         Ok(())
     }
@@ -144,6 +178,10 @@ impl GovernanceApi<<Test as frame_system::Config>::AccountId> for Test {
 
     fn handle_subnet_removal(_subnet_id: u16) {}
 
+    fn handle_key_rotation(key: &AccountId, new_key: &AccountId) -> DispatchResult {
+        Governance::rotate_voting_key(key, new_key)
+    }
+
     fn execute_application(user_id: &AccountId) -> DispatchResult {
         Governance::execute_application(user_id)
     }
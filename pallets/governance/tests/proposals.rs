@@ -1,8 +1,17 @@
 use dao::ApplicationStatus;
+use frame_support::{
+    traits::{EnsureOrigin, Get},
+    weights::Weight,
+};
 use mock::*;
 use pallet_subspace::{subnet::SubnetChangeset, GlobalParams, SubnetParams};
-use proposal::get_reward_allocation;
+use proposal::{
+    get_minimal_stake_to_execute_with_percentage, get_reward_allocation, ProposalKind,
+    ProposalState, ProposalTally,
+};
+use sp_runtime::{DispatchError, Percent};
 use substrate_fixed::{types::extra::U32, FixedI128};
+use voting::{MAX_DELEGATION_DEPTH, MAX_VOTING_POWER_DELEGATIONS};
 
 mod mock;
 
@@ -47,6 +56,11 @@ fn global_proposal_validates_parameters() {
                 floor_delegation_fee,
                 floor_founder_share,
                 min_weight_stake,
+                unbonding_period,
+                subnet_auction_period,
+                rate_limit_config,
+                fee_config,
+                subnet_auction_config,
                 curator,
                 general_subnet_application_cost,
                 subnet_stake_threshold,
@@ -73,6 +87,13 @@ fn global_proposal_validates_parameters() {
                 governance_config.proposal_cost,
                 governance_config.proposal_expiration,
                 general_subnet_application_cost,
+                unbonding_period,
+                governance_config.proposal_enactment_delay,
+                subnet_auction_period,
+                burn_config.destination,
+                rate_limit_config,
+                fee_config,
+                subnet_auction_config,
             )
         };
 
@@ -215,6 +236,11 @@ fn global_params_proposal_accepted() {
             floor_delegation_fee,
             floor_founder_share,
             min_weight_stake,
+            unbonding_period,
+            subnet_auction_period,
+            rate_limit_config,
+            fee_config,
+            subnet_auction_config,
             curator,
             general_subnet_application_cost,
             subnet_stake_threshold,
@@ -243,6 +269,13 @@ fn global_params_proposal_accepted() {
             governance_config.proposal_cost,
             governance_config.proposal_expiration,
             general_subnet_application_cost,
+            unbonding_period,
+            governance_config.proposal_enactment_delay,
+            subnet_auction_period,
+            burn_config.destination,
+            rate_limit_config,
+            fee_config,
+            subnet_auction_config,
         )
         .unwrap();
 
@@ -288,10 +321,21 @@ fn subnet_params_proposal_accepted() {
             trust_ratio,
             maximum_set_weight_calls_per_epoch,
             bonds_ma,
+            liquid_alpha,
+            alpha_low,
+            alpha_high,
             target_registrations_interval,
             target_registrations_per_interval,
             max_registrations_per_interval,
             adjustment_alpha,
+            unbonding_period,
+            slash_ratio,
+            slash_threshold,
+            commit_reveal_weights,
+            weight_reveal_period,
+            consensus,
+            weight_history,
+            reward_history,
             mut governance_config,
         } = Subspace::subnet_params(0);
 
@@ -320,6 +364,17 @@ fn subnet_params_proposal_accepted() {
             target_registrations_per_interval,
             max_registrations_per_interval,
             adjustment_alpha,
+            unbonding_period,
+            slash_ratio,
+            slash_threshold,
+            commit_reveal_weights,
+            weight_reveal_period,
+            consensus,
+            weight_history,
+            reward_history,
+            liquid_alpha,
+            alpha_low,
+            alpha_high,
         )
         .unwrap();
 
@@ -428,6 +483,299 @@ fn subnet_proposals_counts_delegated_stake() {
     });
 }
 
+#[test]
+fn explicit_delegation_chain_credits_voter() {
+    new_test_ext().execute_with(|| {
+        const FOR: u32 = 0;
+        const AGAINST: u32 = 1;
+        const DELEGATOR: u32 = 2;
+        const INTERMEDIATE: u32 = 3;
+
+        zero_min_burn();
+        let origin = get_origin(0);
+
+        register(FOR, 0, 0, to_nano(5));
+        assert_ok!(Governance::disable_vote_power_delegation(get_origin(FOR)));
+        register(AGAINST, 0, 1, to_nano(10));
+
+        stake(DELEGATOR, 0, 1, to_nano(8));
+        stake(INTERMEDIATE, 0, 1, to_nano(4));
+
+        assert_ok!(Governance::delegate_voting_power(
+            get_origin(INTERMEDIATE),
+            None,
+            FOR
+        ));
+        assert_ok!(Governance::delegate_voting_power(
+            get_origin(DELEGATOR),
+            None,
+            INTERMEDIATE
+        ));
+
+        config(1, 100);
+
+        assert_ok!(Governance::do_add_global_custom_proposal(
+            origin,
+            vec![b'0'; 64]
+        ));
+
+        vote(FOR, 0, true);
+        vote(AGAINST, 0, false);
+
+        step_block(100);
+
+        assert_eq!(
+            Proposals::<Test>::get(0).unwrap().status,
+            ProposalStatus::Accepted {
+                block: 100,
+                stake_for: 17_000_000_000,
+                stake_against: 10_000_000_000,
+            }
+        );
+
+        let unrewarded = UnrewardedProposals::<Test>::get(0).unwrap();
+        assert_eq!(
+            unrewarded.votes_for.get(&FOR),
+            Some(&VotingPower {
+                direct: to_nano(5),
+                delegated: to_nano(12),
+            })
+        );
+        assert_eq!(
+            unrewarded.votes_against.get(&AGAINST),
+            Some(&VotingPower {
+                direct: 0,
+                delegated: to_nano(10),
+            })
+        );
+    });
+}
+
+#[test]
+fn explicit_delegator_vote_overrides_delegate() {
+    new_test_ext().execute_with(|| {
+        const FOR: u32 = 0;
+        const AGAINST: u32 = 1;
+        const DELEGATOR: u32 = 2;
+
+        zero_min_burn();
+        let origin = get_origin(0);
+
+        register(FOR, 0, 0, to_nano(10));
+        register(AGAINST, 0, 1, to_nano(5));
+
+        stake(DELEGATOR, 0, 0, to_nano(8));
+        assert_ok!(Governance::delegate_voting_power(
+            get_origin(DELEGATOR),
+            Some(0),
+            FOR
+        ));
+
+        config(1, 100);
+
+        assert_ok!(Governance::do_add_subnet_custom_proposal(
+            origin,
+            0,
+            vec![b'0'; 64]
+        ));
+
+        vote(FOR, 0, true);
+        vote(AGAINST, 0, false);
+        vote(DELEGATOR, 0, false);
+
+        step_block(100);
+
+        assert_eq!(
+            Proposals::<Test>::get(0).unwrap().status,
+            ProposalStatus::Refused {
+                block: 100,
+                stake_for: 10_000_000_000,
+                stake_against: 13_000_000_000,
+            }
+        );
+    });
+}
+
+#[test]
+fn voting_power_delegation_rejects_self_and_cycles() {
+    new_test_ext().execute_with(|| {
+        assert_err!(
+            Governance::delegate_voting_power(get_origin(0), None, 0),
+            Error::<Test>::DelegatingToSelf
+        );
+
+        assert_ok!(Governance::delegate_voting_power(get_origin(0), None, 1));
+        assert_ok!(Governance::delegate_voting_power(get_origin(1), None, 2));
+        assert_err!(
+            Governance::delegate_voting_power(get_origin(2), None, 0),
+            Error::<Test>::DelegationCycle
+        );
+
+        // Subnet scoped delegations fall back to the global ones when walking the chain.
+        assert_err!(
+            Governance::delegate_voting_power(get_origin(2), Some(0), 0),
+            Error::<Test>::DelegationCycle
+        );
+
+        assert_ok!(Governance::undelegate_voting_power(get_origin(1), None));
+        assert_ok!(Governance::delegate_voting_power(get_origin(2), None, 0));
+        assert_err!(
+            Governance::undelegate_voting_power(get_origin(1), None),
+            Error::<Test>::NotDelegating
+        );
+    });
+}
+
+#[test]
+fn voting_power_delegations_are_bounded() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Governance::delegate_voting_power(get_origin(0), None, 1));
+        assert_ok!(Governance::delegate_voting_power(get_origin(0), Some(0), 2));
+        assert_eq!(VotingPowerDelegationCount::<Test>::get(), 2);
+
+        VotingPowerDelegationCount::<Test>::put(MAX_VOTING_POWER_DELEGATIONS);
+        assert_err!(
+            Governance::delegate_voting_power(get_origin(1), None, 2),
+            Error::<Test>::TooManyVotingPowerDelegations
+        );
+
+        // Replacing a delegation doesn't add one.
+        assert_ok!(Governance::delegate_voting_power(get_origin(0), None, 2));
+        assert_eq!(
+            VotingPowerDelegationCount::<Test>::get(),
+            MAX_VOTING_POWER_DELEGATIONS
+        );
+
+        assert_ok!(Governance::undelegate_voting_power(get_origin(0), None));
+        assert_ok!(Governance::delegate_voting_power(get_origin(1), None, 2));
+        assert_eq!(
+            VotingPowerDelegationCount::<Test>::get(),
+            MAX_VOTING_POWER_DELEGATIONS
+        );
+    });
+}
+
+#[test]
+fn voting_power_delegation_limits_the_whole_chain() {
+    new_test_ext().execute_with(|| {
+        // Grow the chain 0 -> 1 -> ... -> 8 from its tail, where nothing is downstream.
+        for key in 0..MAX_DELEGATION_DEPTH {
+            assert_ok!(Governance::delegate_voting_power(
+                get_origin(key),
+                None,
+                key + 1
+            ));
+        }
+        assert_err!(
+            Governance::delegate_voting_power(get_origin(MAX_DELEGATION_DEPTH), None, 100),
+            Error::<Test>::DelegationChainTooLong
+        );
+        // The global chain also applies to subnets.
+        assert_err!(
+            Governance::delegate_voting_power(get_origin(MAX_DELEGATION_DEPTH), Some(0), 100),
+            Error::<Test>::DelegationChainTooLong
+        );
+
+        // Joining two chains counts the delegators upstream and the delegates downstream.
+        for key in 200..203 {
+            assert_ok!(Governance::delegate_voting_power(
+                get_origin(key),
+                None,
+                key + 1
+            ));
+        }
+        for key in 300..305 {
+            assert_ok!(Governance::delegate_voting_power(
+                get_origin(key),
+                None,
+                key + 1
+            ));
+        }
+        assert_err!(
+            Governance::delegate_voting_power(get_origin(203), None, 300),
+            Error::<Test>::DelegationChainTooLong
+        );
+        assert_ok!(Governance::delegate_voting_power(
+            get_origin(203),
+            None,
+            301
+        ));
+    });
+}
+
+#[test]
+fn module_key_rotation_moves_locks_and_delegations() {
+    new_test_ext().execute_with(|| {
+        const KEY: u32 = 0;
+        const NEW_KEY: u32 = 1;
+        const DELEGATOR: u32 = 2;
+        const OTHER: u32 = 3;
+
+        zero_min_burn();
+
+        register(KEY, 0, KEY, to_nano(10));
+        config(1, 100);
+        SubnetGovernanceConfig::<Test>::mutate(0, |config| {
+            config.vote_mode = VoteMode::Conviction;
+            config.conviction_lock_period = 1_000;
+        });
+
+        assert_ok!(Governance::do_add_subnet_custom_proposal(
+            get_origin(KEY),
+            0,
+            vec![b'0'; 64]
+        ));
+        assert_ok!(Governance::vote_proposal_with_conviction(
+            get_origin(KEY),
+            0,
+            true,
+            2
+        ));
+        assert_ok!(Governance::delegate_voting_power(
+            get_origin(DELEGATOR),
+            None,
+            KEY
+        ));
+        assert_ok!(Governance::update_delegating_voting_power(&KEY, false));
+
+        let lock = StakeLocks::<Test>::get(KEY, 0).unwrap();
+        assert_ok!(Subspace::rotate_module_key(
+            get_origin(KEY),
+            0,
+            KEY,
+            NEW_KEY
+        ));
+
+        assert_eq!(StakeLocks::<Test>::get(NEW_KEY, 0), Some(lock));
+        assert_eq!(StakeLocks::<Test>::get(KEY, 0), None);
+        assert!(StakeLockExpirations::<Test>::contains_key(
+            lock.unlock_block,
+            (NEW_KEY, 0)
+        ));
+        assert!(!StakeLockExpirations::<Test>::contains_key(
+            lock.unlock_block,
+            (KEY, 0)
+        ));
+        assert_eq!(
+            VotingPowerDelegations::<Test>::get(None::<u16>, DELEGATOR),
+            Some(NEW_KEY)
+        );
+        assert!(!Governance::is_delegating_voting_power(&NEW_KEY));
+        assert!(Governance::is_delegating_voting_power(&KEY));
+
+        // Merging two delegation chains could break their bounds.
+        assert_ok!(Governance::delegate_voting_power(
+            get_origin(OTHER),
+            None,
+            DELEGATOR
+        ));
+        assert_err!(
+            Subspace::rotate_module_key(get_origin(NEW_KEY), 0, NEW_KEY, OTHER),
+            Error::<Test>::DelegationKeyInUse
+        );
+    });
+}
+
 #[test]
 fn creates_treasury_transfer_proposal_and_transfers() {
     new_test_ext().execute_with(|| {
@@ -458,6 +806,660 @@ fn creates_treasury_transfer_proposal_and_transfers() {
     });
 }
 
+#[test]
+fn accepted_proposal_is_enacted_after_delay() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        add_balance(DaoTreasuryAddress::<Test>::get(), to_nano(10));
+        add_balance(0, to_nano(3));
+        register(0, 0, 0, to_nano(1));
+        config(to_nano(1), 100);
+        GlobalGovernanceConfig::<Test>::mutate(|config| config.proposal_enactment_delay = 50);
+
+        Governance::add_transfer_dao_treasury_proposal(
+            get_origin(0),
+            vec![b'0'; 64],
+            to_nano(5),
+            0,
+        )
+        .expect("proposal should be created");
+        vote(0, 0, true);
+
+        step_block(100);
+
+        assert!(matches!(
+            Proposals::<Test>::get(0).unwrap().status,
+            ProposalStatus::Enacting {
+                enactment_block: 150,
+                ..
+            }
+        ));
+        assert_eq!(
+            EnactmentQueue::<Test>::get(150).unwrap().into_inner(),
+            vec![0]
+        );
+        assert_eq!(get_balance(DaoTreasuryAddress::<Test>::get()), to_nano(10));
+        // The proposal cost is only refunded once the proposal is enacted.
+        assert_eq!(get_balance(0), to_nano(1));
+
+        step_block(50);
+
+        assert_eq!(
+            Proposals::<Test>::get(0).unwrap().status,
+            ProposalStatus::Accepted {
+                block: 100,
+                stake_for: to_nano(1),
+                stake_against: 0,
+            }
+        );
+        assert_eq!(EnactmentQueue::<Test>::get(150), None);
+        assert_eq!(get_balance(DaoTreasuryAddress::<Test>::get()), to_nano(5));
+        assert_eq!(get_balance(0), to_nano(7));
+    });
+}
+
+#[test]
+fn failed_enactment_is_dequeued() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        let treasury = DaoTreasuryAddress::<Test>::get();
+        add_balance(treasury, to_nano(10));
+        add_balance(0, to_nano(3));
+        register(0, 0, 0, to_nano(1));
+        config(to_nano(1), 100);
+        GlobalGovernanceConfig::<Test>::mutate(|config| config.proposal_enactment_delay = 50);
+
+        Governance::add_transfer_dao_treasury_proposal(
+            get_origin(0),
+            vec![b'0'; 64],
+            to_nano(5),
+            0,
+        )
+        .expect("proposal should be created");
+        vote(0, 0, true);
+
+        step_block(100);
+
+        // The treasury can no longer pay the transfer once the proposal is enacted.
+        assert_ok!(Subspace::transfer_balance_to_account(
+            &treasury,
+            &99,
+            to_nano(9)
+        ));
+
+        step_block(50);
+
+        assert_eq!(
+            Proposals::<Test>::get(0).unwrap().status,
+            ProposalStatus::EnactmentFailed {
+                block: 150,
+                stake_for: to_nano(1),
+                stake_against: 0,
+            }
+        );
+        assert_eq!(EnactmentQueue::<Test>::get(150), None);
+        assert_eq!(get_balance(treasury), to_nano(1));
+        assert_eq!(get_balance(0), to_nano(1));
+
+        step_block(1);
+        assert!(matches!(
+            Proposals::<Test>::get(0).unwrap().status,
+            ProposalStatus::EnactmentFailed { .. }
+        ));
+    });
+}
+
+#[test]
+fn curator_cancels_enacting_proposal() {
+    new_test_ext().execute_with(|| {
+        const CURATOR: u32 = 1;
+
+        zero_min_burn();
+        Curator::<Test>::set(CURATOR);
+
+        add_balance(DaoTreasuryAddress::<Test>::get(), to_nano(10));
+        register(0, 0, 0, to_nano(1));
+        config(1, 100);
+        GlobalGovernanceConfig::<Test>::mutate(|config| config.proposal_enactment_delay = 50);
+
+        Governance::add_transfer_dao_treasury_proposal(
+            get_origin(0),
+            vec![b'0'; 64],
+            to_nano(5),
+            0,
+        )
+        .expect("proposal should be created");
+        let proposer_balance = get_balance(0);
+
+        assert_err!(
+            Governance::cancel_proposal(get_origin(CURATOR), 0),
+            Error::<Test>::ProposalNotEnacting
+        );
+
+        vote(0, 0, true);
+        step_block(100);
+
+        assert_err!(
+            Governance::cancel_proposal(get_origin(0), 0),
+            Error::<Test>::NotCurator
+        );
+        assert_ok!(Governance::cancel_proposal(get_origin(CURATOR), 0));
+
+        assert_eq!(
+            Proposals::<Test>::get(0).unwrap().status,
+            ProposalStatus::Canceled { block: 100 }
+        );
+
+        step_block(100);
+
+        assert_eq!(EnactmentQueue::<Test>::get(150), None);
+        assert_eq!(get_balance(DaoTreasuryAddress::<Test>::get()), to_nano(10));
+        // Canceled proposals do not get their cost back.
+        assert_eq!(get_balance(0), proposer_balance);
+    });
+}
+
+#[test]
+fn fast_track_vote_cancels_enacting_proposal() {
+    new_test_ext().execute_with(|| {
+        const PROPOSER: u32 = 0;
+        const CANCELER_A: u32 = 1;
+        const CANCELER_B: u32 = 2;
+
+        zero_min_burn();
+
+        register(PROPOSER, 0, 0, to_nano(10));
+        register(CANCELER_A, 0, 1, to_nano(5));
+        register(CANCELER_B, 0, 2, to_nano(5));
+        config(1, 100);
+        GlobalGovernanceConfig::<Test>::mutate(|config| config.proposal_enactment_delay = 50);
+
+        assert_ok!(Governance::do_add_global_custom_proposal(
+            get_origin(PROPOSER),
+            vec![b'0'; 64]
+        ));
+        vote(PROPOSER, 0, true);
+        step_block(100);
+
+        // 5 out of the 10 tokens needed to cancel.
+        assert_ok!(Governance::vote_cancel_proposal(get_origin(CANCELER_A), 0));
+        assert!(matches!(
+            Proposals::<Test>::get(0).unwrap().status,
+            ProposalStatus::Enacting { .. }
+        ));
+        assert_err!(
+            Governance::vote_cancel_proposal(get_origin(CANCELER_A), 0),
+            Error::<Test>::AlreadyVoted
+        );
+
+        assert_ok!(Governance::vote_cancel_proposal(get_origin(CANCELER_B), 0));
+        assert_eq!(
+            Proposals::<Test>::get(0).unwrap().status,
+            ProposalStatus::Canceled { block: 100 }
+        );
+        assert_eq!(EnactmentQueue::<Test>::get(150), None);
+    });
+}
+
+#[test]
+fn runtime_call_proposal_is_dispatched_as_root() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        register(0, 0, 0, to_nano(10));
+        config(1, 100);
+
+        let remark = RuntimeCall::System(frame_system::Call::remark {
+            remark: b"governance".to_vec(),
+        });
+        assert_ok!(Governance::add_runtime_call_proposal(
+            get_origin(0),
+            vec![b'0'; 64],
+            Box::new(remark)
+        ));
+
+        // Only root can set storage.
+        let set_storage = RuntimeCall::System(frame_system::Call::set_storage {
+            items: vec![(b"governance".to_vec(), b"root".to_vec())],
+        });
+        assert_ok!(Governance::add_runtime_call_proposal(
+            get_origin(0),
+            vec![b'0'; 64],
+            Box::new(set_storage)
+        ));
+
+        // The required stake is stored along with the proposal, the call on its own.
+        assert!(matches!(
+            Proposals::<Test>::get(1).unwrap().data,
+            ProposalData::RuntimeCall {
+                required_stake,
+            } if required_stake == Percent::from_percent(75)
+        ));
+        assert!(ProposalCalls::<Test>::contains_key(0));
+        assert!(ProposalCalls::<Test>::contains_key(1));
+
+        vote(0, 0, true);
+        vote(0, 1, true);
+        let weight_before = System::block_weight().total();
+        step_block(100);
+
+        assert!(!ProposalCalls::<Test>::contains_key(0));
+        assert!(!ProposalCalls::<Test>::contains_key(1));
+
+        System::assert_has_event(Event::ProposalCallDispatched(0, Ok(())).into());
+        System::assert_has_event(Event::ProposalCallDispatched(1, Ok(())).into());
+        assert_eq!(
+            frame_support::storage::unhashed::get_raw(b"governance"),
+            Some(b"root".to_vec())
+        );
+
+        // The dispatched calls count towards the block weight.
+        assert!(System::block_weight().total().ref_time() > weight_before.ref_time());
+    });
+}
+
+#[test]
+fn runtime_call_proposal_call_is_bounded_and_removed_once_finished() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        register(0, 0, 0, to_nano(10));
+        config(1, 100);
+
+        let too_large = RuntimeCall::System(frame_system::Call::remark {
+            remark: vec![0; MAX_PROPOSAL_CALL_SIZE as usize],
+        });
+        assert_err!(
+            Governance::add_runtime_call_proposal(
+                get_origin(0),
+                vec![b'0'; 64],
+                Box::new(too_large)
+            ),
+            Error::<Test>::ProposalCallTooLarge
+        );
+
+        let remark = RuntimeCall::System(frame_system::Call::remark {
+            remark: b"governance".to_vec(),
+        });
+        assert_ok!(Governance::add_runtime_call_proposal(
+            get_origin(0),
+            vec![b'0'; 64],
+            Box::new(remark)
+        ));
+        assert!(ProposalCalls::<Test>::contains_key(0));
+
+        step_block(100);
+
+        assert_eq!(
+            Proposals::<Test>::get(0).unwrap().status,
+            ProposalStatus::Expired
+        );
+        assert!(!ProposalCalls::<Test>::contains_key(0));
+    });
+}
+
+#[test]
+fn runtime_call_enactment_waits_for_block_weight() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        register(0, 0, 0, to_nano(10));
+        config(1, 100);
+        GlobalGovernanceConfig::<Test>::mutate(|config| config.proposal_enactment_delay = 50);
+
+        // Calls heavier than a single extrinsic are refused right away.
+        let kill_prefix = RuntimeCall::System(frame_system::Call::kill_prefix {
+            prefix: b"governance".to_vec(),
+            subkeys: u32::MAX,
+        });
+        assert_err!(
+            Governance::add_runtime_call_proposal(
+                get_origin(0),
+                vec![b'0'; 64],
+                Box::new(kill_prefix)
+            ),
+            Error::<Test>::ProposalCallTooHeavy
+        );
+
+        let remark = RuntimeCall::System(frame_system::Call::remark {
+            remark: b"governance".to_vec(),
+        });
+        assert_ok!(Governance::add_runtime_call_proposal(
+            get_origin(0),
+            vec![b'0'; 64],
+            Box::new(remark)
+        ));
+        vote(0, 0, true);
+
+        step_block(149);
+
+        // The enactment block is full, so the call waits for the next one.
+        let max_block = <Test as frame_system::Config>::BlockWeights::get().max_block;
+        System::set_block_consumed_resources(max_block, 0);
+        step_block(1);
+
+        assert!(matches!(
+            Proposals::<Test>::get(0).unwrap().status,
+            ProposalStatus::Enacting {
+                enactment_block: 151,
+                ..
+            }
+        ));
+        assert_eq!(EnactmentQueue::<Test>::get(150), None);
+        assert_eq!(
+            EnactmentQueue::<Test>::get(151).unwrap().into_inner(),
+            vec![0]
+        );
+
+        System::set_block_consumed_resources(Weight::zero(), 0);
+        step_block(1);
+
+        assert!(matches!(
+            Proposals::<Test>::get(0).unwrap().status,
+            ProposalStatus::Accepted { block: 100, .. }
+        ));
+        assert_eq!(EnactmentQueue::<Test>::get(151), None);
+        System::assert_has_event(Event::ProposalCallDispatched(0, Ok(())).into());
+    });
+}
+
+#[test]
+fn runtime_call_proposal_only_dispatches_listed_calls_as_root() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        register(0, 0, 0, to_nano(10));
+        config(1, 100);
+
+        frame_support::storage::unhashed::put_raw(b"governance", b"root");
+
+        // Killing storage needs root, but it is not one of the root calls.
+        let kill_storage = RuntimeCall::System(frame_system::Call::kill_storage {
+            keys: vec![b"governance".to_vec()],
+        });
+        assert_ok!(Governance::add_runtime_call_proposal(
+            get_origin(0),
+            vec![b'0'; 64],
+            Box::new(kill_storage)
+        ));
+
+        vote(0, 0, true);
+        step_block(100);
+
+        System::assert_has_event(
+            Event::ProposalCallDispatched(0, Err(DispatchError::BadOrigin)).into(),
+        );
+        assert_eq!(
+            frame_support::storage::unhashed::get_raw(b"governance"),
+            Some(b"root".to_vec())
+        );
+
+        assert_ok!(EnsureGovernance::try_origin(RuntimeOrigin::from(
+            RawOrigin::Governance
+        )));
+        assert!(EnsureGovernance::try_origin(RuntimeOrigin::root()).is_err());
+        assert!(EnsureGovernance::try_origin(get_origin(0)).is_err());
+    });
+}
+
+#[test]
+fn quadratic_vote_weighs_by_square_root_of_stake() {
+    new_test_ext().execute_with(|| {
+        const FOR_A: u32 = 0;
+        const FOR_B: u32 = 1;
+        const AGAINST: u32 = 2;
+
+        zero_min_burn();
+
+        register(FOR_A, 0, 0, to_nano(5));
+        register(FOR_B, 0, 1, to_nano(5));
+        register(AGAINST, 0, 2, to_nano(16));
+
+        config(1, 100);
+        SubnetGovernanceConfig::<Test>::mutate(0, |config| config.vote_mode = VoteMode::Quadratic);
+
+        assert_ok!(Governance::do_add_subnet_custom_proposal(
+            get_origin(FOR_A),
+            0,
+            vec![b'0'; 64]
+        ));
+
+        vote(FOR_A, 0, true);
+        vote(FOR_B, 0, true);
+        vote(AGAINST, 0, false);
+
+        step_block(100);
+
+        // 2 * sqrt(5 tokens) beats sqrt(16 tokens), even though the raw stake doesn't.
+        assert_eq!(
+            Proposals::<Test>::get(0).unwrap().status,
+            ProposalStatus::Accepted {
+                block: 100,
+                stake_for: 141_420,
+                stake_against: 126_491,
+            }
+        );
+    });
+}
+
+#[test]
+fn proposal_tally_projects_the_next_tick() {
+    new_test_ext().execute_with(|| {
+        const FOR_A: u32 = 0;
+        const FOR_B: u32 = 1;
+        const AGAINST: u32 = 2;
+
+        zero_min_burn();
+
+        register(FOR_A, 0, 0, to_nano(5));
+        register(FOR_B, 0, 1, to_nano(5));
+        register(AGAINST, 0, 2, to_nano(16));
+
+        config(1, 100);
+        SubnetGovernanceConfig::<Test>::mutate(0, |config| config.vote_mode = VoteMode::Quadratic);
+
+        assert_ok!(Governance::do_add_global_custom_proposal(
+            get_origin(FOR_A),
+            vec![b'0'; 64]
+        ));
+        assert_ok!(Governance::do_add_subnet_custom_proposal(
+            get_origin(FOR_A),
+            0,
+            vec![b'0'; 64]
+        ));
+
+        vote(FOR_A, 1, true);
+        vote(FOR_B, 1, true);
+        vote(AGAINST, 1, false);
+
+        let ids = |subnet_id| -> Vec<u64> {
+            Governance::get_proposals(subnet_id)
+                .iter()
+                .map(|proposal| proposal.id)
+                .collect()
+        };
+        assert_eq!(ids(None), vec![0, 1]);
+        assert_eq!(ids(Some(0)), vec![1]);
+
+        let infos = Governance::get_proposal_infos(Some(ProposalState::Open), Some(0));
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].kind, ProposalKind::SubnetCustom);
+        assert_eq!(infos[0].votes_for, vec![FOR_A, FOR_B]);
+        assert_eq!(infos[0].votes_against, vec![AGAINST]);
+
+        // The projection is counted like the tick that decides the proposal.
+        let tally = Governance::proposal_tally(1);
+        assert_eq!(
+            tally,
+            Some(ProposalTally {
+                stake_for: 141_420,
+                stake_against: 126_491,
+                voted_stake: to_nano(26),
+                minimal_stake_to_execute: get_minimal_stake_to_execute_with_percentage::<Test>(
+                    Percent::from_parts(50),
+                    Some(0),
+                ),
+            })
+        );
+
+        step_block(100);
+
+        assert_eq!(
+            Proposals::<Test>::get(1).unwrap().status,
+            ProposalStatus::Accepted {
+                block: 100,
+                stake_for: 141_420,
+                stake_against: 126_491,
+            }
+        );
+        assert_eq!(Governance::proposal_tally(1), None);
+
+        // Decided proposals keep the stake they were decided with, but no longer list voters.
+        let infos = Governance::get_proposal_infos(Some(ProposalState::Accepted), None);
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].id, 1);
+        assert_eq!(
+            (infos[0].stake_for, infos[0].stake_against),
+            (141_420, 126_491)
+        );
+        assert!(infos[0].votes_for.is_empty());
+        assert!(Governance::get_proposal_infos(Some(ProposalState::Open), None).is_empty());
+    });
+}
+
+#[test]
+fn conviction_vote_multiplies_weight_and_locks_stake() {
+    new_test_ext().execute_with(|| {
+        const FOR: u32 = 0;
+        const AGAINST: u32 = 1;
+
+        zero_min_burn();
+
+        register(FOR, 0, 0, to_nano(5));
+        register(AGAINST, 0, 1, to_nano(10));
+
+        config(1, 100);
+
+        assert_ok!(Governance::do_add_subnet_custom_proposal(
+            get_origin(FOR),
+            0,
+            vec![b'0'; 64]
+        ));
+
+        assert_err!(
+            Governance::vote_proposal_with_conviction(get_origin(FOR), 0, true, 2),
+            Error::<Test>::NotConvictionMode
+        );
+
+        SubnetGovernanceConfig::<Test>::mutate(0, |config| {
+            config.vote_mode = VoteMode::Conviction;
+            config.conviction_lock_period = 1_000;
+        });
+
+        assert_err!(
+            Governance::vote_proposal_with_conviction(get_origin(FOR), 0, true, MAX_CONVICTION + 1),
+            Error::<Test>::InvalidConviction
+        );
+        assert_ok!(Governance::vote_proposal_with_conviction(
+            get_origin(FOR),
+            0,
+            true,
+            2
+        ));
+        vote(AGAINST, 0, false);
+
+        assert_eq!(
+            StakeLocks::<Test>::get(FOR, 0),
+            Some(StakeLock {
+                subnet_id: Some(0),
+                amount: to_nano(5),
+                conviction: 2,
+                unlock_block: 2_100,
+            })
+        );
+
+        step_block(100);
+
+        assert_eq!(
+            Proposals::<Test>::get(0).unwrap().status,
+            ProposalStatus::Accepted {
+                block: 100,
+                stake_for: 15_000_000_000,
+                stake_against: 10_000_000_000,
+            }
+        );
+
+        assert_err!(
+            Subspace::do_remove_stake(get_origin(FOR), 0, FOR, to_nano(1)),
+            pallet_subspace::Error::<Test>::StakeLocked
+        );
+
+        step_block(2_000);
+
+        assert_eq!(Governance::get_locked_stake(&FOR, Some(0)), 0);
+        assert_eq!(StakeLocks::<Test>::get(FOR, 0), None);
+        assert_eq!(StakeLockExpirations::<Test>::iter().count(), 0);
+        assert_ok!(Subspace::do_remove_stake(
+            get_origin(FOR),
+            0,
+            FOR,
+            to_nano(1)
+        ));
+    });
+}
+
+#[test]
+fn conviction_only_multiplies_the_locked_stake() {
+    new_test_ext().execute_with(|| {
+        const FOR: u32 = 0;
+        const AGAINST: u32 = 1;
+        const DELEGATOR: u32 = 2;
+
+        zero_min_burn();
+
+        register(FOR, 0, 0, to_nano(5));
+        register(AGAINST, 0, 1, to_nano(25));
+        stake(DELEGATOR, 0, 0, to_nano(10));
+        delegate(DELEGATOR);
+
+        config(1, 100);
+        SubnetGovernanceConfig::<Test>::mutate(0, |config| {
+            config.vote_mode = VoteMode::Conviction;
+            config.conviction_lock_period = 1_000;
+        });
+
+        assert_ok!(Governance::do_add_subnet_custom_proposal(
+            get_origin(FOR),
+            0,
+            vec![b'0'; 64]
+        ));
+        assert_ok!(Governance::vote_proposal_with_conviction(
+            get_origin(FOR),
+            0,
+            true,
+            2
+        ));
+        vote(AGAINST, 0, false);
+
+        // Stake added after voting isn't locked either.
+        stake(FOR, 0, 0, to_nano(5));
+
+        step_block(100);
+
+        // 5 locked tokens count three times, the 10 delegated and 5 added tokens count once.
+        assert_eq!(
+            Proposals::<Test>::get(0).unwrap().status,
+            ProposalStatus::Accepted {
+                block: 100,
+                stake_for: 30_000_000_000,
+                stake_against: 25_000_000_000,
+            }
+        );
+    });
+}
+
 /// This test, observes the distribution of governance reward logic over time.
 #[test]
 fn rewards_wont_exceed_treasury() {
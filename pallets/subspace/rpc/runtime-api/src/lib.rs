@@ -40,8 +40,149 @@ pub struct ModuleInfo {
     pub stats: ModuleStats,
 }
 
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct MetagraphModule {
+    pub uid: u16,
+    pub key: AccountId,
+    pub name: Vec<u8>,
+    pub address: Vec<u8>,
+    pub stake: u64,
+    pub emission: u64,
+    pub incentive: u16,
+    pub dividends: u16,
+    pub trust: u16,
+    pub rank: u16,
+    pub validator_permit: bool,
+    pub last_update: u64,
+    pub registration_block: u64,
+    pub delegation_fee: Percent,
+}
+
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct SubnetMetagraph {
+    pub netuid: u16,
+    /// Total number of modules in the subnet, used to page through the metagraph.
+    pub module_count: u16,
+    /// Modules in the requested uid range, ordered by uid.
+    pub modules: Vec<MetagraphModule>,
+}
+
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct RewardRecord {
+    pub netuid: u16,
+    /// Block the epoch that distributed the reward ran at.
+    pub block: u64,
+    pub amount: u64,
+}
+
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct WeightRecord {
+    /// Block the weights were set at.
+    pub block: u64,
+    pub weights: Vec<(u16, u16)>, // Vec of (uid, weight)
+}
+
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct ValidatorEpochRecord {
+    /// Block the epoch ran at.
+    pub block: u64,
+    pub validator_trust: u16,
+    pub dividends: u16,
+}
+
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct WeightHistory {
+    pub netuid: u16,
+    pub uid: u16,
+    /// Weights set by the module, oldest first.
+    pub weights: Vec<WeightRecord>,
+    /// Validator trust and dividends of the module on past epochs, oldest first.
+    pub epochs: Vec<ValidatorEpochRecord>,
+}
+
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct WeightsValidation {
+    /// Name of the first check the weights fail, `None` when they would be accepted.
+    pub error: Option<Vec<u8>>,
+    pub min_allowed_weights: u16,
+    pub max_allowed_weights: u16,
+    /// Stake needed to set weights on the given amount of uids.
+    pub min_stake_for_weights: u64,
+    pub stake: u64,
+    /// `set_weights` calls made this epoch and how many are allowed, 0 for no limit.
+    pub set_weight_calls: u16,
+    pub max_set_weight_calls: u16,
+    /// The normalized weights that would be stored.
+    pub weights: Vec<(u16, u16)>, // Vec of (uid, weight)
+}
+
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct PrunedModule {
+    pub netuid: u16,
+    pub uid: u16,
+    pub key: AccountId,
+}
+
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, Serialize, Deserialize)]
+pub struct RegistrationPreview {
+    pub netuid: u16,
+    /// Name of the first check the registration fails, `None` when it would go through.
+    pub error: Option<Vec<u8>>,
+    /// Taken from the registration stake.
+    pub burn: u64,
+    pub min_stake: u64,
+    /// Whether the stake covers the minimum stake and the burn.
+    pub enough_stake: bool,
+    pub registrations_left_this_block: u16,
+    pub registrations_left_this_interval: u16,
+    /// Whether the subnet doesn't exist yet and is created by the registration.
+    pub creates_subnet: bool,
+    /// The subnet removed to make room for the new one.
+    pub replaced_subnet: Option<u16>,
+    pub immunity_period: u16,
+    /// Whether the subnet is full and every module on it is immune, failing the registration.
+    pub subnet_immune: bool,
+    /// The module removed to make room for the new one.
+    pub pruned_module: Option<PrunedModule>,
+}
+
 sp_api::decl_runtime_apis! {
+    #[api_version(2)]
     pub trait SubspaceRuntimeApi {
         fn get_module_info(key: AccountId, netuid: u16) -> ModuleInfo;
+
+        /// Returns the modules with uids in `start_uid..start_uid + limit`.
+        #[api_version(2)]
+        fn get_subnet_metagraph(netuid: u16, start_uid: u16, limit: u16) -> SubnetMetagraph;
+
+        /// Returns the rewards earned by the key on epochs ran in `from_block..=to_block`, on
+        /// the given subnet or on every subnet.
+        #[api_version(2)]
+        fn get_reward_history(
+            key: AccountId,
+            netuid: Option<u16>,
+            from_block: u64,
+            to_block: u64,
+        ) -> Vec<RewardRecord>;
+
+        /// Returns the past weights and validator epochs of the module, empty unless the subnet
+        /// keeps the weight history.
+        #[api_version(2)]
+        fn get_weight_history(netuid: u16, uid: u16) -> WeightHistory;
+
+        /// Runs every `set_weights` check for the key without writing anything, returning the
+        /// first one that fails and the limits the weights are checked against.
+        #[api_version(2)]
+        fn validate_weights(
+            netuid: u16,
+            key: AccountId,
+            uids: Vec<u16>,
+            values: Vec<u16>,
+        ) -> WeightsValidation;
+
+        /// Previews what registering on the subnet with `stake` would cost and which module it
+        /// would evict, as of the current block.
+        #[api_version(2)]
+        fn get_registration_preview(netuid: u16, stake: u64) -> RegistrationPreview;
     }
 }
@@ -1,7 +1,7 @@
 use jsonrpsee::{
-    core::{ClientError as JsonRpseeError, RpcResult},
+    core::RpcResult,
     proc_macros::rpc,
-    types::error::ErrorObject,
+    types::error::{ErrorObject, ErrorObjectOwned},
 };
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
@@ -10,12 +10,18 @@ use sp_runtime::{
     MultiSignature,
 };
 use std::sync::Arc;
-use subspace_runtime_api::ModuleInfo;
 pub use subspace_runtime_api::SubspaceRuntimeApi;
+use subspace_runtime_api::{
+    ModuleInfo, RegistrationPreview, RewardRecord, SubnetMetagraph, WeightHistory,
+    WeightsValidation,
+};
 
 type Signature = MultiSignature;
 type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
 
+/// Number of modules returned by `subspace_getSubnetMetagraph` when no limit is given.
+const DEFAULT_METAGRAPH_PAGE_SIZE: u16 = 256;
+
 #[derive(Clone, serde::Deserialize, serde::Serialize)]
 pub struct Custom {
     code: u32,
@@ -30,6 +36,51 @@ pub trait SubspaceApi<BlockHash> {
         netuid: u16,
         at: Option<BlockHash>,
     ) -> RpcResult<ModuleInfo>;
+
+    #[method(name = "subspace_getSubnetMetagraph")]
+    fn get_subnet_metagraph(
+        &self,
+        netuid: u16,
+        start_uid: Option<u16>,
+        limit: Option<u16>,
+        at: Option<BlockHash>,
+    ) -> RpcResult<SubnetMetagraph>;
+
+    #[method(name = "subspace_getRewardHistory")]
+    fn get_reward_history(
+        &self,
+        key: AccountId,
+        netuid: Option<u16>,
+        from_block: Option<u64>,
+        to_block: Option<u64>,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<RewardRecord>>;
+
+    #[method(name = "subspace_getWeightHistory")]
+    fn get_weight_history(
+        &self,
+        netuid: u16,
+        uid: u16,
+        at: Option<BlockHash>,
+    ) -> RpcResult<WeightHistory>;
+
+    #[method(name = "subspace_validateWeights")]
+    fn validate_weights(
+        &self,
+        netuid: u16,
+        key: AccountId,
+        uids: Vec<u16>,
+        values: Vec<u16>,
+        at: Option<BlockHash>,
+    ) -> RpcResult<WeightsValidation>;
+
+    #[method(name = "subspace_getRegistrationPreview")]
+    fn get_registration_preview(
+        &self,
+        netuid: u16,
+        stake: u64,
+        at: Option<BlockHash>,
+    ) -> RpcResult<RegistrationPreview>;
 }
 
 pub struct SubspacePallet<C, Block> {
@@ -61,18 +112,93 @@ where
         let api = self.client.runtime_api();
         let at = at.unwrap_or_else(|| self.client.info().best_hash);
 
-        let value = api.get_module_info(at, key, netuid).map_err(runtime_error_into_rpc_err);
-        Ok(value.unwrap())
+        api.get_module_info(at, key, netuid).map_err(runtime_error_into_rpc_err)
+    }
+
+    fn get_subnet_metagraph(
+        &self,
+        netuid: u16,
+        start_uid: Option<u16>,
+        limit: Option<u16>,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<SubnetMetagraph> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_subnet_metagraph(
+            at,
+            netuid,
+            start_uid.unwrap_or_default(),
+            limit.unwrap_or(DEFAULT_METAGRAPH_PAGE_SIZE),
+        )
+        .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn get_reward_history(
+        &self,
+        key: AccountId,
+        netuid: Option<u16>,
+        from_block: Option<u64>,
+        to_block: Option<u64>,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Vec<RewardRecord>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_reward_history(
+            at,
+            key,
+            netuid,
+            from_block.unwrap_or_default(),
+            to_block.unwrap_or(u64::MAX),
+        )
+        .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn get_weight_history(
+        &self,
+        netuid: u16,
+        uid: u16,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<WeightHistory> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_weight_history(at, netuid, uid).map_err(runtime_error_into_rpc_err)
+    }
+
+    fn validate_weights(
+        &self,
+        netuid: u16,
+        key: AccountId,
+        uids: Vec<u16>,
+        values: Vec<u16>,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<WeightsValidation> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.validate_weights(at, netuid, key, uids, values)
+            .map_err(runtime_error_into_rpc_err)
+    }
+
+    fn get_registration_preview(
+        &self,
+        netuid: u16,
+        stake: u64,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<RegistrationPreview> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.get_registration_preview(at, netuid, stake)
+            .map_err(runtime_error_into_rpc_err)
     }
 }
 
 const RUNTIME_ERROR: i32 = 1;
 
 /// Converts a runtime trap into an RPC error.
-fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> JsonRpseeError {
-    JsonRpseeError::Call(ErrorObject::owned(
-        RUNTIME_ERROR,
-        "Runtime error",
-        Some(format!("{:?}", err)),
-    ))
+fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> ErrorObjectOwned {
+    ErrorObject::owned(RUNTIME_ERROR, "Runtime error", Some(format!("{:?}", err)))
 }
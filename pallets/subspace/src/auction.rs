@@ -0,0 +1,178 @@
+use crate::subnet::SubnetChangeset;
+
+use super::*;
+
+use frame_support::{pallet_prelude::DispatchResult, storage::with_storage_layer};
+use frame_system::ensure_signed;
+use sp_core::ConstU32;
+use sp_runtime::{BoundedVec, DispatchError};
+use sp_std::vec::Vec;
+
+impl<T: Config> Pallet<T> {
+    /// Locks `amount` as the caller's bid for the next subnet slot. Bidding again raises the bid,
+    /// only locking the difference. The first bid opens the bidding window. Bids under the
+    /// configured minimum and new bidders over the configured maximum are rejected.
+    pub fn do_bid_subnet_slot(
+        origin: T::RuntimeOrigin,
+        name: BoundedVec<u8, ConstU32<256>>,
+        amount: u64,
+    ) -> DispatchResult {
+        let key = ensure_signed(origin)?;
+
+        let period = SubnetAuctionPeriod::<T>::get();
+        ensure!(period > 0, Error::<T>::SubnetAuctionDisabled);
+
+        // Fail early on names that could never be used to create the subnet.
+        SubnetChangeset::<T>::new(SubnetParams {
+            name: name.clone(),
+            founder: key.clone(),
+            ..DefaultSubnetParams::<T>::get()
+        })?;
+
+        let config = SubnetAuctionConfig::<T>::get();
+        ensure!(amount >= config.min_bid, Error::<T>::SubnetBidBelowMinimum);
+
+        let previous = match SubnetBids::<T>::get(&key) {
+            Some(bid) => bid.amount,
+            None => {
+                ensure!(
+                    SubnetBids::<T>::count() < config.max_bids,
+                    Error::<T>::TooManySubnetBids
+                );
+                0
+            }
+        };
+        ensure!(amount > previous, Error::<T>::SubnetBidTooLow);
+
+        let additional = amount.saturating_sub(previous);
+        ensure!(
+            Self::has_enough_balance(&key, additional),
+            Error::<T>::NotEnoughBalanceToRegister
+        );
+        let additional_as_balance =
+            Self::u64_to_balance(additional).ok_or(Error::<T>::CouldNotConvertToBalance)?;
+        Self::remove_balance_from_account(&key, additional_as_balance)?;
+
+        SubnetBids::<T>::insert(&key, SubnetBid { name, amount });
+
+        let auction_end = match SubnetAuctionEnd::<T>::get() {
+            Some(end) => end,
+            None => {
+                let end = Self::get_current_block_number().saturating_add(period);
+                SubnetAuctionEnd::<T>::put(end);
+                end
+            }
+        };
+
+        Self::deposit_event(Event::SubnetBidPlaced(key, amount, auction_end));
+
+        Ok(())
+    }
+
+    /// Closes the running auction once its bidding window is over. The highest bid whose subnet
+    /// can be created wins the slot and keeps its deposit locked, every other bid is refunded.
+    pub fn close_subnet_auction(block_number: u64) {
+        match SubnetAuctionEnd::<T>::get() {
+            Some(end) if end <= block_number => SubnetAuctionEnd::<T>::kill(),
+            _ => return,
+        }
+
+        let mut bids: Vec<_> = SubnetBids::<T>::drain().collect();
+        bids.sort_by(|(_, a), (_, b)| b.amount.cmp(&a.amount));
+
+        let mut slot_taken = false;
+        for (bidder, bid) in bids {
+            if !slot_taken {
+                let res = with_storage_layer(|| Self::assign_subnet_slot(&bidder, bid.name));
+                match res {
+                    Ok(netuid) => {
+                        SubnetDeposit::<T>::insert(netuid, (&bidder, bid.amount));
+                        Self::deposit_event(Event::SubnetAuctionWon(netuid, bidder, bid.amount));
+                        slot_taken = true;
+                        continue;
+                    }
+                    Err(err) => {
+                        log::warn!("could not create the subnet of bidder {bidder:?}: {err:?}")
+                    }
+                }
+            }
+
+            Self::add_balance_to_account(
+                &bidder,
+                Self::u64_to_balance(bid.amount).unwrap_or_default(),
+            );
+            Self::deposit_event(Event::SubnetBidRefunded(bidder, bid.amount));
+        }
+    }
+
+    /// Creates the subnet of an auction winner, taking over the slot of a subnet when all of them
+    /// are in use.
+    fn assign_subnet_slot(
+        founder: &T::AccountId,
+        name: BoundedVec<u8, ConstU32<256>>,
+    ) -> Result<u16, DispatchError> {
+        let params = SubnetParams {
+            name,
+            founder: founder.clone(),
+            ..DefaultSubnetParams::<T>::get()
+        };
+        let changeset = SubnetChangeset::new(params)?;
+
+        let target_subnet = if TotalSubnets::<T>::get() >= MaxAllowedSubnets::<T>::get() {
+            let netuid =
+                Self::get_subnet_slot_to_replace().ok_or(Error::<T>::NoSubnetSlotAvailable)?;
+            Self::forfeit_subnet_deposit(netuid);
+            Self::remove_subnet(netuid);
+            Some(netuid)
+        } else {
+            None
+        };
+
+        Self::add_subnet(changeset, target_subnet)
+    }
+
+    /// Returns the subnet whose slot goes to the auction winner: an expired subnet, which no
+    /// module registered on, or the least staked one. The global subnet and subnets still in
+    /// their immunity period are never replaced.
+    pub fn get_subnet_slot_to_replace() -> Option<u16> {
+        let current_block = Self::get_current_block_number();
+        let immunity_period = SubnetAuctionConfig::<T>::get().immunity_period;
+        let netuids: Vec<u16> = Self::netuids()
+            .into_iter()
+            .filter(|netuid| *netuid != 0)
+            .filter(|netuid| {
+                let registered_at = SubnetRegistrationBlock::<T>::get(netuid);
+                current_block.saturating_sub(registered_at) >= immunity_period
+            })
+            .collect();
+
+        netuids
+            .iter()
+            .copied()
+            .find(|netuid| N::<T>::get(netuid) == 0)
+            .or_else(|| netuids.into_iter().min_by_key(|netuid| TotalStake::<T>::get(netuid)))
+    }
+
+    /// Sends the deposit of a subnet that lost its slot to the DAO treasury instead of returning it
+    /// to the founder.
+    pub fn forfeit_subnet_deposit(netuid: u16) {
+        if let Some((founder, deposit)) = SubnetDeposit::<T>::take(netuid) {
+            Self::add_balance_to_account(
+                &T::get_dao_treasury_address(),
+                Self::u64_to_balance(deposit).unwrap_or_default(),
+            );
+            Self::deposit_event(Event::SubnetDepositForfeited(netuid, founder, deposit));
+        }
+    }
+
+    /// Returns the deposit of a subnet that is removed in good standing to its founder.
+    pub fn return_subnet_deposit(netuid: u16) {
+        if let Some((founder, deposit)) = SubnetDeposit::<T>::take(netuid) {
+            Self::add_balance_to_account(
+                &founder,
+                Self::u64_to_balance(deposit).unwrap_or_default(),
+            );
+            Self::deposit_event(Event::SubnetDepositReturned(netuid, founder, deposit));
+        }
+    }
+}
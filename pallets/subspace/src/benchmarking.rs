@@ -1,7 +1,8 @@
 #![cfg(feature = "runtime-benchmarks")]
 
-use crate::{voting::VoteMode, Pallet as SubspaceMod, *};
+use crate::{set_weights::MAX_SET_WEIGHTS_BATCH, Pallet as SubspaceMod, *};
 use frame_benchmarking::{account, benchmarks};
+use frame_support::{traits::Get, BoundedVec};
 use frame_system::RawOrigin;
 pub use pallet::*;
 use sp_arithmetic::per_things::Percent;
@@ -12,9 +13,18 @@ fn register_mock<T: Config>(
     module_key: T::AccountId,
     stake: u64,
     name: Vec<u8>,
+) -> Result<(), &'static str> {
+    register_mock_on::<T>(key, module_key, stake, name, "testnet".as_bytes().to_vec())
+}
+
+fn register_mock_on<T: Config>(
+    key: T::AccountId,
+    module_key: T::AccountId,
+    stake: u64,
+    name: Vec<u8>,
+    network: Vec<u8>,
 ) -> Result<(), &'static str> {
     let address = "test".as_bytes().to_vec();
-    let network = "testnet".as_bytes().to_vec();
     BurnConfig::<T>::mutate(|cfg| cfg.min_burn = 0);
     SubspaceMod::<T>::add_balance_to_account(
         &key,
@@ -46,6 +56,64 @@ fn submit_dao_application<T: Config>() -> Result<(), &'static str> {
     Ok(())
 }
 
+/// Registers `key` and a second module on `subnets` new subnets. `key` gets uid 0 and the second
+/// module uid 1 on each of them.
+fn register_on_subnets<T: Config>(
+    key: T::AccountId,
+    subnets: u32,
+) -> Result<Vec<u16>, &'static str> {
+    let other: T::AccountId = account("Other", 0, 4);
+    let stake = 100000000000000u64;
+    MaxAllowedSubnets::<T>::set(u16::MAX);
+    MaxRegistrationsPerBlock::<T>::set(u16::MAX);
+
+    let mut netuids = Vec::new();
+    for i in 0..subnets {
+        // Subnet names have to be unique and valid utf8.
+        let mut network = "subnet".as_bytes().to_vec();
+        network.extend(
+            i.to_be_bytes()
+                .iter()
+                .flat_map(|byte| [b'a' + (byte >> 4), b'a' + (byte & 0x0f)]),
+        );
+        register_mock_on::<T>(
+            key.clone(),
+            key.clone(),
+            stake,
+            "test".as_bytes().to_vec(),
+            network.clone(),
+        )?;
+        register_mock_on::<T>(
+            other.clone(),
+            other.clone(),
+            stake,
+            "test1".as_bytes().to_vec(),
+            network.clone(),
+        )?;
+        netuids
+            .push(SubspaceMod::<T>::get_netuid_for_name(&network).ok_or("subnet was not created")?);
+    }
+    Ok(netuids)
+}
+
+/// Turns on the call fees and rate limits without getting in the way of the benchmarks, so the
+/// calls take the paths that charge the fee and count the call.
+fn enable_fees_and_rate_limits<T: Config>() {
+    FeeConfig::<T>::set(global::FeeConfiguration {
+        set_weights_fee: 100,
+        refund_set_weights: false,
+        register_fee: 100,
+        transfer_multiple_fee: 100,
+    });
+    RateLimitConfig::<T>::set(global::RateLimitConfiguration {
+        interval: u64::MAX,
+        add_stake: u16::MAX,
+        remove_stake: u16::MAX,
+        transfer_stake: u16::MAX,
+        update_module: u16::MAX,
+    });
+}
+
 const REMOVE_WHEN_STAKING: u64 = 500;
 
 benchmarks! {
@@ -63,6 +131,7 @@ benchmarks! {
         register_mock::<T>(module_key2.clone(), module_key2.clone(), stake, "test1".as_bytes().to_vec())?;
         let uids = vec![0];
         let weights = vec![10];
+        enable_fees_and_rate_limits::<T>();
     }: set_weights(RawOrigin::Signed(module_key2), netuid, uids, weights)
 
     // ---------------------------------
@@ -80,6 +149,7 @@ benchmarks! {
             SubspaceMod::<T>::u64_to_balance(stake + 2000).unwrap(),
         );
         register_mock::<T>(module_key.clone(), module_key.clone(), stake.clone(), "test".as_bytes().to_vec())?;
+        enable_fees_and_rate_limits::<T>();
     }: add_stake(RawOrigin::Signed(key), netuid, module_key, stake)
 
     // 2
@@ -95,6 +165,7 @@ benchmarks! {
             SubspaceMod::<T>::u64_to_balance(amount).unwrap(),
         );
         SubspaceMod::<T>::add_stake(RawOrigin::Signed(caller.clone()).into(), netuid, module_key.clone(), amount - REMOVE_WHEN_STAKING)?;
+        enable_fees_and_rate_limits::<T>();
     }: remove_stake(RawOrigin::Signed(caller), netuid, module_key, amount - REMOVE_WHEN_STAKING)
 
     // ---------------------------------
@@ -118,6 +189,7 @@ benchmarks! {
         );
         // remove REMOVE_WHEN_STAKING from all amounts
         amounts.iter_mut().for_each(|x| *x -= REMOVE_WHEN_STAKING);
+        enable_fees_and_rate_limits::<T>();
     }: add_stake_multiple(RawOrigin::Signed(caller), netuid, module_keys, amounts)
 
     // 4
@@ -138,6 +210,7 @@ benchmarks! {
         // remove REMOVE_WHEN_STAKING from all amounts
         amounts.iter_mut().for_each(|x| *x -= REMOVE_WHEN_STAKING);
         SubspaceMod::<T>::add_stake_multiple(RawOrigin::Signed(caller.clone()).into(), netuid, module_keys.clone(), amounts.clone())?;
+        enable_fees_and_rate_limits::<T>();
     }: remove_stake_multiple(RawOrigin::Signed(caller), netuid, module_keys, amounts)
 
     // ---------------------------------
@@ -159,6 +232,7 @@ benchmarks! {
             SubspaceMod::<T>::u64_to_balance(amount).unwrap(),
         );
         SubspaceMod::<T>::add_stake(RawOrigin::Signed(caller.clone()).into(), netuid, module_key.clone(), amount - REMOVE_WHEN_STAKING)?;
+        enable_fees_and_rate_limits::<T>();
     }: transfer_stake(RawOrigin::Signed(caller), netuid, module_key, new_module_key, amount - REMOVE_WHEN_STAKING)
 
     // 6
@@ -174,7 +248,7 @@ benchmarks! {
         );
         // Reduce by REMOVE_WHEN_STAKING
         amounts.iter_mut().for_each(|x| *x -= REMOVE_WHEN_STAKING);
-
+        enable_fees_and_rate_limits::<T>();
     }: transfer_multiple(RawOrigin::Signed(caller), destinations, amounts)
 
     // ---------------------------------
@@ -190,6 +264,7 @@ benchmarks! {
             &key,
             SubspaceMod::<T>::u64_to_balance(stake + 2000).unwrap(),
         );
+        enable_fees_and_rate_limits::<T>();
     }: register(RawOrigin::Signed(key.clone()), "test".as_bytes().to_vec(), "test".as_bytes().to_vec(), "test".as_bytes().to_vec(), stake.into(), module_key.clone(), Some("metadata".as_bytes().to_vec()))
 
     // 8
@@ -214,6 +289,7 @@ benchmarks! {
         let address = "updated_address".as_bytes().to_vec();
         let delegation_fee = Some(Percent::from_percent(5));
         let metadata = Some("updated_metadata".as_bytes().to_vec());
+        enable_fees_and_rate_limits::<T>();
     }: update_module(RawOrigin::Signed(caller), netuid, name, address, delegation_fee, metadata)
 
 
@@ -240,8 +316,23 @@ benchmarks! {
         params.tempo,
         params.trust_ratio,
         params.maximum_set_weight_calls_per_epoch,
-        params.vote_mode,
-        params.bonds_ma
+        params.governance_config.vote_mode,
+        params.bonds_ma,
+        params.target_registrations_interval,
+        params.target_registrations_per_interval,
+        params.max_registrations_per_interval,
+        params.adjustment_alpha,
+        params.unbonding_period,
+        params.slash_ratio,
+        params.slash_threshold,
+        params.commit_reveal_weights,
+        params.weight_reveal_period,
+        params.consensus,
+        params.weight_history,
+        params.reward_history,
+        params.liquid_alpha,
+        params.alpha_low,
+        params.alpha_high
     )
     // ---------------------------------
     // Subnet 0 DAO
@@ -442,6 +533,123 @@ benchmarks! {
     //     SubspaceMod::<T>::vote_proposal(RawOrigin::Signed(caller.clone()).into(), proposal_id, true)?;
     // }: unvote_proposal(RawOrigin::Signed(caller), proposal_id)
 
+    // ---------------------------------
+    // Batched and committed weights
+    // ---------------------------------
+
+    // 24
+    set_weights_multiple {
+        let n in 1 .. (MAX_SET_WEIGHTS_BATCH as u32);
+        let key: T::AccountId = account("Alice", 0, 1);
+        let netuids = register_on_subnets::<T>(key.clone(), n)?;
+        let weights = netuids.into_iter().map(|netuid| (netuid, vec![1], vec![10])).collect::<Vec<_>>();
+        enable_fees_and_rate_limits::<T>();
+    }: set_weights_multiple(RawOrigin::Signed(key), weights)
+
+    // 25
+    commit_weights {
+        let netuid = 0;
+        let module_key: T::AccountId = account("ModuleKey", 0, 2);
+        let module_key2: T::AccountId = account("ModuleKey2", 0, 3);
+        let stake = 100000000000000u64;
+        register_mock::<T>(module_key.clone(), module_key.clone(), stake, "test".as_bytes().to_vec())?;
+        register_mock::<T>(module_key2.clone(), module_key2.clone(), stake, "test1".as_bytes().to_vec())?;
+        CommitRevealWeights::<T>::insert(netuid, true);
+        let commit_hash = SubspaceMod::<T>::hash_weights(&module_key2, netuid, &[0], &[10], "salt".as_bytes());
+        enable_fees_and_rate_limits::<T>();
+    }: commit_weights(RawOrigin::Signed(module_key2), netuid, commit_hash)
+
+    // 26
+    reveal_weights {
+        let netuid = 0;
+        let module_key: T::AccountId = account("ModuleKey", 0, 2);
+        let module_key2: T::AccountId = account("ModuleKey2", 0, 3);
+        let stake = 100000000000000u64;
+        register_mock::<T>(module_key.clone(), module_key.clone(), stake, "test".as_bytes().to_vec())?;
+        register_mock::<T>(module_key2.clone(), module_key2.clone(), stake, "test1".as_bytes().to_vec())?;
+        CommitRevealWeights::<T>::insert(netuid, true);
+        let uids = vec![0];
+        let weights = vec![10];
+        let salt = "salt".as_bytes().to_vec();
+        let commit_hash = SubspaceMod::<T>::hash_weights(&module_key2, netuid, &uids, &weights, &salt);
+        SubspaceMod::<T>::commit_weights(RawOrigin::Signed(module_key2.clone()).into(), netuid, commit_hash)?;
+        // Reveals are only accepted once the epoch of the commit is over.
+        let tempo: u32 = Tempo::<T>::get(netuid).into();
+        frame_system::Pallet::<T>::set_block_number(frame_system::Pallet::<T>::block_number() + tempo.into());
+        enable_fees_and_rate_limits::<T>();
+    }: reveal_weights(RawOrigin::Signed(module_key2), netuid, uids, weights, salt)
+
+    // ---------------------------------
+    // Unbonding
+    // ---------------------------------
+
+    // 27
+    withdraw_unbonded {
+        let s in 1 .. DefaultMaxAllowedSubnets::<T>::get().into();
+        let key: T::AccountId = account("Alice", 0, 1);
+        for netuid in register_on_subnets::<T>(key.clone(), s)? {
+            Unlocking::<T>::insert(netuid, &key, vec![UnlockChunk { amount: 1000, unlock_block: 0 }]);
+        }
+    }: withdraw_unbonded(RawOrigin::Signed(key))
+
+    // 28
+    set_auto_compound {
+        let key: T::AccountId = account("Alice", 0, 1);
+        let module_key: T::AccountId = account("ModuleKey", 0, 2);
+    }: set_auto_compound(RawOrigin::Signed(key), Some(module_key), Some(false))
+
+    // ---------------------------------
+    // Module keys
+    // ---------------------------------
+
+    // 29
+    rotate_module_key {
+        let s in 1 .. DefaultMaxAllowedSubnets::<T>::get().into();
+        let key: T::AccountId = account("Alice", 0, 1);
+        let new_key: T::AccountId = account("Bob", 0, 2);
+        let netuid = register_on_subnets::<T>(key.clone(), s)?.into_iter().next().ok_or("no subnet")?;
+    }: rotate_module_key(RawOrigin::Signed(key.clone()), netuid, key, new_key)
+
+    // 30
+    set_module_controller {
+        let netuid = 0;
+        let module_key: T::AccountId = account("ModuleKey", 0, 2);
+        let controller: T::AccountId = account("Controller", 0, 3);
+        let stake = 100000000000000u64;
+        register_mock::<T>(module_key.clone(), module_key.clone(), stake, "test".as_bytes().to_vec())?;
+    }: set_module_controller(RawOrigin::Signed(module_key.clone()), netuid, module_key, controller)
+
+    // 31
+    controller_update_module {
+        let netuid = 0;
+        let module_key: T::AccountId = account("ModuleKey", 0, 2);
+        let controller: T::AccountId = account("Controller", 0, 3);
+        let stake = 100000000000000u64;
+        register_mock::<T>(module_key.clone(), module_key.clone(), stake, "test".as_bytes().to_vec())?;
+        SubspaceMod::<T>::set_module_controller(RawOrigin::Signed(module_key.clone()).into(), netuid, module_key.clone(), controller.clone())?;
+        let name = "updated_name".as_bytes().to_vec();
+        let address = "updated_address".as_bytes().to_vec();
+        let delegation_fee = Some(Percent::from_percent(5));
+        let metadata = Some("updated_metadata".as_bytes().to_vec());
+        enable_fees_and_rate_limits::<T>();
+    }: controller_update_module(RawOrigin::Signed(controller), netuid, module_key, name, address, delegation_fee, metadata)
+
+    // ---------------------------------
+    // Subnet slot auction
+    // ---------------------------------
+
+    // 32
+    bid_subnet_slot {
+        let caller: T::AccountId = account("Alice", 0, 1);
+        let amount = 100000000000000u64;
+        SubnetAuctionPeriod::<T>::set(100);
+        SubspaceMod::<T>::add_balance_to_account(
+            &caller,
+            SubspaceMod::<T>::u64_to_balance(amount + 2000).unwrap(),
+        );
+        let name = BoundedVec::truncate_from("auction".as_bytes().to_vec());
+    }: bid_subnet_slot(RawOrigin::Signed(caller), name, amount)
+
     // ---------------------------------
     // Testnet
     // ---------------------------------
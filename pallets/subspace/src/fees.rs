@@ -0,0 +1,63 @@
+use super::*;
+
+use sp_runtime::transaction_validity::{InvalidTransaction, TransactionValidityError};
+
+impl<T: Config> Pallet<T> {
+    /// Returns the extra fee this kind of call pays.
+    pub fn call_fee(call_type: &CallType) -> u64 {
+        let config = FeeConfig::<T>::get();
+        match call_type {
+            CallType::SetWeights => config.set_weights_fee,
+            CallType::Register => config.register_fee,
+            CallType::TransferMultiple => config.transfer_multiple_fee,
+            _ => 0,
+        }
+    }
+
+    /// Ensures the account can pay the fee without touching its balance.
+    pub fn ensure_can_pay_call_fee(
+        who: &T::AccountId,
+        fee: u64,
+    ) -> Result<(), TransactionValidityError> {
+        if fee != 0 && !Self::has_enough_balance(who, fee) {
+            return Err(InvalidTransaction::Payment.into());
+        }
+
+        Ok(())
+    }
+
+    /// Takes the fee of a call that is being dispatched. The dispatchables with a fee call this
+    /// themselves, so calls wrapped in batches or multisigs pay too. Weight setting calls keep
+    /// their fee when they succeed and refunds are on.
+    pub fn charge_call_fee(
+        origin: &T::RuntimeOrigin,
+        call_type: CallType,
+    ) -> dispatch::DispatchResult {
+        let who = ensure_signed(origin.clone())?;
+        if call_type == CallType::SetWeights && FeeConfig::<T>::get().refund_set_weights {
+            return Ok(());
+        }
+
+        Self::pay_call_fee(&who, Self::call_fee(&call_type))
+    }
+
+    /// Sends a fee from the account to the treasury.
+    pub fn pay_call_fee(who: &T::AccountId, fee: u64) -> dispatch::DispatchResult {
+        if fee == 0 {
+            return Ok(());
+        }
+
+        ensure!(
+            Self::has_enough_balance(who, fee),
+            Error::<T>::NotEnoughBalanceToPayFee
+        );
+        let fee_as_balance =
+            Self::u64_to_balance(fee).ok_or(Error::<T>::CouldNotConvertToBalance)?;
+        Self::remove_balance_from_account(who, fee_as_balance)?;
+        Self::add_balance_to_account(&T::get_dao_treasury_address(), fee_as_balance);
+
+        Self::deposit_event(Event::CallFeePaid(who.clone(), fee));
+
+        Ok(())
+    }
+}
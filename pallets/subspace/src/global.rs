@@ -2,6 +2,18 @@ use super::*;
 use frame_support::pallet_prelude::{DispatchResult, MaxEncodedLen};
 use sp_runtime::DispatchError;
 
+/// Where the burn paid on module registration goes.
+#[derive(Clone, Copy, TypeInfo, Decode, Encode, PartialEq, Eq, Debug, Default, MaxEncodedLen)]
+pub enum BurnDestination {
+    /// The burn is removed from circulation.
+    #[default]
+    Destroy,
+    /// The burn is recycled to the DAO treasury.
+    DaoTreasury,
+    /// The burn is paid to the founder of the subnet the module registered on.
+    SubnetFounder,
+}
+
 // TODO:
 // This will eventually become a subnet parameter (once we have global stake)
 // So it will hold truly all burn adjustments.
@@ -14,6 +26,8 @@ pub struct BurnConfiguration<T> {
     pub min_burn: u64,
     /// max burn the adjustment algorithm can set
     pub max_burn: u64,
+    /// where the registration burn goes
+    pub destination: BurnDestination,
     pub _pd: PhantomData<T>,
 }
 
@@ -22,6 +36,7 @@ impl<T: Config> Default for BurnConfiguration<T> {
         Self {
             min_burn: 4_000_000_000,
             max_burn: 250_000_000_000,
+            destination: BurnDestination::Destroy,
             _pd: PhantomData,
         }
     }
@@ -38,6 +53,113 @@ impl<T: Config> BurnConfiguration<T> {
     }
 }
 
+/// Kinds of calls limited per account.
+#[derive(Clone, Copy, TypeInfo, Decode, Encode, PartialEq, Eq, Debug, MaxEncodedLen)]
+pub enum RateLimitedCall {
+    AddStake,
+    RemoveStake,
+    TransferStake,
+    UpdateModule,
+}
+
+impl RateLimitedCall {
+    /// `InvalidTransaction::Custom` code a call is rejected with once its limit is reached.
+    pub fn error_code(self) -> u8 {
+        match self {
+            Self::AddStake => 1,
+            Self::RemoveStake => 2,
+            Self::TransferStake => 3,
+            Self::UpdateModule => 4,
+        }
+    }
+}
+
+/// Limits on how many calls of each kind an account can get into the blocks of an interval.
+/// Disabled until governance sets an interval and the limits.
+#[derive(Clone, TypeInfo, Decode, Encode, PartialEq, Eq, Debug, MaxEncodedLen)]
+pub struct RateLimitConfiguration {
+    /// blocks after which the call counters reset, 0 disables the limits
+    pub interval: u64,
+    /// max `add_stake` and `add_stake_multiple` calls per interval
+    pub add_stake: u16,
+    /// max `remove_stake`, `remove_stake_multiple` and `controller_remove_stake` calls per
+    /// interval
+    pub remove_stake: u16,
+    /// max `transfer_stake` and `transfer_multiple` calls per interval
+    pub transfer_stake: u16,
+    /// max `update_module` and `controller_update_module` calls per interval
+    pub update_module: u16,
+}
+
+impl Default for RateLimitConfiguration {
+    fn default() -> Self {
+        Self {
+            interval: 0,
+            add_stake: 0,
+            remove_stake: 0,
+            transfer_stake: 0,
+            update_module: 0,
+        }
+    }
+}
+
+impl RateLimitConfiguration {
+    /// Max calls of the kind per interval, 0 meaning unlimited.
+    pub fn limit(&self, call: RateLimitedCall) -> u16 {
+        match call {
+            RateLimitedCall::AddStake => self.add_stake,
+            RateLimitedCall::RemoveStake => self.remove_stake,
+            RateLimitedCall::TransferStake => self.transfer_stake,
+            RateLimitedCall::UpdateModule => self.update_module,
+        }
+    }
+}
+
+/// Extra fees some calls pay to the treasury on top of the transaction payment.
+#[derive(Clone, TypeInfo, Decode, Encode, PartialEq, Eq, Debug, MaxEncodedLen)]
+pub struct FeeConfiguration {
+    /// fee taken on weight setting calls
+    pub set_weights_fee: u64,
+    /// whether weight setting calls only pay the fee when they fail
+    pub refund_set_weights: bool,
+    /// fee taken on `register`, on top of the burn
+    pub register_fee: u64,
+    /// fee taken on `transfer_multiple`
+    pub transfer_multiple_fee: u64,
+}
+
+impl Default for FeeConfiguration {
+    fn default() -> Self {
+        Self {
+            set_weights_fee: 0,
+            refund_set_weights: true,
+            register_fee: 0,
+            transfer_multiple_fee: 0,
+        }
+    }
+}
+
+/// Limits of the subnet slot auction.
+#[derive(Clone, TypeInfo, Decode, Encode, PartialEq, Eq, Debug, MaxEncodedLen)]
+pub struct SubnetAuctionConfiguration {
+    /// min amount a bid has to lock
+    pub min_bid: u64,
+    /// max number of accounts bidding in the same auction
+    pub max_bids: u32,
+    /// blocks during which a new subnet can not lose its slot to an auction winner
+    pub immunity_period: u64,
+}
+
+impl Default for SubnetAuctionConfiguration {
+    fn default() -> Self {
+        Self {
+            min_bid: 1_000_000_000_000,
+            max_bids: 32,
+            immunity_period: 10_800,
+        }
+    }
+}
+
 impl<T: Config> Pallet<T> {
     pub fn global_params() -> GlobalParams<T> {
         GlobalParams {
@@ -56,6 +178,12 @@ impl<T: Config> Pallet<T> {
             max_allowed_weights: MaxAllowedWeightsGlobal::<T>::get(),
             subnet_stake_threshold: SubnetStakeThreshold::<T>::get(),
             min_weight_stake: MinWeightStake::<T>::get(),
+            // staking
+            unbonding_period: UnbondingPeriodGlobal::<T>::get(),
+            subnet_auction_period: SubnetAuctionPeriod::<T>::get(),
+            subnet_auction_config: SubnetAuctionConfig::<T>::get(),
+            rate_limit_config: RateLimitConfig::<T>::get(),
+            fee_config: FeeConfig::<T>::get(),
 
             // s0 config
             general_subnet_application_cost: T::get_general_subnet_application_cost(),
@@ -89,6 +217,17 @@ impl<T: Config> Pallet<T> {
         MaxAllowedWeightsGlobal::<T>::put(params.max_allowed_weights);
         MinWeightStake::<T>::put(params.min_weight_stake);
 
+        // staking
+        UnbondingPeriodGlobal::<T>::put(params.unbonding_period);
+        SubnetAuctionPeriod::<T>::put(params.subnet_auction_period);
+        SubnetAuctionConfig::<T>::put(params.subnet_auction_config);
+
+        // rate limits
+        RateLimitConfig::<T>::put(params.rate_limit_config);
+
+        // fees
+        FeeConfig::<T>::put(params.fee_config);
+
         T::update_global_governance_configuration(params.governance_config)
             .expect("invalid governance configuration");
 
@@ -161,6 +300,26 @@ impl<T: Config> Pallet<T> {
             Error::<T>::InvalidProposalExpiration
         );
 
+        ensure!(
+            params.unbonding_period <= MAX_UNBONDING_PERIOD,
+            Error::<T>::InvalidUnbondingPeriod
+        );
+
+        ensure!(
+            params.subnet_auction_period <= MAX_SUBNET_AUCTION_PERIOD,
+            Error::<T>::InvalidSubnetAuctionPeriod
+        );
+
+        let auction_config = &params.subnet_auction_config;
+        ensure!(
+            auction_config.max_bids > 0 && auction_config.max_bids <= MAX_SUBNET_BIDS,
+            Error::<T>::InvalidSubnetAuctionConfig
+        );
+        ensure!(
+            auction_config.immunity_period <= MAX_SUBNET_AUCTION_IMMUNITY_PERIOD,
+            Error::<T>::InvalidSubnetAuctionConfig
+        );
+
         Ok(())
     }
 }
@@ -2,7 +2,7 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![recursion_limit = "512"]
 
-use crate::subnet::SubnetChangeset;
+use crate::{global::RateLimitedCall, subnet::SubnetChangeset};
 use frame_system::{self as system, ensure_signed};
 pub use pallet::*;
 use scale_info::TypeInfo;
@@ -37,14 +37,19 @@ mod benchmarking;
 // Pallet Imports
 // ---------------------------------
 
+mod auction;
+mod fees;
 pub mod global;
 mod math;
 pub mod module;
-mod registration;
-mod set_weights;
+mod rate_limit;
+pub mod registration;
+mod reward_history;
+pub mod set_weights;
 mod staking;
 mod step;
 pub mod subnet;
+mod weight_history;
 pub mod weights; // Weight benchmarks // Commune consensus weights
 
 #[cfg(debug_assertions)]
@@ -64,13 +69,17 @@ pub mod pallet {
     pub use crate::weights::WeightInfo;
     use frame_support::{pallet_prelude::*, traits::Currency, Identity};
     use frame_system::pallet_prelude::*;
-    use global::BurnConfiguration;
+    use global::{
+        BurnConfiguration, BurnDestination, FeeConfiguration, RateLimitConfiguration,
+        RateLimitedCall, SubnetAuctionConfiguration,
+    };
     use module::ModuleChangeset;
-    use pallet_governance_api::{GovernanceConfiguration, VoteMode};
+    use pallet_governance_api::{GovernanceConfiguration, VoteMode, MAX_VOTING_POWER_DELEGATIONS};
     use sp_arithmetic::per_things::Percent;
+    use sp_core::H256;
     pub use sp_std::{vec, vec::Vec};
 
-    const STORAGE_VERSION: StorageVersion = StorageVersion::new(11);
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(12);
 
     #[pallet::pallet]
     #[pallet::storage_version(STORAGE_VERSION)]
@@ -96,6 +105,18 @@ pub mod pallet {
 
         /// The weight information of this pallet.
         type WeightInfo: WeightInfo;
+
+        /// Number of epochs of reward records kept per account on subnets that opted into the
+        /// reward history. Zero disables the reward history on every subnet.
+        #[pallet::constant]
+        #[pallet::no_default]
+        type RewardHistoryDepth: Get<u32>;
+
+        /// Number of weight vectors and validator epoch records kept per module on subnets that
+        /// opted into the weight history.
+        #[pallet::constant]
+        #[pallet::no_default]
+        type WeightHistoryDepth: Get<u32>;
     }
 
     pub type BalanceOf<T> =
@@ -147,6 +168,24 @@ pub mod pallet {
     pub type BondsMovingAverage<T> =
         StorageMap<_, Identity, u16, u64, ValueQuery, DefaultBondsMovingAverage<T>>;
 
+    #[pallet::storage] // --- MAP ( netuid ) --> liquid_alpha
+    pub type LiquidAlphaEnabled<T> = StorageMap<_, Identity, u16, bool, ValueQuery>;
+
+    #[pallet::type_value]
+    pub fn DefaultAlphaValues<T: Config>() -> (u16, u16) {
+        (6_553, 32_767) // ~0.1, the alpha of the default bonds moving average, and ~0.5
+    }
+
+    #[pallet::storage] // --- MAP ( netuid ) --> (alpha_low, alpha_high)
+    pub type AlphaValues<T> =
+        StorageMap<_, Identity, u16, (u16, u16), ValueQuery, DefaultAlphaValues<T>>;
+
+    #[pallet::storage] // --- MAP ( netuid ) --> slash_ratio
+    pub type SlashRatio<T> = StorageMap<_, Identity, u16, Percent, ValueQuery>;
+
+    #[pallet::storage] // --- MAP ( netuid ) --> slash_threshold
+    pub type SlashThreshold<T> = StorageMap<_, Identity, u16, Percent, ValueQuery>;
+
     #[pallet::storage] // --- DMAP ( netuid ) --> validator_permit
     pub type ValidatorPermits<T: Config> = StorageMap<_, Identity, u16, Vec<bool>, ValueQuery>;
 
@@ -253,6 +292,27 @@ pub mod pallet {
     pub type SetWeightCallsPerEpoch<T: Config> =
         StorageDoubleMap<_, Identity, u16, Identity, T::AccountId, u16, ValueQuery>;
 
+    #[pallet::storage] // --- MAP ( netuid ) --> commit_reveal_weights
+    pub type CommitRevealWeights<T> = StorageMap<_, Identity, u16, bool, ValueQuery>;
+
+    #[pallet::type_value]
+    pub fn DefaultWeightRevealPeriod<T: Config>() -> u16 {
+        1
+    }
+    #[pallet::storage] // --- MAP ( netuid ) --> weight_reveal_period
+    pub type WeightRevealPeriod<T> =
+        StorageMap<_, Identity, u16, u16, ValueQuery, DefaultWeightRevealPeriod<T>>;
+
+    #[pallet::storage] // --- MAP ( netuid ) --> weight_history
+    pub type WeightHistoryEnabled<T> = StorageMap<_, Identity, u16, bool, ValueQuery>;
+
+    #[pallet::storage] // --- MAP ( netuid ) --> reward_history
+    pub type RewardHistoryEnabled<T> = StorageMap<_, Identity, u16, bool, ValueQuery>;
+
+    #[pallet::storage] // --- DMAP ( netuid, key ) --> weight_commit
+    pub type WeightCommits<T: Config> =
+        StorageDoubleMap<_, Identity, u16, Identity, T::AccountId, WeightCommit, OptionQuery>;
+
     #[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo)]
     #[scale_info(skip_type_params(T))]
     pub struct ModuleParams<T: Config> {
@@ -263,6 +323,74 @@ pub mod pallet {
         pub controller: T::AccountId,
     }
 
+    /// Maximum number of pending unlocking chunks per account on a subnet.
+    pub const MAX_UNLOCKING_CHUNKS: usize = 32;
+    /// Upper bound for the unbonding period, roughly 30 days of 8 second blocks.
+    pub const MAX_UNBONDING_PERIOD: u64 = 324_000;
+
+    /// Stake that was removed from a module and is waiting to be withdrawn.
+    #[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, MaxEncodedLen)]
+    pub struct UnlockChunk {
+        /// Amount of tokens that will be released.
+        pub amount: u64,
+        /// Block from which the amount can be withdrawn.
+        pub unlock_block: u64,
+    }
+
+    /// Upper bound for the subnet slot auction bidding window, roughly 7 days of 8 second blocks.
+    pub const MAX_SUBNET_AUCTION_PERIOD: u64 = 75_600;
+
+    /// Upper bound for the bids of one auction, all of them are settled in the same block.
+    pub const MAX_SUBNET_BIDS: u32 = 256;
+
+    /// Upper bound for the immunity period of new subnets, roughly 30 days of 8 second blocks.
+    pub const MAX_SUBNET_AUCTION_IMMUNITY_PERIOD: u64 = 324_000;
+
+    /// Deposit locked by an account to bid for a subnet slot.
+    #[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, MaxEncodedLen)]
+    pub struct SubnetBid {
+        /// Name of the subnet that is created if the bid wins.
+        pub name: BoundedVec<u8, ConstU32<256>>,
+        /// Amount of tokens locked by the bid.
+        pub amount: u64,
+    }
+
+    /// Rewards an account earned on a subnet during one epoch.
+    #[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, MaxEncodedLen)]
+    pub struct RewardRecord {
+        /// Block the epoch ran at.
+        pub block: u64,
+        /// Total amount earned, either as stake or as free balance.
+        pub amount: u64,
+    }
+
+    /// Weights a validator set on a subnet that keeps the weight history.
+    #[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo)]
+    pub struct WeightRecord {
+        /// Block the weights were set at.
+        pub block: u64,
+        /// Normalized weights, as `(uid, weight)` pairs.
+        pub weights: Vec<(u16, u16)>,
+    }
+
+    /// Validator trust and dividends a module had at the end of an epoch.
+    #[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, MaxEncodedLen)]
+    pub struct ValidatorEpochRecord {
+        /// Block the epoch ran at.
+        pub block: u64,
+        pub validator_trust: u16,
+        pub dividends: u16,
+    }
+
+    /// Hash of weights committed by a validator, waiting to be revealed.
+    #[derive(Decode, Encode, PartialEq, Eq, Clone, Debug, TypeInfo, MaxEncodedLen)]
+    pub struct WeightCommit {
+        /// Blake2 hash of the encoded `(key, netuid, uids, values, salt)` tuple.
+        pub hash: H256,
+        /// Block the commit was submitted at.
+        pub commit_block: u64,
+    }
+
     #[derive(
         Decode, Encode, PartialEq, Eq, Clone, TypeInfo, frame_support::DebugNoBound, MaxEncodedLen,
     )]
@@ -280,6 +408,11 @@ pub mod pallet {
         pub floor_delegation_fee: Percent, // min delegation fee
         pub floor_founder_share: u8,       // min founder share
         pub min_weight_stake: u64,         // min weight stake required
+        pub unbonding_period: u64,         // min blocks removed stake stays locked
+        pub subnet_auction_period: u64,    // subnet slot bidding window, 0 disables the auction
+        pub subnet_auction_config: SubnetAuctionConfiguration, // bid and slot limits of the auction
+        pub rate_limit_config: RateLimitConfiguration, // per account limits on staking calls
+        pub fee_config: FeeConfiguration,  // extra fees of some calls
 
         // S0 governance
         pub curator: T::AccountId,
@@ -314,11 +447,22 @@ pub mod pallet {
                 founder: DefaultKey::<T>::get(),
                 maximum_set_weight_calls_per_epoch: 0,
                 bonds_ma: DefaultBondsMovingAverage::<T>::get(),
+                liquid_alpha: false,
+                alpha_low: DefaultAlphaValues::<T>::get().0,
+                alpha_high: DefaultAlphaValues::<T>::get().1,
                 target_registrations_interval: DefaultTargetRegistrationsInterval::<T>::get(),
                 target_registrations_per_interval: DefaultTargetRegistrationsPerInterval::<T>::get(
                 ),
                 max_registrations_per_interval: 42,
                 adjustment_alpha: DefaultAdjustmentAlpha::<T>::get(),
+                unbonding_period: 0,
+                slash_ratio: Percent::from_percent(0),
+                slash_threshold: Percent::from_percent(0),
+                commit_reveal_weights: false,
+                weight_reveal_period: DefaultWeightRevealPeriod::<T>::get(),
+                consensus: SubnetConsensus::Yuma,
+                weight_history: false,
+                reward_history: false,
                 governance_config: GovernanceConfiguration {
                     vote_mode: VoteMode::Authority,
                     ..Default::default()
@@ -350,15 +494,48 @@ pub mod pallet {
         pub maximum_set_weight_calls_per_epoch: u16,
         // consensus
         pub bonds_ma: u64,
+        pub liquid_alpha: bool, // bonds alpha follows how much each weight agrees with consensus
+        pub alpha_low: u16,     // bonds alpha of weights out of consensus, out of u16::MAX
+        pub alpha_high: u16,    // bonds alpha of weights in consensus, out of u16::MAX
         // registrations
         pub target_registrations_interval: u16,
         pub target_registrations_per_interval: u16,
         pub max_registrations_per_interval: u16,
         pub adjustment_alpha: u64,
+        // staking
+        pub unbonding_period: u64,
+        // slashing
+        pub slash_ratio: Percent, // share of the stake taken from an out of consensus validator
+        pub slash_threshold: Percent, // validator trust under which a validator gets slashed
+        // weights
+        pub commit_reveal_weights: bool, // weights have to be committed before being revealed
+        pub weight_reveal_period: u16,   // epochs a commit can be revealed in
+        pub weight_history: bool,        // past weights and validator epochs are kept
+        pub reward_history: bool,        // past rewards of every account are kept
+        // epoch
+        pub consensus: SubnetConsensus, // how the subnet distributes its emission
 
         pub governance_config: GovernanceConfiguration,
     }
 
+    /// Consensus a subnet runs on each epoch to distribute its emission.
+    #[derive(
+        Decode, Encode, PartialEq, Eq, Clone, Copy, Debug, Default, TypeInfo, MaxEncodedLen,
+    )]
+    pub enum SubnetConsensus {
+        /// Incentive and dividends weighted by stake, used by the global subnet.
+        Linear,
+        /// Yuma consensus, with bonds and clipping of weights out of consensus.
+        #[default]
+        Yuma,
+        /// No consensus, the whole emission is relayed to the subnet founder. Only governance
+        /// can switch a subnet to it.
+        Root,
+    }
+
+    #[pallet::storage] // --- MAP ( netuid ) --> consensus
+    pub type SubnetConsensusType<T> = StorageMap<_, Identity, u16, SubnetConsensus>;
+
     #[pallet::type_value]
     pub fn DefaultMaxAllowedUids<T: Config>() -> u16 {
         820
@@ -545,6 +722,11 @@ pub mod pallet {
         DefaultDelegationFee<T>,
     >;
 
+    #[pallet::storage] // --- DMAP ( netuid, module_key ) --> controller | Cold account managing the
+                       // module's stake, fee and registration. The module key itself when unset
+    pub type Controller<T: Config> =
+        StorageDoubleMap<_, Identity, u16, Identity, T::AccountId, T::AccountId>;
+
     #[pallet::storage] // --- DMAP ( netuid, uid ) --> block number that the module is registered
     pub type RegistrationBlock<T: Config> =
         StorageDoubleMap<_, Identity, u16, Identity, u16, u64, ValueQuery>;
@@ -587,6 +769,50 @@ pub mod pallet {
     #[pallet::storage] // --- MAP ( netuid ) --> subnet_total_stake
     pub type TotalStake<T> = StorageMap<_, Identity, u16, u64, ValueQuery>;
 
+    #[pallet::storage] // --- ITEM ( unbonding_period_global )
+    pub type UnbondingPeriodGlobal<T> = StorageValue<_, u64, ValueQuery>;
+
+    #[pallet::storage] // --- ITEM ( subnet_auction_period )
+    pub type SubnetAuctionPeriod<T> = StorageValue<_, u64, ValueQuery>;
+
+    #[pallet::storage] // --- ITEM ( block the running subnet slot auction closes at )
+    pub type SubnetAuctionEnd<T> = StorageValue<_, u64>;
+
+    #[pallet::storage] // --- ITEM ( subnet_auction_config )
+    pub type SubnetAuctionConfig<T> = StorageValue<_, SubnetAuctionConfiguration, ValueQuery>;
+
+    #[pallet::storage] // --- MAP ( bidder ) --> SubnetBid
+    pub type SubnetBids<T: Config> = CountedStorageMap<_, Identity, T::AccountId, SubnetBid>;
+
+    #[pallet::storage] // --- MAP ( netuid ) --> (founder, deposit) | Deposit of an auctioned subnet
+    pub type SubnetDeposit<T: Config> = StorageMap<_, Identity, u16, (T::AccountId, u64)>;
+
+    #[pallet::storage] // --- MAP ( netuid ) --> block the subnet was added at
+    pub type SubnetRegistrationBlock<T> = StorageMap<_, Identity, u16, u64, ValueQuery>;
+
+    #[pallet::storage] // --- ITEM ( rate_limit_config )
+    pub type RateLimitConfig<T> = StorageValue<_, RateLimitConfiguration, ValueQuery>;
+
+    #[pallet::storage] // --- ITEM ( fee_config )
+    pub type FeeConfig<T> = StorageValue<_, FeeConfiguration, ValueQuery>;
+
+    #[pallet::storage] // --- DMAP ( account_id, call ) --> calls included in the current interval
+    pub type RateLimitUsage<T: Config> =
+        StorageDoubleMap<_, Identity, T::AccountId, Identity, RateLimitedCall, u16, ValueQuery>;
+
+    #[pallet::storage] // --- MAP ( netuid ) --> unbonding_period
+    pub type UnbondingPeriod<T> = StorageMap<_, Identity, u16, u64, ValueQuery>;
+
+    #[pallet::storage] // --- DMAP ( netuid, account_id ) --> Vec<UnlockChunk> | Removed stake waiting
+                       // to be withdrawn
+    pub type Unlocking<T: Config> =
+        StorageDoubleMap<_, Identity, u16, Identity, T::AccountId, Vec<UnlockChunk>, ValueQuery>;
+
+    #[pallet::storage] // --- DMAP ( delegator, Option<module_key> ) --> auto_compound | Whether the
+                       // delegator's dividends are restaked, per module or globally (None)
+    pub type AutoCompound<T: Config> =
+        StorageDoubleMap<_, Identity, T::AccountId, Identity, Option<T::AccountId>, bool>;
+
     // ---------------------------------
     // Module Consensus Variables
     // ---------------------------------
@@ -606,6 +832,52 @@ pub mod pallet {
     pub type Weights<T: Config> =
         StorageDoubleMap<_, Identity, u16, Identity, u16, Vec<(u16, u16)>, ValueQuery>;
 
+    #[pallet::storage] // --- DMAP ( netuid, account_id ) --> Vec<RewardRecord> | Rewards earned on the
+                       // last `RewardHistoryDepth` epochs, oldest first
+    pub type RewardHistory<T: Config> = StorageDoubleMap<
+        _,
+        Identity,
+        u16,
+        Identity,
+        T::AccountId,
+        BoundedVec<RewardRecord, T::RewardHistoryDepth>,
+        ValueQuery,
+    >;
+
+    #[pallet::storage] // --- MAP ( netuid ) --> Vec<block> | Blocks of the epochs kept in the reward
+                       // history, oldest first
+    pub type RewardHistoryEpochs<T: Config> =
+        StorageMap<_, Identity, u16, BoundedVec<u64, T::RewardHistoryDepth>, ValueQuery>;
+
+    #[pallet::storage] // --- DMAP ( netuid, block ) --> Vec<account_id> | Accounts that have a reward
+                       // record for the epoch
+    pub type RewardedAccounts<T: Config> =
+        StorageDoubleMap<_, Identity, u16, Identity, u64, Vec<T::AccountId>, ValueQuery>;
+
+    #[pallet::storage] // --- DMAP ( netuid, uid ) --> Vec<WeightRecord> | Last `WeightHistoryDepth`
+                       // weight vectors set by the module, oldest first
+    pub type WeightHistory<T: Config> = StorageDoubleMap<
+        _,
+        Identity,
+        u16,
+        Identity,
+        u16,
+        BoundedVec<WeightRecord, T::WeightHistoryDepth>,
+        ValueQuery,
+    >;
+
+    #[pallet::storage] // --- DMAP ( netuid, uid ) --> Vec<ValidatorEpochRecord> | Last
+                       // `WeightHistoryDepth` epochs of the module, oldest first
+    pub type ValidatorHistory<T: Config> = StorageDoubleMap<
+        _,
+        Identity,
+        u16,
+        Identity,
+        u16,
+        BoundedVec<ValidatorEpochRecord, T::WeightHistoryDepth>,
+        ValueQuery,
+    >;
+
     // ---------------------------------
     // Event Variables
     // ---------------------------------
@@ -615,20 +887,49 @@ pub mod pallet {
     pub enum Event<T: Config> {
         NetworkAdded(u16, Vec<u8>), // --- Event created when a new network is added.
         NetworkRemoved(u16),        // --- Event created when a network is removed.
+        // (bidder, total amount locked by the bid, block the auction closes at)
+        SubnetBidPlaced(T::AccountId, u64, u64),
+        // (bidder, amount)
+        SubnetBidRefunded(T::AccountId, u64),
+        // (netuid, founder, deposit)
+        SubnetAuctionWon(u16, T::AccountId, u64),
+        // (netuid, founder, deposit)
+        SubnetDepositReturned(u16, T::AccountId, u64),
+        // (netuid, founder, deposit) | The deposit is sent to the DAO treasury.
+        SubnetDepositForfeited(u16, T::AccountId, u64),
         StakeAdded(T::AccountId, T::AccountId, u64), /* --- Event created when stake has been
-                                     * transfered from the a coldkey account
-                                     * onto the key staking account. */
-        StakeRemoved(T::AccountId, T::AccountId, u64), /* --- Event created when stake has been
-                                                        * removed from the key staking account
-                                                        * onto the coldkey account. */
+                                                      * transfered from the a coldkey account
+                                                      * onto the key staking account. */
+        StakeRemoved(T::AccountId, T::AccountId, u64, u64), /* --- Event created when stake has
+                                                             * been removed from the key
+                                                             * staking account, the last field
+                                                             * is the block it unlocks at. */
+        UnbondedWithdrawn(T::AccountId, u64), /* --- Event created when unlocked stake has been
+                                               * withdrawn onto the coldkey account. */
+        // (delegator, module_key or None for the global setting, auto_compound or None if
+        // cleared)
+        AutoCompoundSet(T::AccountId, Option<T::AccountId>, Option<bool>),
         WeightsSet(u16, u16), /* ---- Event created when a caller successfully sets their
                                * weights on a subnetwork. */
+        WeightsCommitted(u16, u16), /* ---- Event created when a caller commits the hash of
+                                     * their weights on a subnetwork. */
+        // (key, netuids set, (netuid, error) of the failed entries)
+        WeightsSetMultiple(T::AccountId, Vec<u16>, Vec<(u16, DispatchError)>),
         ModuleRegistered(u16, u16, T::AccountId), /* --- Event created when a new module
                                                    * account has been registered to the chain. */
+        // (netuid, module_key, burn, where the burn went)
+        RegistrationBurned(u16, T::AccountId, u64, BurnDestination),
         ModuleDeregistered(u16, u16, T::AccountId), /* --- Event created when a module account
                                                      * has been deregistered from the chain. */
         ModuleUpdated(u16, T::AccountId), /* --- Event created when the module got updated
                                            * information is added to the network. */
+        // (netuid, module_key, controller)
+        ModuleControllerSet(u16, T::AccountId, T::AccountId),
+        // (old_key, new_key, netuids the module was rotated on)
+        ModuleKeyRotated(T::AccountId, T::AccountId, Vec<u16>),
+
+        // (payer, fee sent to the treasury)
+        CallFeePaid(T::AccountId, u64),
 
         // faucet
         Faucet(T::AccountId, BalanceOf<T>), // (id, balance_to_add)
@@ -643,6 +944,9 @@ pub mod pallet {
         GlobalProposalAccepted(u64), // (id)
         CustomProposalAccepted(u64), // (id)
         SubnetProposalAccepted(u64, u16), // (id, netuid)
+
+        // slashing
+        Slashed(u16, T::AccountId, u64, u64), // (netuid, module_key, amount, epoch)
     }
 
     // ---------------------------------
@@ -759,6 +1063,84 @@ pub mod pallet {
         InvalidMaxRegistrationsPerInterval,
         InvalidAdjustmentAlpha,
         InvalidTargetRegistrationsInterval,
+
+        // Unbonding
+        /// The unbonding period exceeds the allowed maximum.
+        InvalidUnbondingPeriod,
+        /// The account has too many pending unlocking chunks on this subnet.
+        TooManyUnlockingChunks,
+        /// There is no unlocked stake ready to be withdrawn.
+        NothingToWithdraw,
+
+        // Commit-reveal
+        /// The weight reveal period must be at least one epoch.
+        InvalidWeightRevealPeriod,
+        /// The subnet requires weights to be committed and revealed.
+        CommitRevealEnabled,
+        /// The subnet does not use commit-reveal for weights.
+        CommitRevealDisabled,
+        /// There is no weight commit to reveal.
+        NoWeightCommit,
+        /// The revealed weights do not match the commit.
+        InvalidWeightReveal,
+        /// The reveal window of the commit is over.
+        WeightRevealExpired,
+        /// The epoch the weights were committed in has not ended yet.
+        WeightRevealTooEarly,
+
+        // Consensus
+        /// The global subnet can only run the linear consensus.
+        InvalidSubnetConsensus,
+        /// Only governance can switch a subnet to the root consensus.
+        RootConsensusRequiresGovernance,
+        /// The low bonds alpha must not be above the high one.
+        InvalidAlphaValues,
+
+        // Subnet auction
+        /// The subnet slot auction period is above the allowed maximum.
+        InvalidSubnetAuctionPeriod,
+        /// The subnet slot auction is disabled.
+        SubnetAuctionDisabled,
+        /// All subnet slots are taken, a new subnet has to win the slot auction.
+        SubnetSlotAuctionRequired,
+        /// A bid can only be raised.
+        SubnetBidTooLow,
+        /// The bid is below the minimum of the subnet slot auction.
+        SubnetBidBelowMinimum,
+        /// The running auction already has the maximum number of bidders.
+        TooManySubnetBids,
+        /// The subnet slot auction limits are out of bounds.
+        InvalidSubnetAuctionConfig,
+        /// There is no subnet slot that can be given to the auction winner.
+        NoSubnetSlotAvailable,
+
+        // Batched weights
+        /// The weights batch has no entries.
+        EmptyWeightsBatch,
+        /// The weights batch has more entries than allowed.
+        WeightsBatchTooLarge,
+        /// None of the entries of the weights batch could be set.
+        NoWeightsSet,
+
+        // Key rotation
+        /// The new module key is already registered, has stake on a subnet or has a subnet bid.
+        ModuleKeyInUse,
+
+        // Controllers
+        /// The caller is not the controller of the module.
+        NotModuleController,
+
+        // Conviction voting
+        /// The stake is locked by a conviction vote until its conviction period ends.
+        StakeLocked,
+
+        // Rate limits
+        /// The account used up its calls of this kind for the current rate limit interval.
+        RateLimitExceeded,
+
+        // Fees
+        /// The account can't pay the extra fee of the call.
+        NotEnoughBalanceToPayFee,
     }
 
     // ---------------------------------
@@ -876,9 +1258,47 @@ pub mod pallet {
             uids: Vec<u16>,
             weights: Vec<u16>,
         ) -> DispatchResult {
+            Self::charge_call_fee(&origin, CallType::SetWeights)?;
             Self::do_set_weights(origin, netuid, uids, weights)
         }
 
+        #[pallet::call_index(13)]
+        #[pallet::weight((T::WeightInfo::commit_weights(), DispatchClass::Normal, Pays::No))]
+        pub fn commit_weights(
+            origin: OriginFor<T>,
+            netuid: u16,
+            commit_hash: H256,
+        ) -> DispatchResult {
+            Self::charge_call_fee(&origin, CallType::SetWeights)?;
+            Self::do_commit_weights(origin, netuid, commit_hash)
+        }
+
+        #[pallet::call_index(14)]
+        #[pallet::weight((T::WeightInfo::reveal_weights(), DispatchClass::Normal, Pays::No))]
+        pub fn reveal_weights(
+            origin: OriginFor<T>,
+            netuid: u16,
+            uids: Vec<u16>,
+            weights: Vec<u16>,
+            salt: Vec<u8>,
+        ) -> DispatchResult {
+            Self::charge_call_fee(&origin, CallType::SetWeights)?;
+            Self::do_reveal_weights(origin, netuid, uids, weights, salt)
+        }
+
+        #[pallet::call_index(15)]
+        #[pallet::weight((
+            T::WeightInfo::set_weights_multiple(weights.len() as u32),
+            DispatchClass::Normal,
+            Pays::No
+        ))]
+        pub fn set_weights_multiple(
+            origin: OriginFor<T>,
+            weights: Vec<(u16, Vec<u16>, Vec<u16>)>,
+        ) -> DispatchResult {
+            Self::do_set_weights_multiple(origin, weights)
+        }
+
         // ---------------------------------
         // Stake operations
         // ---------------------------------
@@ -891,6 +1311,7 @@ pub mod pallet {
             module_key: T::AccountId,
             amount: u64,
         ) -> DispatchResult {
+            Self::consume_rate_limit(&origin, RateLimitedCall::AddStake)?;
             Self::do_add_stake(origin, netuid, module_key, amount)
         }
 
@@ -902,6 +1323,7 @@ pub mod pallet {
             module_key: T::AccountId,
             amount: u64,
         ) -> DispatchResult {
+            Self::consume_rate_limit(&origin, RateLimitedCall::RemoveStake)?;
             Self::do_remove_stake(origin, netuid, module_key, amount)
         }
 
@@ -917,6 +1339,7 @@ pub mod pallet {
             module_keys: Vec<T::AccountId>,
             amounts: Vec<u64>,
         ) -> DispatchResult {
+            Self::consume_rate_limit(&origin, RateLimitedCall::AddStake)?;
             Self::do_add_stake_multiple(origin, netuid, module_keys, amounts)
         }
 
@@ -928,6 +1351,7 @@ pub mod pallet {
             module_keys: Vec<T::AccountId>,
             amounts: Vec<u64>,
         ) -> DispatchResult {
+            Self::consume_rate_limit(&origin, RateLimitedCall::RemoveStake)?;
             Self::do_remove_stake_multiple(origin, netuid, module_keys, amounts)
         }
 
@@ -944,6 +1368,7 @@ pub mod pallet {
             new_module_key: T::AccountId, // --- The new module key.
             amount: u64,                  // --- The amount of stake to transfer.
         ) -> DispatchResult {
+            Self::consume_rate_limit(&origin, RateLimitedCall::TransferStake)?;
             Self::do_transfer_stake(origin, netuid, module_key, new_module_key, amount)
         }
 
@@ -954,6 +1379,8 @@ pub mod pallet {
             destinations: Vec<T::AccountId>, // --- The module key.
             amounts: Vec<u64>,    // --- The amount of stake to transfer.
         ) -> DispatchResult {
+            Self::consume_rate_limit(&origin, RateLimitedCall::TransferStake)?;
+            Self::charge_call_fee(&origin, CallType::TransferMultiple)?;
             Self::do_transfer_multiple(origin, destinations, amounts)
         }
 
@@ -972,6 +1399,7 @@ pub mod pallet {
             module_key: T::AccountId,
             metadata: Option<Vec<u8>>,
         ) -> DispatchResult {
+            Self::charge_call_fee(&origin, CallType::Register)?;
             Self::do_register(origin, network, name, address, stake, module_key, metadata)
         }
 
@@ -995,6 +1423,7 @@ pub mod pallet {
             delegation_fee: Option<Percent>,
             metadata: Option<Vec<u8>>,
         ) -> DispatchResult {
+            Self::consume_rate_limit(&origin, RateLimitedCall::UpdateModule)?;
             let key = ensure_signed(origin.clone())?;
             ensure!(Self::is_registered(netuid, &key), Error::<T>::NotRegistered);
 
@@ -1005,6 +1434,108 @@ pub mod pallet {
             Self::do_update_module(origin, netuid, changeset)
         }
 
+        #[pallet::call_index(17)]
+        #[pallet::weight((
+            T::WeightInfo::rotate_module_key(TotalSubnets::<T>::get().into()).saturating_add(
+                // The governance entries of the key are moved by going through every voting
+                // power delegation.
+                T::DbWeight::get().reads_writes(
+                    MAX_VOTING_POWER_DELEGATIONS.into(),
+                    MAX_VOTING_POWER_DELEGATIONS.into()
+                )
+            ),
+            DispatchClass::Normal,
+            Pays::No
+        ))]
+        pub fn rotate_module_key(
+            origin: OriginFor<T>,
+            netuid: u16,
+            module_key: T::AccountId,
+            new_key: T::AccountId,
+        ) -> DispatchResult {
+            Self::do_rotate_module_key(origin, netuid, module_key, new_key)
+        }
+
+        // ---------------------------------
+        // Controllers
+        // ---------------------------------
+
+        #[pallet::call_index(18)]
+        #[pallet::weight((T::WeightInfo::set_module_controller(), DispatchClass::Normal, Pays::No))]
+        pub fn set_module_controller(
+            origin: OriginFor<T>,
+            netuid: u16,
+            module_key: T::AccountId,
+            controller: T::AccountId,
+        ) -> DispatchResult {
+            Self::do_set_module_controller(origin, netuid, module_key, controller)
+        }
+
+        #[pallet::call_index(19)]
+        #[pallet::weight((
+            T::WeightInfo::controller_update_module(),
+            DispatchClass::Normal,
+            Pays::No
+        ))]
+        pub fn controller_update_module(
+            origin: OriginFor<T>,
+            netuid: u16,
+            module_key: T::AccountId,
+            name: Vec<u8>,
+            address: Vec<u8>,
+            delegation_fee: Option<Percent>,
+            metadata: Option<Vec<u8>>,
+        ) -> DispatchResult {
+            Self::consume_rate_limit(&origin, RateLimitedCall::UpdateModule)?;
+            let controller = ensure_signed(origin)?;
+            ensure!(
+                Self::is_registered(netuid, &module_key),
+                Error::<T>::NotRegistered
+            );
+            Self::ensure_module_controller(netuid, &module_key, &controller)?;
+
+            let params = Self::module_params(netuid, &module_key);
+            let uid = Self::get_uid_for_key(netuid, &module_key);
+
+            let changeset =
+                ModuleChangeset::update(&params, name, address, delegation_fee, metadata);
+            changeset.apply::<T>(netuid, module_key, uid)
+        }
+
+        #[pallet::call_index(20)]
+        #[pallet::weight((T::WeightInfo::deregister(), DispatchClass::Normal, Pays::No))]
+        pub fn controller_deregister(
+            origin: OriginFor<T>,
+            netuid: u16,
+            module_key: T::AccountId,
+        ) -> DispatchResult {
+            let controller = ensure_signed(origin)?;
+            Self::ensure_module_controller(netuid, &module_key, &controller)?;
+            Self::deregister_module(netuid, module_key)
+        }
+
+        #[pallet::call_index(21)]
+        #[pallet::weight((T::WeightInfo::remove_stake(), DispatchClass::Normal, Pays::No))]
+        pub fn controller_remove_stake(
+            origin: OriginFor<T>,
+            netuid: u16,
+            module_key: T::AccountId,
+            amount: u64,
+        ) -> DispatchResult {
+            Self::consume_rate_limit(&origin, RateLimitedCall::RemoveStake)?;
+            Self::do_controller_remove_stake(origin, netuid, module_key, amount)
+        }
+
+        #[pallet::call_index(22)]
+        #[pallet::weight((T::WeightInfo::bid_subnet_slot(), DispatchClass::Normal, Pays::No))]
+        pub fn bid_subnet_slot(
+            origin: OriginFor<T>,
+            name: BoundedVec<u8, ConstU32<256>>,
+            amount: u64,
+        ) -> DispatchResult {
+            Self::do_bid_subnet_slot(origin, name, amount)
+        }
+
         #[pallet::call_index(10)]
         #[pallet::weight((T::WeightInfo::update_subnet(), DispatchClass::Normal, Pays::No))]
         pub fn update_subnet(
@@ -1029,7 +1560,25 @@ pub mod pallet {
             target_registrations_per_interval: u16,
             max_registrations_per_interval: u16,
             adjustment_alpha: u64,
+            unbonding_period: u64,
+            slash_ratio: Percent,
+            slash_threshold: Percent,
+            commit_reveal_weights: bool,
+            weight_reveal_period: u16,
+            consensus: SubnetConsensus,
+            weight_history: bool,
+            reward_history: bool,
+            liquid_alpha: bool,
+            alpha_low: u16,
+            alpha_high: u16,
         ) -> DispatchResult {
+            // the root consensus relays the whole emission to the founder
+            ensure!(
+                consensus != SubnetConsensus::Root
+                    || Self::get_subnet_consensus(netuid) == SubnetConsensus::Root,
+                Error::<T>::RootConsensusRequiresGovernance
+            );
+
             let params = SubnetParams {
                 founder,
                 founder_share,
@@ -1045,10 +1594,21 @@ pub mod pallet {
                 trust_ratio,
                 maximum_set_weight_calls_per_epoch,
                 bonds_ma,
+                liquid_alpha,
+                alpha_low,
+                alpha_high,
                 target_registrations_interval,
                 target_registrations_per_interval,
                 max_registrations_per_interval,
                 adjustment_alpha,
+                unbonding_period,
+                slash_ratio,
+                slash_threshold,
+                commit_reveal_weights,
+                weight_reveal_period,
+                consensus,
+                weight_history,
+                reward_history,
                 governance_config: GovernanceConfiguration {
                     vote_mode,
                     ..T::get_subnet_governance_configuration(netuid)
@@ -1059,6 +1619,30 @@ pub mod pallet {
             Self::do_update_subnet(origin, netuid, changeset)
         }
 
+        // ---------------------------------
+        // Unbonding
+        // ---------------------------------
+
+        #[pallet::call_index(12)]
+        #[pallet::weight((
+            T::WeightInfo::withdraw_unbonded(TotalSubnets::<T>::get().into()),
+            DispatchClass::Normal,
+            Pays::No
+        ))]
+        pub fn withdraw_unbonded(origin: OriginFor<T>) -> DispatchResult {
+            Self::do_withdraw_unbonded(origin)
+        }
+
+        #[pallet::call_index(16)]
+        #[pallet::weight((T::WeightInfo::set_auto_compound(), DispatchClass::Normal, Pays::No))]
+        pub fn set_auto_compound(
+            origin: OriginFor<T>,
+            module_key: Option<T::AccountId>,
+            auto_compound: Option<bool>,
+        ) -> DispatchResult {
+            Self::do_set_auto_compound(origin, module_key, auto_compound)
+        }
+
         // ---------------------------------
         // Testnet
         // ---------------------------------
@@ -1166,6 +1750,37 @@ where
     > {
         input.try_into().ok()
     }
+
+    pub fn call_type(call: &T::RuntimeCall) -> CallType {
+        match call.is_sub_type() {
+            Some(Call::add_stake { .. }) => CallType::AddStake,
+            Some(Call::add_stake_multiple { .. }) => CallType::AddStakeMultiple,
+            Some(Call::remove_stake { .. }) => CallType::RemoveStake,
+            Some(Call::remove_stake_multiple { .. }) => CallType::RemoveStakeMultiple,
+            Some(Call::transfer_stake { .. }) => CallType::TransferStake,
+            Some(Call::transfer_multiple { .. }) => CallType::TransferMultiple,
+            Some(
+                Call::set_weights { .. }
+                | Call::set_weights_multiple { .. }
+                | Call::commit_weights { .. }
+                | Call::reveal_weights { .. },
+            ) => CallType::SetWeights,
+            Some(Call::register { .. }) => CallType::Register,
+            Some(Call::update_module { .. }) => CallType::Update,
+            _ => CallType::Other,
+        }
+    }
+
+    /// Returns the extra fee of the call, `set_weights_multiple` pays it for every entry.
+    pub fn call_fee(call: &T::RuntimeCall) -> u64 {
+        let fee = Pallet::<T>::call_fee(&Self::call_type(call));
+        match call.is_sub_type() {
+            Some(Call::set_weights_multiple { weights }) => {
+                fee.saturating_mul(weights.len() as u64)
+            }
+            _ => fee,
+        }
+    }
 }
 
 impl<T: Config + Send + Sync + TypeInfo> sp_std::fmt::Debug for SubspaceSignedExtension<T> {
@@ -1184,7 +1799,7 @@ where
     type AccountId = T::AccountId;
     type Call = T::RuntimeCall;
     type AdditionalSigned = ();
-    type Pre = (CallType, u64, Self::AccountId);
+    type Pre = (CallType, u64, Self::AccountId, Option<RateLimitedCall>);
 
     fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
         Ok(())
@@ -1197,8 +1812,18 @@ where
         _info: &DispatchInfoOf<Self::Call>,
         _len: usize,
     ) -> TransactionValidity {
+        if let Some(call) = call.is_sub_type() {
+            Pallet::<T>::ensure_within_rate_limit(who, call)?;
+        }
+
+        Pallet::<T>::ensure_can_pay_call_fee(who, Self::call_fee(call))?;
+
         match call.is_sub_type() {
-            Some(Call::set_weights { netuid, .. }) => {
+            Some(
+                Call::set_weights { netuid, .. }
+                | Call::commit_weights { netuid, .. }
+                | Call::reveal_weights { netuid, .. },
+            ) => {
                 let priority: u64 = Self::get_priority_set_weights(who, *netuid);
                 Ok(ValidTransaction {
                     priority,
@@ -1206,6 +1831,19 @@ where
                     ..Default::default()
                 })
             }
+            Some(Call::set_weights_multiple { weights }) => {
+                // A batch doesn't get ahead of the lowest priority call it replaces.
+                let priority: u64 = weights
+                    .iter()
+                    .map(|(netuid, _, _)| Self::get_priority_set_weights(who, *netuid))
+                    .min()
+                    .unwrap_or_default();
+                Ok(ValidTransaction {
+                    priority,
+                    longevity: 1,
+                    ..Default::default()
+                })
+            }
             _ => Ok(ValidTransaction {
                 priority: Self::get_priority_vanilla(who),
                 ..Default::default()
@@ -1213,7 +1851,6 @@ where
         }
     }
 
-    // NOTE: Add later when we put in a pre and post dispatch step.
     fn pre_dispatch(
         self,
         who: &Self::AccountId,
@@ -1221,19 +1858,17 @@ where
         _info: &DispatchInfoOf<Self::Call>,
         _len: usize,
     ) -> Result<Self::Pre, TransactionValidityError> {
-        let who = who.clone();
-        match call.is_sub_type() {
-            Some(Call::add_stake { .. }) => Ok((CallType::AddStake, 0, who)),
-            Some(Call::add_stake_multiple { .. }) => Ok((CallType::AddStakeMultiple, 0, who)),
-            Some(Call::remove_stake { .. }) => Ok((CallType::RemoveStake, 0, who)),
-            Some(Call::remove_stake_multiple { .. }) => Ok((CallType::RemoveStakeMultiple, 0, who)),
-            Some(Call::transfer_stake { .. }) => Ok((CallType::TransferStake, 0, who)),
-            Some(Call::transfer_multiple { .. }) => Ok((CallType::TransferMultiple, 0, who)),
-            Some(Call::set_weights { .. }) => Ok((CallType::SetWeights, 0, who)),
-            Some(Call::register { .. }) => Ok((CallType::Register, 0, who)),
-            Some(Call::update_module { .. }) => Ok((CallType::Update, 0, who)),
-            _ => Ok((CallType::Other, 0, who)),
-        }
+        // The call is counted against the limit when it is dispatched.
+        let rate_limited = match call.is_sub_type() {
+            Some(call) => Pallet::<T>::ensure_within_rate_limit(who, call)?,
+            None => None,
+        };
+
+        // The fee is taken when the call is dispatched.
+        let fee = Self::call_fee(call);
+        Pallet::<T>::ensure_can_pay_call_fee(who, fee)?;
+
+        Ok((Self::call_type(call), fee, who.clone(), rate_limited))
     }
 
     fn post_dispatch(
@@ -1241,44 +1876,14 @@ where
         _info: &DispatchInfoOf<Self::Call>,
         _post_info: &PostDispatchInfoOf<Self::Call>,
         _len: usize,
-        _result: &dispatch::DispatchResult,
+        result: &dispatch::DispatchResult,
     ) -> Result<(), TransactionValidityError> {
-        if let Some((call_type, _transaction_fee, _who)) = maybe_pre {
-            match call_type {
-                CallType::SetWeights => {
-                    log::debug!("Not Implemented!");
-                }
-                CallType::AddStake => {
-                    log::debug!("Not Implemented! Need to add potential transaction fees here.");
-                }
-
-                CallType::AddStakeMultiple => {
-                    log::debug!("Not Implemented! Need to add potential transaction fees here.");
-                }
-                CallType::RemoveStake => {
-                    log::debug!("Not Implemented! Need to add potential transaction fees here.");
-                }
-                CallType::RemoveStakeMultiple => {
-                    log::debug!("Not Implemented! Need to add potential transaction fees here.");
-                }
-                CallType::TransferStake => {
-                    log::debug!("Not Implemented! Need to add potential transaction fees here.");
-                }
-                CallType::TransferStakeMultiple => {
-                    log::debug!("Not Implemented! Need to add potential transaction fees here.");
-                }
-                CallType::TransferMultiple => {
-                    log::debug!("Not Implemented! Need to add potential transaction fees here.");
-                }
-                CallType::AddNetwork => {
-                    log::debug!("Not Implemented! Need to add potential transaction fees here.");
-                }
-                CallType::Register => {
-                    log::debug!("Not Implemented!");
-                }
-                _ => {
-                    log::debug!("Not Implemented!");
-                }
+        if let (Some((_, transaction_fee, who, rate_limited)), Err(_)) = (maybe_pre, result) {
+            // Failed calls roll their fee and count back, but still pay the fee and take up
+            // the account's calls. The balance was checked before dispatching.
+            let _ = Pallet::<T>::pay_call_fee(&who, transaction_fee);
+            if let Some(kind) = rate_limited {
+                Pallet::<T>::count_rate_limited_call(&who, kind);
             }
         }
         Ok(())
@@ -3,19 +3,44 @@
 use sp_std::{vec, vec::Vec};
 use substrate_fixed::types::{I32F32, I64F64};
 
+// Sums the values, saturating instead of overflowing.
+fn saturating_sum<'a>(values: impl IntoIterator<Item = &'a I32F32>) -> I32F32 {
+    values.into_iter().fold(I32F32::from_num(0), |acc, x| acc.saturating_add(*x))
+}
+
+// Divides `x` by a non-zero `y`, saturating instead of overflowing.
+fn saturating_div(x: I32F32, y: I32F32) -> I32F32 {
+    let zero = I32F32::from_num(0);
+    x.checked_div(y).unwrap_or(if (x < zero) == (y < zero) {
+        I32F32::max_value()
+    } else {
+        I32F32::min_value()
+    })
+}
+
+// Divides `x` by a non-zero `y`, saturating instead of overflowing.
+fn saturating_div_64(x: I64F64, y: I64F64) -> I64F64 {
+    let zero = I64F64::from_num(0);
+    x.checked_div(y).unwrap_or(if (x < zero) == (y < zero) {
+        I64F64::max_value()
+    } else {
+        I64F64::min_value()
+    })
+}
+
 // Return true when vector sum is zero.
 pub fn is_zero(vector: &[I32F32]) -> bool {
-    vector.iter().sum::<I32F32>() == I32F32::from_num(0)
+    saturating_sum(vector) == I32F32::from_num(0)
 }
 
 // Normalizes (sum to 1 except 0) the input vector directly in-place.
 pub fn inplace_normalize(x: &mut [I32F32]) {
-    let x_sum: I32F32 = x.iter().sum();
+    let x_sum: I32F32 = saturating_sum(x.iter());
     if x_sum == I32F32::from_num(0.0) {
         return;
     }
     for i in x.iter_mut() {
-        *i /= x_sum;
+        *i = saturating_div(*i, x_sum);
     }
 }
 
@@ -24,7 +49,7 @@ pub fn u16_proportion_to_fixed(x: u16) -> I32F32 {
 }
 
 pub fn fixed_proportion_to_u16(x: I32F32) -> u16 {
-    (x * I32F32::from_num(u16::MAX)).to_num()
+    x.saturating_mul(I32F32::from_num(u16::MAX)).saturating_to_num()
 }
 
 // Return a new sparse matrix with a masked out diagonal of input sparse matrix.
@@ -44,25 +69,30 @@ pub fn mask_diag_sparse(sparse_matrix: &[Vec<(u16, I32F32)>]) -> Vec<Vec<(u16, I
 /// Normalizes (sum to 1 except 0) each row (dim=0) of a sparse matrix in-place.
 pub fn inplace_row_normalize_sparse(sparse_matrix: &mut [Vec<(u16, I32F32)>]) {
     for sparse_row in sparse_matrix.iter_mut() {
-        let row_sum: I32F32 = sparse_row.iter().map(|(_j, value)| *value).sum();
+        let row_sum: I32F32 = saturating_sum(sparse_row.iter().map(|(_j, value)| value));
         if row_sum != I32F32::from_num(0) {
-            sparse_row.iter_mut().for_each(|(_j, value)| *value /= row_sum);
+            sparse_row
+                .iter_mut()
+                .for_each(|(_j, value)| *value = saturating_div(*value, row_sum));
         }
     }
 }
 
+// Entries outside of the vector or the result count as zero.
 pub fn matmul_sparse(
     sparse_matrix: &[Vec<(u16, I32F32)>],
     vector: &[I32F32],
     columns: u16,
 ) -> Vec<I32F32> {
     let mut result: Vec<I32F32> = vec![I32F32::from_num(0.0); columns as usize];
-    for (i, sparse_row) in sparse_matrix.iter().enumerate() {
+    for (sparse_row, vector_i) in sparse_matrix.iter().zip(vector) {
         for (j, value) in sparse_row.iter() {
             // Compute ranks: r_j = SUM(i) w_ij * s_i
             // Compute trust scores: t_j = SUM(i) w_ij * s_i
             // result_j = SUM(i) vector_i * matrix_ij
-            result[*j as usize] += vector[i] * value;
+            if let Some(result_j) = result.get_mut(*j as usize) {
+                *result_j = result_j.saturating_add(vector_i.saturating_mul(*value));
+            }
         }
     }
     result
@@ -74,36 +104,30 @@ pub fn weighted_median_col_sparse(
     columns: u16,
     majority: I32F32,
 ) -> Vec<I32F32> {
-    let rows = stake.len();
     let zero: I32F32 = I32F32::from_num(0);
     let mut use_stake: Vec<I32F32> = stake.iter().copied().filter(|&s| s > zero).collect();
     inplace_normalize(&mut use_stake);
-    let stake_sum: I32F32 = use_stake.iter().sum();
+    let stake_sum: I32F32 = saturating_sum(&use_stake);
     let stake_idx: Vec<usize> = (0..use_stake.len()).collect();
-    let minority: I32F32 = stake_sum - majority;
+    let minority: I32F32 = stake_sum.saturating_sub(majority);
     let mut use_score: Vec<Vec<I32F32>> = vec![vec![zero; use_stake.len()]; columns as usize];
-    let mut median: Vec<I32F32> = vec![zero; columns as usize];
     let mut k: usize = 0;
-    for r in 0..rows {
-        if stake[r] <= zero {
+    for (r, stake_r) in stake.iter().enumerate() {
+        if *stake_r <= zero {
             continue;
         }
-        for (c, val) in score[r].iter() {
-            use_score[*c as usize][k] = *val;
+        // Rows without scores and columns out of range count as zero scores.
+        for (c, val) in score.get(r).into_iter().flatten() {
+            if let Some(column) = use_score.get_mut(*c as usize) {
+                column[k] = *val;
+            }
         }
         k += 1;
     }
-    for c in 0..columns as usize {
-        median[c] = weighted_median(
-            &use_stake,
-            &use_score[c],
-            &stake_idx,
-            minority,
-            zero,
-            stake_sum,
-        );
-    }
-    median
+    use_score
+        .iter()
+        .map(|column| weighted_median(&use_stake, column, &stake_idx, minority, zero, stake_sum))
+        .collect()
 }
 
 // Stake-weighted median score finding algorithm, based on a mid pivot binary search.
@@ -133,7 +157,7 @@ pub fn weighted_median_col_sparse(
 //
 // # Returns:
 //     * 'median': ( I32F32 ):
-//         - median via random pivot binary search.
+//         - median via random pivot binary search, 0 when stake and score lengths differ.
 //
 pub fn weighted_median(
     stake: &Vec<I32F32>,
@@ -144,13 +168,12 @@ pub fn weighted_median(
     partition_hi: I32F32,
 ) -> I32F32 {
     let n = partition_idx.len();
-    if n == 0 {
+    if n == 0 || stake.len() != score.len() {
         return I32F32::from_num(0);
     }
     if n == 1 {
         return score[partition_idx[0]];
     }
-    assert!(stake.len() == score.len());
     let mid_idx: usize = n / 2;
     let pivot: I32F32 = score[partition_idx[mid_idx]];
     let mut lo_stake: I32F32 = I32F32::from_num(0);
@@ -162,50 +185,35 @@ pub fn weighted_median(
             continue;
         }
         if score[idx] < pivot {
-            lo_stake += stake[idx];
+            lo_stake = lo_stake.saturating_add(stake[idx]);
             lower.push(idx);
         } else {
-            hi_stake += stake[idx];
+            hi_stake = hi_stake.saturating_add(stake[idx]);
             upper.push(idx);
         }
     }
-    if (partition_lo + lo_stake <= minority) && (minority < partition_hi - hi_stake) {
+    let lo_edge = partition_lo.saturating_add(lo_stake);
+    let hi_edge = partition_hi.saturating_sub(hi_stake);
+    if (lo_edge <= minority) && (minority < hi_edge) {
         return pivot;
-    } else if (minority < partition_lo + lo_stake) && !lower.is_empty() {
-        return weighted_median(
-            stake,
-            score,
-            &lower,
-            minority,
-            partition_lo,
-            partition_lo + lo_stake,
-        );
-    } else if (partition_hi - hi_stake <= minority) && !upper.is_empty() {
-        return weighted_median(
-            stake,
-            score,
-            &upper,
-            minority,
-            partition_hi - hi_stake,
-            partition_hi,
-        );
+    } else if (minority < lo_edge) && !lower.is_empty() {
+        return weighted_median(stake, score, &lower, minority, partition_lo, lo_edge);
+    } else if (hi_edge <= minority) && !upper.is_empty() {
+        return weighted_median(stake, score, &upper, minority, hi_edge, partition_hi);
     }
     pivot
 }
 
 // Sum across each row (dim=0) of a sparse matrix.
 pub fn row_sum_sparse(sparse_matrix: &[Vec<(u16, I32F32)>]) -> Vec<I32F32> {
-    let rows = sparse_matrix.len();
-    let mut result: Vec<I32F32> = vec![I32F32::from_num(0); rows];
-    for (i, sparse_row) in sparse_matrix.iter().enumerate() {
-        for (_j, value) in sparse_row.iter() {
-            result[i] += value;
-        }
-    }
-    result
+    sparse_matrix
+        .iter()
+        .map(|sparse_row| saturating_sum(sparse_row.iter().map(|(_j, value)| value)))
+        .collect()
 }
 
-// Return sparse matrix with values above column threshold set to threshold value.
+// Return sparse matrix with values above column threshold set to threshold value. Columns
+// without a threshold are kept as is.
 pub fn col_clip_sparse(
     sparse_matrix: &[Vec<(u16, I32F32)>],
     col_threshold: &[I32F32],
@@ -213,18 +221,20 @@ pub fn col_clip_sparse(
     let mut result: Vec<Vec<(u16, I32F32)>> = vec![vec![]; sparse_matrix.len()];
     for (i, sparse_row) in sparse_matrix.iter().enumerate() {
         for (j, value) in sparse_row.iter() {
-            if col_threshold[*j as usize] < *value {
-                if 0 < col_threshold[*j as usize] {
-                    result[i].push((*j, col_threshold[*j as usize]));
+            match col_threshold.get(*j as usize) {
+                Some(threshold) if *threshold < *value => {
+                    if 0 < *threshold {
+                        result[i].push((*j, *threshold));
+                    }
                 }
-            } else {
-                result[i].push((*j, *value));
+                _ => result[i].push((*j, *value)),
             }
         }
     }
     result
 }
 
+// Rows without a mask entry are kept.
 pub fn mask_rows_sparse(
     mask: &[bool],
     sparse_matrix: &[Vec<(u16, I32F32)>],
@@ -234,11 +244,9 @@ pub fn mask_rows_sparse(
     }
 
     let n: usize = sparse_matrix.len();
-    assert_eq!(n, mask.len());
-
     let mut result: Vec<Vec<(u16, I32F32)>> = vec![vec![]; n];
     for (i, sparse_row) in sparse_matrix.iter().enumerate() {
-        if !mask[i] {
+        if !mask.get(i).copied().unwrap_or(false) {
             result[i].clone_from(sparse_row);
         }
     }
@@ -266,34 +274,30 @@ pub fn vec_mask_sparse_matrix(
     Some(result)
 }
 
+// Entries without a mask entry are kept.
 pub fn inplace_mask_vector(mask: &[bool], vector: &mut [I32F32]) {
-    if mask.is_empty() {
-        return;
-    }
-
-    assert_eq!(mask.len(), vector.len());
     let zero: I32F32 = I32F32::from_num(0.0);
 
     vector
         .iter_mut()
-        .enumerate()
-        .filter(|(idx, _)| mask[*idx])
-        .for_each(|(_, v)| *v = zero);
+        .zip(mask)
+        .filter(|(_, masked)| **masked)
+        .for_each(|(v, _)| *v = zero);
 }
 
 pub fn inplace_normalize_64(x: &mut [I64F64]) {
-    let x_sum: I64F64 = x.iter().sum();
+    let x_sum: I64F64 = x.iter().fold(I64F64::from_num(0), |acc, x| acc.saturating_add(*x));
     if x_sum == I64F64::from_num(0) {
         return;
     }
 
     for x in x {
-        *x /= x_sum;
+        *x = saturating_div_64(*x, x_sum);
     }
 }
 
 pub fn vec_fixed64_to_fixed32(vec: Vec<I64F64>) -> Vec<I32F32> {
-    vec.into_iter().map(I32F32::from_num).collect()
+    vec.into_iter().map(I32F32::saturating_from_num).collect()
 }
 
 pub fn is_topk(vector: &[I32F32], k: usize) -> Vec<bool> {
@@ -311,33 +315,37 @@ pub fn is_topk(vector: &[I32F32], k: usize) -> Vec<bool> {
 }
 
 pub fn inplace_col_normalize_sparse(sparse_matrix: &mut [Vec<(u16, I32F32)>], columns: u16) {
-    let mut col_sum: Vec<I32F32> = vec![I32F32::from_num(0.0); columns as usize]; // assume square matrix, rows=cols
+    let zero: I32F32 = I32F32::from_num(0.0);
+    let mut col_sum: Vec<I32F32> = vec![zero; columns as usize]; // assume square matrix, rows=cols
 
     for sparse_row in sparse_matrix.iter() {
         for (j, value) in sparse_row {
-            col_sum[*j as usize] += *value;
+            if let Some(sum) = col_sum.get_mut(*j as usize) {
+                *sum = sum.saturating_add(*value);
+            }
         }
     }
 
     for sparse_row in sparse_matrix {
         for (j, value) in sparse_row {
-            if col_sum[*j as usize] == I32F32::from_num(0.) {
-                continue;
+            match col_sum.get(*j as usize) {
+                Some(sum) if *sum != zero => *value = saturating_div(*value, *sum),
+                _ => continue,
             }
-
-            *value /= col_sum[*j as usize];
         }
     }
 }
 
+// Rows without a vector entry are multiplied by zero.
 pub fn row_hadamard_sparse(
     sparse_matrix: &[Vec<(u16, I32F32)>],
     vector: &[I32F32],
 ) -> Vec<Vec<(u16, I32F32)>> {
     let mut result: Vec<Vec<(u16, I32F32)>> = sparse_matrix.to_vec();
     for (i, sparse_row) in result.iter_mut().enumerate() {
+        let factor = vector.get(i).copied().unwrap_or(I32F32::from_num(0));
         for (_j, value) in sparse_row {
-            *value *= vector[i];
+            *value = value.saturating_mul(factor);
         }
     }
     result
@@ -349,10 +357,11 @@ pub fn inplace_normalize_using_sum(x: &mut [I32F32], x_sum: I32F32) {
     }
 
     for x in x {
-        *x /= x_sum;
+        *x = saturating_div(*x, x_sum);
     }
 }
 
+// Entries outside of the vector count as zero.
 pub fn matmul_transpose_sparse(
     sparse_matrix: &[Vec<(u16, I32F32)>],
     vector: &[I32F32],
@@ -363,29 +372,83 @@ pub fn matmul_transpose_sparse(
             // Compute dividends: d_j = SUM(i) b_ji * inc_i
             // result_j = SUM(i) vector_i * matrix_ji
             // result_i = SUM(j) vector_j * matrix_ij
-            result[i] += vector[*j as usize] * value;
+            if let Some(vector_j) = vector.get(*j as usize) {
+                result[i] = result[i].saturating_add(vector_j.saturating_mul(*value));
+            }
         }
     }
     result
 }
 
+// Rows missing from `old` count as empty.
 pub fn mat_ema_sparse(
     new: &[Vec<(u16, I32F32)>],
     old: &[Vec<(u16, I32F32)>],
     alpha: I32F32,
 ) -> Vec<Vec<(u16, I32F32)>> {
-    assert!(new.len() == old.len());
-    let n = new.len(); // assume square matrix, rows=cols
     let zero: I32F32 = I32F32::from_num(0.0);
-    let one_minus_alpha: I32F32 = I32F32::from_num(1.0) - alpha;
-    let mut result: Vec<Vec<(u16, I32F32)>> = vec![vec![]; n];
-    for i in 0..new.len() {
-        let mut row: Vec<I32F32> = vec![zero; n];
-        for (j, value) in new[i].iter() {
-            row[*j as usize] += alpha * value;
+    let one_minus_alpha: I32F32 = I32F32::from_num(1.0).saturating_sub(alpha);
+    let mut result: Vec<Vec<(u16, I32F32)>> = vec![vec![]; new.len()];
+    for (i, new_row) in new.iter().enumerate() {
+        let old_row: &[(u16, I32F32)] = old.get(i).map(Vec::as_slice).unwrap_or_default();
+        let width = new_row
+            .iter()
+            .chain(old_row)
+            .map(|(j, _)| *j as usize + 1)
+            .max()
+            .unwrap_or_default();
+        let mut row: Vec<I32F32> = vec![zero; width];
+        for (j, value) in new_row.iter() {
+            row[*j as usize] = row[*j as usize].saturating_add(alpha.saturating_mul(*value));
+        }
+        for (j, value) in old_row.iter() {
+            row[*j as usize] =
+                row[*j as usize].saturating_add(one_minus_alpha.saturating_mul(*value));
+        }
+        for (j, value) in row.iter().enumerate() {
+            if *value > zero {
+                result[i].push((j as u16, *value))
+            }
+        }
+    }
+    result
+}
+
+// Moving average of sparse matrices with an alpha per entry of `new`, aligned with its rows.
+// Entries without an alpha use `default_alpha`.
+pub fn mat_ema_alpha_sparse(
+    new: &[Vec<(u16, I32F32)>],
+    old: &[Vec<(u16, I32F32)>],
+    alpha: &[Vec<(u16, I32F32)>],
+    default_alpha: I32F32,
+) -> Vec<Vec<(u16, I32F32)>> {
+    let zero: I32F32 = I32F32::from_num(0.0);
+    let one: I32F32 = I32F32::from_num(1.0);
+    let mut result: Vec<Vec<(u16, I32F32)>> = vec![vec![]; new.len()];
+    for (i, new_row) in new.iter().enumerate() {
+        let old_row: &[(u16, I32F32)] = old.get(i).map(Vec::as_slice).unwrap_or_default();
+        let alpha_row: &[(u16, I32F32)] = alpha.get(i).map(Vec::as_slice).unwrap_or_default();
+        let width = new_row
+            .iter()
+            .chain(old_row)
+            .map(|(j, _)| *j as usize + 1)
+            .max()
+            .unwrap_or_default();
+        let mut row_alpha: Vec<I32F32> = vec![default_alpha; width];
+        for (j, value) in alpha_row.iter() {
+            if let Some(alpha_j) = row_alpha.get_mut(*j as usize) {
+                *alpha_j = *value;
+            }
+        }
+        let mut row: Vec<I32F32> = vec![zero; width];
+        for (j, value) in new_row.iter() {
+            let alpha_j = row_alpha[*j as usize];
+            row[*j as usize] = row[*j as usize].saturating_add(alpha_j.saturating_mul(*value));
         }
-        for (j, value) in old[i].iter() {
-            row[*j as usize] += one_minus_alpha * value;
+        for (j, value) in old_row.iter() {
+            let one_minus_alpha = one.saturating_sub(row_alpha[*j as usize]);
+            row[*j as usize] =
+                row[*j as usize].saturating_add(one_minus_alpha.saturating_mul(*value));
         }
         for (j, value) in row.iter().enumerate() {
             if *value > zero {
@@ -397,62 +460,69 @@ pub fn mat_ema_sparse(
 }
 
 /// Max-upscale vector and convert to u16 so max_value = u16::MAX. Assumes non-negative normalized
-/// input.
+/// input, negative values become 0.
 pub fn vec_max_upscale_to_u16(vec: &[I32F32]) -> Vec<u16> {
     let u16_max: I32F32 = I32F32::from_num(u16::MAX);
     let threshold: I32F32 = I32F32::from_num(32768);
     let max_value: Option<&I32F32> = vec.iter().max();
     match max_value {
         Some(val) => {
-            if *val == I32F32::from_num(0) {
-                return vec.iter().map(|e: &I32F32| (e * u16_max).to_num::<u16>()).collect();
+            if *val <= I32F32::from_num(0) {
+                return vec
+                    .iter()
+                    .map(|e: &I32F32| e.saturating_mul(u16_max).saturating_to_num::<u16>())
+                    .collect();
             }
             if *val > threshold {
+                let scale = saturating_div(u16_max, *val);
                 return vec
                     .iter()
-                    .map(|e: &I32F32| (e * (u16_max / *val)).round().to_num::<u16>())
+                    .map(|e: &I32F32| e.saturating_mul(scale).round().saturating_to_num::<u16>())
                     .collect();
             }
-            return vec
-                .iter()
-                .map(|e: &I32F32| ((e * u16_max) / *val).round().to_num::<u16>())
-                .collect();
-        }
-        None => {
-            let sum: I32F32 = vec.iter().sum();
-            return vec.iter().map(|e: &I32F32| ((e * u16_max) / sum).to_num::<u16>()).collect();
+            vec.iter()
+                .map(|e: &I32F32| {
+                    saturating_div(e.saturating_mul(u16_max), *val)
+                        .round()
+                        .saturating_to_num::<u16>()
+                })
+                .collect()
         }
+        None => Vec::new(),
     }
 }
 
+// Entries without a divisor, or with a zero one, are 0.
 pub fn vecdiv(x: &[I32F32], y: &[I32F32]) -> Vec<I32F32> {
-    assert_eq!(x.len(), y.len());
-    let n = x.len();
-    let mut result: Vec<I32F32> = vec![I32F32::from_num(0); n];
-    for i in 0..n {
-        if y[i] != 0 {
-            result[i] = x[i] / y[i];
-        }
-    }
-    result
+    let zero: I32F32 = I32F32::from_num(0);
+    x.iter()
+        .enumerate()
+        .map(|(i, x_i)| match y.get(i) {
+            Some(y_i) if *y_i != zero => saturating_div(*x_i, *y_i),
+            _ => zero,
+        })
+        .collect()
 }
 
 // Max-upscale each column (dim=1) of a sparse matrix in-place.
 pub fn inplace_col_max_upscale_sparse(sparse_matrix: &mut [Vec<(u16, I32F32)>], columns: u16) {
-    let mut col_max: Vec<I32F32> = vec![I32F32::from_num(0.0); columns as usize]; // assume square matrix, rows=cols
+    let zero: I32F32 = I32F32::from_num(0.0);
+    let mut col_max: Vec<I32F32> = vec![zero; columns as usize]; // assume square matrix, rows=cols
     for sparse_row in sparse_matrix.iter() {
         for (j, value) in sparse_row.iter() {
-            if col_max[*j as usize] < *value {
-                col_max[*j as usize] = *value;
+            if let Some(max) = col_max.get_mut(*j as usize) {
+                if *max < *value {
+                    *max = *value;
+                }
             }
         }
     }
     for sparse_row in sparse_matrix.iter_mut() {
         for (j, value) in sparse_row.iter_mut() {
-            if col_max[*j as usize] == I32F32::from_num(0.) {
-                continue;
+            match col_max.get(*j as usize) {
+                Some(max) if *max != zero => *value = saturating_div(*value, *max),
+                _ => continue,
             }
-            *value /= col_max[*j as usize];
         }
     }
 }
@@ -460,6 +530,7 @@ pub fn inplace_col_max_upscale_sparse(sparse_matrix: &mut [Vec<(u16, I32F32)>],
 #[cfg(test)]
 mod tests {
     use crate::math::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
     use substrate_fixed::types::{I32F32, I64F64, I96F32};
 
     macro_rules! fixed_vec {
@@ -754,4 +825,459 @@ mod tests {
             I32F32::from_num(0),
         );
     }
+
+    // Random inputs generated per property, with fixed seeds so failures reproduce.
+    const CASES: u64 = 200;
+    // Raw bits of `1` in `I32F32`. References are exact rationals over the raw bits, so the only
+    // rounding is the one done by the function under test.
+    const ONE_BITS: i128 = 1 << 32;
+
+    fn bits(x: I32F32) -> i128 {
+        x.to_bits() as i128
+    }
+
+    /// Random vector with values in `[0, max)`.
+    fn random_vec(rng: &mut StdRng, len: usize, max: I32F32) -> Vec<I32F32> {
+        (0..len).map(|_| I32F32::from_bits(rng.gen_range(0..max.to_bits()))).collect()
+    }
+
+    /// Random sparse matrix with values in `[0, max)` and ascending columns in each row.
+    fn random_sparse_mat(
+        rng: &mut StdRng,
+        rows: usize,
+        columns: u16,
+        max: I32F32,
+    ) -> Vec<Vec<(u16, I32F32)>> {
+        let density: f64 = rng.gen_range(0.1..=1.0);
+        let mut mat = Vec::with_capacity(rows);
+        for _ in 0..rows {
+            let mut row = vec![];
+            for j in 0..columns {
+                if rng.gen_bool(density) {
+                    row.push((j, I32F32::from_bits(rng.gen_range(0..max.to_bits()))));
+                }
+            }
+            mat.push(row);
+        }
+        mat
+    }
+
+    /// Dense matrix of the raw bits of a sparse one.
+    fn to_dense(mat: &[Vec<(u16, I32F32)>], columns: u16) -> Vec<Vec<i128>> {
+        mat.iter()
+            .map(|row| {
+                let mut dense = vec![0; columns as usize];
+                for (j, value) in row {
+                    dense[*j as usize] += bits(*value);
+                }
+                dense
+            })
+            .collect()
+    }
+
+    /// Asserts that the raw bits `result`, with `frac_bits` fractional bits, are within `ulps`
+    /// units in the last place of the exact rational `numerator / denominator`.
+    fn assert_within_ulps(
+        result: i128,
+        frac_bits: u32,
+        numerator: i128,
+        denominator: i128,
+        ulps: i128,
+    ) {
+        assert!(denominator > 0);
+        let error = (result * denominator - (numerator << frac_bits)).abs();
+        assert!(
+            error <= ulps * denominator,
+            "{result} differs from {numerator} / {denominator} by more than {ulps} ulps"
+        );
+    }
+
+    /// Asserts `value / divisor` was computed to within one ulp, or kept when `divisor` is zero.
+    fn assert_dense_quotient(result: &[Vec<i128>], dense: &[Vec<i128>], divisors: &[i128]) {
+        assert_eq!(result.len(), dense.len());
+        for (result_row, row) in result.iter().zip(dense) {
+            for ((result, value), divisor) in result_row.iter().zip(row).zip(divisors) {
+                if *divisor == 0 {
+                    assert_eq!(result, value);
+                } else {
+                    assert_within_ulps(*result, 32, *value, *divisor, 1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_math_matmul_sparse_matches_reference() {
+        let one = I32F32::from_num(1);
+        for seed in 0..CASES {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let rows = rng.gen_range(1..=32);
+            let columns = rng.gen_range(1..=32);
+            let mat = random_sparse_mat(&mut rng, rows, columns, one);
+            let dense = to_dense(&mat, columns);
+
+            let vector = random_vec(&mut rng, rows, one);
+            let result = matmul_sparse(&mat, &vector, columns);
+            for (j, result_j) in result.iter().enumerate() {
+                let reference: i128 =
+                    dense.iter().zip(&vector).map(|(row, v)| row[j] * bits(*v)).sum();
+                // One rounding per product, additions are exact.
+                assert_within_ulps(
+                    bits(*result_j),
+                    32,
+                    reference,
+                    ONE_BITS * ONE_BITS,
+                    rows as i128,
+                );
+            }
+
+            let vector = random_vec(&mut rng, columns as usize, one);
+            let result = matmul_transpose_sparse(&mat, &vector);
+            for (row, result_i) in dense.iter().zip(&result) {
+                let reference: i128 = row.iter().zip(&vector).map(|(m, v)| m * bits(*v)).sum();
+                assert_within_ulps(
+                    bits(*result_i),
+                    32,
+                    reference,
+                    ONE_BITS * ONE_BITS,
+                    columns as i128,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_math_sparse_normalization_matches_reference() {
+        for seed in 0..CASES {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let rows = rng.gen_range(1..=32);
+            let columns = rng.gen_range(1..=32);
+            let max = I32F32::from_num(if rng.gen_bool(0.5) { 1 } else { 1_000 });
+            let mat = random_sparse_mat(&mut rng, rows, columns, max);
+            let dense = to_dense(&mat, columns);
+
+            // Sums stay far from the bounds, so they are exact.
+            for (sum, row) in row_sum_sparse(&mat).iter().zip(&dense) {
+                assert_eq!(bits(*sum), row.iter().sum::<i128>());
+            }
+
+            let mut row_normalized = mat.clone();
+            inplace_row_normalize_sparse(&mut row_normalized);
+            for (result_row, row) in to_dense(&row_normalized, columns).iter().zip(&dense) {
+                let sum: i128 = row.iter().sum();
+                assert_dense_quotient(
+                    &[result_row.clone()],
+                    &[row.clone()],
+                    &vec![sum; columns as usize],
+                );
+            }
+
+            let col_sums: Vec<i128> =
+                (0..columns as usize).map(|j| dense.iter().map(|row| row[j]).sum()).collect();
+            let col_maxes: Vec<i128> = (0..columns as usize)
+                .map(|j| dense.iter().map(|row| row[j]).max().unwrap_or_default())
+                .collect();
+
+            let mut col_normalized = mat.clone();
+            inplace_col_normalize_sparse(&mut col_normalized, columns);
+            assert_dense_quotient(&to_dense(&col_normalized, columns), &dense, &col_sums);
+
+            let mut col_upscaled = mat.clone();
+            inplace_col_max_upscale_sparse(&mut col_upscaled, columns);
+            let col_upscaled = to_dense(&col_upscaled, columns);
+            assert_dense_quotient(&col_upscaled, &dense, &col_maxes);
+            for (j, max) in col_maxes.iter().enumerate() {
+                if *max > 0 {
+                    let upscaled_max = col_upscaled.iter().map(|row| row[j]).max();
+                    assert_eq!(upscaled_max, Some(ONE_BITS));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_math_mat_ema_sparse_matches_reference() {
+        let one = I32F32::from_num(1);
+        for seed in 0..CASES {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let n = rng.gen_range(1..=32);
+            let new = random_sparse_mat(&mut rng, n, n as u16, one);
+            let old = random_sparse_mat(&mut rng, n, n as u16, one);
+            let alpha = I32F32::from_bits(rng.gen_range(0..=1 << 32));
+
+            let result = mat_ema_sparse(&new, &old, alpha);
+            let alpha = bits(alpha);
+            let new = to_dense(&new, n as u16);
+            let old = to_dense(&old, n as u16);
+            for ((result_row, new_row), old_row) in
+                to_dense(&result, n as u16).iter().zip(&new).zip(&old)
+            {
+                for ((result, new), old) in result_row.iter().zip(new_row).zip(old_row) {
+                    let reference = alpha * new + (ONE_BITS - alpha) * old;
+                    // Only the two products are rounded, once each.
+                    assert_within_ulps(*result, 32, reference, ONE_BITS * ONE_BITS, 2);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_math_mat_ema_alpha_sparse_matches_constant_alpha() {
+        let one = I32F32::from_num(1);
+        for seed in 0..CASES {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let n = rng.gen_range(1..=32);
+            let new = random_sparse_mat(&mut rng, n, n as u16, one);
+            let old = random_sparse_mat(&mut rng, n, n as u16, one);
+            let alpha = I32F32::from_bits(rng.gen_range(0..=1 << 32));
+            let expected = mat_ema_sparse(&new, &old, alpha);
+
+            assert_eq!(mat_ema_alpha_sparse(&new, &old, &[], alpha), expected);
+
+            // An alpha given for every entry overrides the default one.
+            let alpha_mat: Vec<Vec<(u16, I32F32)>> =
+                vec![(0..n as u16).map(|j| (j, alpha)).collect(); n];
+            assert_eq!(
+                mat_ema_alpha_sparse(&new, &old, &alpha_mat, I32F32::from_num(0)),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_math_vec_max_upscale_to_u16_matches_reference() {
+        for seed in 0..CASES {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let len = rng.gen_range(1..=64);
+            // Large values take the path that scales before multiplying.
+            let max = I32F32::from_num(if rng.gen_bool(0.5) { 1 } else { 1_000_000 });
+            let vector = random_vec(&mut rng, len, max);
+
+            let result = vec_max_upscale_to_u16(&vector);
+            let vector_max = vector.iter().copied().map(bits).max().unwrap_or_default();
+            for (value, upscaled) in vector.iter().zip(&result) {
+                if vector_max == 0 {
+                    assert_eq!(*upscaled, 0);
+                } else {
+                    // Rounded to the nearest integer, plus the rounding of the scale.
+                    assert_within_ulps(
+                        *upscaled as i128,
+                        0,
+                        bits(*value) * u16::MAX as i128,
+                        vector_max,
+                        1,
+                    );
+                }
+            }
+            let expected_max = if vector_max > 0 { u16::MAX } else { 0 };
+            assert_eq!(result.iter().max().copied(), Some(expected_max));
+        }
+    }
+
+    #[test]
+    fn test_math_weighted_median_col_sparse_splits_stake() {
+        let one = I32F32::from_num(1);
+        let zero = I32F32::from_num(0);
+        for seed in 0..CASES {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let rows = rng.gen_range(1..=32);
+            let columns = rng.gen_range(1..=16);
+            let mut stake = random_vec(&mut rng, rows, one);
+            for stake in stake.iter_mut() {
+                if rng.gen_bool(0.2) {
+                    *stake = zero;
+                }
+            }
+            let score = random_sparse_mat(&mut rng, rows, columns, one);
+            let majority = I32F32::from_bits(rng.gen_range(1 << 31..(ONE_BITS * 9 / 10) as i64));
+
+            let result = weighted_median_col_sparse(&stake, &score, columns, majority);
+            assert_eq!(result.len(), columns as usize);
+
+            let mut use_stake: Vec<I32F32> = stake.iter().copied().filter(|s| *s > zero).collect();
+            inplace_normalize(&mut use_stake);
+            // The stake sums below are exact, so the split is checked without any tolerance.
+            let stake_sum: i128 = use_stake.iter().copied().map(bits).sum();
+            let minority = stake_sum - bits(majority);
+
+            let dense = to_dense(&score, columns);
+            for (c, median) in result.iter().enumerate() {
+                let median = bits(*median);
+                let staked: Vec<(i128, i128)> = dense
+                    .iter()
+                    .zip(&stake)
+                    .filter(|(_, stake)| **stake > zero)
+                    .map(|(row, _)| row[c])
+                    .zip(use_stake.iter().copied().map(bits))
+                    .collect();
+                if staked.is_empty() {
+                    assert_eq!(median, 0);
+                    continue;
+                }
+
+                assert!(staked.iter().any(|(score, _)| *score == median));
+                let below: i128 = staked.iter().filter(|(s, _)| *s < median).map(|(_, w)| w).sum();
+                let above: i128 = staked.iter().filter(|(s, _)| *s > median).map(|(_, w)| w).sum();
+                assert!(below <= minority, "{below} stake below the median");
+                assert!(
+                    above <= stake_sum - minority,
+                    "{above} stake above the median"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_math_inplace_normalize_64_matches_reference() {
+        for seed in 0..CASES {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let len = rng.gen_range(1..=64);
+            // Up to 64 stakes below 10^17 RAO, so the total stays below `I64F64::MAX`.
+            let stakes: Vec<u64> =
+                (0..len).map(|_| rng.gen_range(0..100_000_000_000_000_000)).collect();
+            let total: i128 = stakes.iter().map(|stake| *stake as i128).sum();
+
+            let mut normalized: Vec<I64F64> = stakes.iter().map(|s| I64F64::from_num(*s)).collect();
+            inplace_normalize_64(&mut normalized);
+            for (stake, normalized) in stakes.iter().zip(&normalized) {
+                if total == 0 {
+                    assert_eq!(*normalized, I64F64::from_num(0));
+                } else {
+                    assert_within_ulps(normalized.to_bits(), 64, *stake as i128, total, 1);
+                }
+            }
+
+            let narrowed = vec_fixed64_to_fixed32(normalized.clone());
+            for (wide, narrow) in normalized.iter().zip(&narrowed) {
+                assert_within_ulps(bits(*narrow), 32, wide.to_bits(), 1 << 64, 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_math_saturates_at_the_fixed_point_bounds() {
+        let max = I32F32::max_value();
+        let min = I32F32::min_value();
+        let one = I32F32::from_num(1);
+        let half = I32F32::from_num(0.5);
+        let zero = I32F32::from_num(0);
+        let ulp = I32F32::from_bits(1);
+
+        let mut vector = vec![one, -one];
+        inplace_normalize_using_sum(&mut vector, ulp);
+        assert_eq!(vector, vec![max, min]);
+
+        let mut mat = vec![vec![(0, max), (1, max)]];
+        inplace_row_normalize_sparse(&mut mat);
+        assert_eq!(mat, vec![vec![(0, one), (1, one)]]);
+
+        let mut mat = vec![vec![(0, max)], vec![(0, max)]];
+        inplace_col_normalize_sparse(&mut mat, 1);
+        assert_eq!(mat, vec![vec![(0, one)], vec![(0, one)]]);
+
+        assert_eq!(
+            row_hadamard_sparse(&[vec![(0, max), (1, -max)]], &[max]),
+            vec![vec![(0, max), (1, min)]]
+        );
+        assert_eq!(
+            matmul_sparse(&[vec![(0, max)], vec![(0, max)]], &[-max, -max], 1),
+            vec![min]
+        );
+        assert_eq!(
+            mat_ema_sparse(&[vec![(0, max), (0, max)]], &[vec![(0, max)]], half),
+            vec![vec![(0, max)]]
+        );
+        assert_eq!(fixed_proportion_to_u16(I32F32::from_num(2)), u16::MAX);
+
+        // 32768 is the largest maximum multiplied by `u16::MAX` before dividing.
+        assert_eq!(
+            vec_max_upscale_to_u16(&[I32F32::from_num(32_768), one]),
+            vec![u16::MAX, 2]
+        );
+        assert_eq!(
+            vec_max_upscale_to_u16(&[I32F32::from_num(32_769), one]),
+            vec![u16::MAX, 2]
+        );
+        assert_eq!(vec_max_upscale_to_u16(&[max, zero]), vec![u16::MAX, 0]);
+
+        let max_64 = I64F64::max_value();
+        let min_64 = I64F64::min_value();
+        let one_64 = I64F64::from_num(1);
+
+        let mut vector = vec![max_64, max_64];
+        inplace_normalize_64(&mut vector);
+        assert_eq!(vector, vec![one_64, one_64]);
+
+        // The sum is one negative ulp, so both quotients overflow.
+        let mut vector = vec![max_64, min_64];
+        inplace_normalize_64(&mut vector);
+        assert_eq!(vector, vec![min_64, max_64]);
+
+        assert_eq!(
+            vec_fixed64_to_fixed32(vec![
+                I64F64::from_num(1u64 << 31),
+                I64F64::from_num(-(1i64 << 31)),
+                I64F64::from_num(-(1i64 << 31) - 1),
+            ]),
+            vec![max, min, min]
+        );
+    }
+
+    #[test]
+    fn test_math_saturates_instead_of_overflowing() {
+        let max = I32F32::max_value();
+        let min = I32F32::min_value();
+        let half = I32F32::from_num(0.5);
+        let one = I32F32::from_num(1);
+        let zero = I32F32::from_num(0);
+
+        assert!(!is_zero(&[max, max]));
+
+        let mut vector = vec![max, max];
+        inplace_normalize(&mut vector);
+        assert_eq!(vector, vec![one, one]);
+
+        assert_eq!(fixed_proportion_to_u16(max), u16::MAX);
+        assert_eq!(fixed_proportion_to_u16(-one), 0);
+
+        assert_eq!(row_sum_sparse(&[vec![(0, max), (1, max)]]), vec![max]);
+
+        let mat = vec![vec![(0, max), (5, one)], vec![(0, max)]];
+        assert_eq!(matmul_sparse(&mat, &[one, one, one], 2), vec![max, zero]);
+        assert_eq!(matmul_transpose_sparse(&mat, &[max]), vec![max, max]);
+
+        assert_eq!(
+            vecdiv(&[max, -max, one, one], &[half, half, zero]),
+            vec![max, min, zero, zero]
+        );
+
+        assert_eq!(
+            vec_fixed64_to_fixed32(vec![I64F64::max_value(), I64F64::min_value()]),
+            vec![max, min]
+        );
+
+        assert_eq!(vec_max_upscale_to_u16(&[-one, zero]), vec![0, 0]);
+        assert_eq!(vec_max_upscale_to_u16(&[-one, max]), vec![0, u16::MAX]);
+
+        // Columns past the number of rows and missing old rows are kept, not panicking.
+        let new = vec![vec![(3, one)], vec![]];
+        assert_eq!(
+            mat_ema_sparse(&new, &[], half),
+            vec![vec![(3, half)], vec![]]
+        );
+
+        let score = vec![vec![(0, half), (9, one)]];
+        assert_eq!(
+            weighted_median_col_sparse(&[one, one], &score, 1, half),
+            vec![half]
+        );
+
+        assert_eq!(
+            mask_rows_sparse(&[true], &mat),
+            vec![vec![], vec![(0, max)]]
+        );
+        let mut vector = vec![one, one];
+        inplace_mask_vector(&[true], &mut vector);
+        assert_eq!(vector, vec![zero, one]);
+    }
 }
@@ -26,7 +26,7 @@ pub fn ss58_to_account_id<T: Config>(
 
 pub mod v11 {
     use self::{
-        global::BurnConfiguration,
+        global::{BurnConfiguration, BurnDestination},
         old_storage::{MaxBurn, MinBurn},
     };
     use super::*;
@@ -164,6 +164,7 @@ pub mod v11 {
             let burn_config = BurnConfiguration::<T> {
                 min_burn: MinBurn::<T>::get(),
                 max_burn: MaxBurn::<T>::get(),
+                destination: BurnDestination::Destroy,
                 _pd: PhantomData,
             };
 
@@ -264,3 +265,51 @@ pub mod v11 {
         }
     }
 }
+
+pub mod v12 {
+    use self::global::{BurnConfiguration, BurnDestination};
+    use super::*;
+
+    pub mod old_storage {
+        use super::*;
+
+        #[derive(Decode, Encode)]
+        pub struct BurnConfiguration {
+            pub min_burn: u64,
+            pub max_burn: u64,
+        }
+    }
+
+    pub struct MigrateToV12<T>(sp_std::marker::PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV12<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let on_chain_version = StorageVersion::get::<Pallet<T>>();
+
+            if on_chain_version != 11 {
+                log::info!("Storage v12 already updated");
+                return Weight::zero();
+            }
+
+            // Registration burns keep being destroyed until governance picks a destination.
+            let translated =
+                BurnConfig::<T>::translate(|old: Option<old_storage::BurnConfiguration>| {
+                    old.map(|old| BurnConfiguration {
+                        min_burn: old.min_burn,
+                        max_burn: old.max_burn,
+                        destination: BurnDestination::Destroy,
+                        _pd: PhantomData,
+                    })
+                });
+
+            if translated.is_err() {
+                log::error!("error migrating the burn configuration to v12");
+            } else {
+                log::info!("Migrated the burn configuration to v12");
+            }
+
+            StorageVersion::new(12).put::<Pallet<T>>();
+            T::DbWeight::get().reads_writes(2, 2)
+        }
+    }
+}
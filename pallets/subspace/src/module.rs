@@ -1,6 +1,9 @@
 use super::*;
 
-use frame_support::pallet_prelude::{Decode, DispatchResult, Encode};
+use frame_support::{
+    pallet_prelude::{Decode, DispatchError, DispatchResult, Encode},
+    traits::Get,
+};
 use sp_arithmetic::per_things::Percent;
 use sp_std::collections::btree_map::BTreeMap;
 pub struct SubnetDistributionParameters;
@@ -17,6 +20,24 @@ pub struct ModuleStats<T: Config> {
     pub weights: Vec<(u16, u16)>, // Vec of (uid, weight)
 }
 
+#[derive(Decode, Encode, PartialEq, Eq, Clone, Debug)]
+pub struct MetagraphModule<T: Config> {
+    pub uid: u16,
+    pub key: T::AccountId,
+    pub name: Vec<u8>,
+    pub address: Vec<u8>,
+    pub stake: u64,
+    pub emission: u64,
+    pub incentive: u16,
+    pub dividends: u16,
+    pub trust: u16,
+    pub rank: u16,
+    pub validator_permit: bool,
+    pub last_update: u64,
+    pub registration_block: u64,
+    pub delegation_fee: Percent,
+}
+
 #[derive(Debug)]
 pub struct ModuleChangeset {
     pub name: Option<Vec<u8>>,
@@ -119,12 +140,241 @@ impl<T: Config> Pallet<T> {
         let key = ensure_signed(origin)?;
         let uid: u16 = Self::get_uid_for_key(netuid, &key);
 
-        // 2. Apply the changeset
+        // 2. Once a controller is set, the module key can only change its address.
+        if Self::is_controlled(netuid, &key) {
+            let params = Self::module_params(netuid, &key);
+            ensure!(
+                changeset.name.is_none()
+                    && changeset.delegation_fee.map_or(true, |fee| fee == params.delegation_fee)
+                    && changeset
+                        .metadata
+                        .as_ref()
+                        .map_or(true, |metadata| Some(metadata) == params.metadata.as_ref()),
+                Error::<T>::NotModuleController
+            );
+        }
+
+        // 3. Apply the changeset
         changeset.apply::<T>(netuid, key, uid)?;
 
         Ok(())
     }
 
+    /// Moves every per-key entry of `key` to `new_key`, on each subnet the module is
+    /// registered on. Uids, bonds, weights and delegated stake are kept, only the key changes.
+    /// Entries the new key already has, like unlocking chunks and reward records, are merged.
+    ///
+    /// The rotation is authorized by the module controller, so a module with a controller can
+    /// still be moved away from a leaked key.
+    pub fn do_rotate_module_key(
+        origin: T::RuntimeOrigin,
+        netuid: u16,
+        key: T::AccountId,
+        new_key: T::AccountId,
+    ) -> DispatchResult {
+        // --- 1. We check the callers (controller) signature.
+        let controller = ensure_signed(origin)?;
+        ensure!(Self::is_registered(netuid, &key), Error::<T>::NotRegistered);
+
+        // --- 2. The caller must control the module on every subnet it is registered on.
+        let netuids = Self::netuids();
+        for netuid in netuids.iter().filter(|netuid| Self::is_registered(**netuid, &key)) {
+            Self::ensure_module_controller(*netuid, &key, &controller)?;
+        }
+
+        // --- 3. The new key must not be known by any subnet, so no entries have to be merged.
+        ensure!(
+            netuids.iter().all(|netuid| {
+                !Uids::<T>::contains_key(netuid, &new_key)
+                    && StakeTo::<T>::get(netuid, &new_key).is_empty()
+                    && StakeFrom::<T>::get(netuid, &new_key).is_empty()
+            }),
+            Error::<T>::ModuleKeyInUse
+        );
+        // Bids can't be merged, as each one is for its own subnet name.
+        ensure!(
+            !SubnetBids::<T>::contains_key(&key) || !SubnetBids::<T>::contains_key(&new_key),
+            Error::<T>::ModuleKeyInUse
+        );
+
+        // --- 4. Move the module and staker entries on every subnet.
+        let mut rotated = Vec::new();
+        for netuid in netuids {
+            if Self::rotate_module_storage(netuid, &key, &new_key) {
+                rotated.push(netuid);
+            }
+            Self::rotate_staker_storage(netuid, &key, &new_key)?;
+            Self::rotate_founder_storage(netuid, &key, &new_key);
+        }
+        if let Some(bid) = SubnetBids::<T>::take(&key) {
+            SubnetBids::<T>::insert(&new_key, bid);
+        }
+
+        // --- 5. Move the auto-compound settings of the key as a delegator.
+        let settings: Vec<_> = AutoCompound::<T>::drain_prefix(&key).collect();
+        for (module_key, auto_compound) in settings {
+            let module_key = module_key.map(|module_key| {
+                if module_key == key {
+                    new_key.clone()
+                } else {
+                    module_key
+                }
+            });
+            AutoCompound::<T>::insert(&new_key, module_key, auto_compound);
+        }
+
+        // --- 6. Move the conviction locks and voting power delegations.
+        T::handle_key_rotation(&key, &new_key)?;
+
+        Self::deposit_event(Event::ModuleKeyRotated(key, new_key, rotated));
+
+        Ok(())
+    }
+
+    /// Moves the entries of the module registered under `key` to `new_key`. Returns whether the
+    /// key was registered on the subnet.
+    fn rotate_module_storage(netuid: u16, key: &T::AccountId, new_key: &T::AccountId) -> bool {
+        let Some(uid) = Uids::<T>::take(netuid, key) else {
+            return false;
+        };
+
+        Uids::<T>::insert(netuid, new_key, uid);
+        Keys::<T>::insert(netuid, uid, new_key);
+
+        if let Some(metadata) = Metadata::<T>::take(netuid, key) {
+            Metadata::<T>::insert(netuid, new_key, metadata);
+        }
+        DelegationFee::<T>::insert(netuid, new_key, DelegationFee::<T>::take(netuid, key));
+        if let Some(controller) = Controller::<T>::take(netuid, key) {
+            Controller::<T>::insert(netuid, new_key, controller);
+        }
+        Stake::<T>::insert(netuid, new_key, Stake::<T>::take(netuid, key));
+
+        if let Some(commit) = WeightCommits::<T>::take(netuid, key) {
+            WeightCommits::<T>::insert(netuid, new_key, commit);
+        }
+        let set_weight_calls = SetWeightCallsPerEpoch::<T>::take(netuid, key);
+        if set_weight_calls > 0 {
+            SetWeightCallsPerEpoch::<T>::insert(netuid, new_key, set_weight_calls);
+        }
+
+        // Delegators now point to the new key. The module's own stake entry is moved with the
+        // staker entries.
+        let stake_from = StakeFrom::<T>::take(netuid, key);
+        for delegator in stake_from.keys().filter(|delegator| *delegator != key) {
+            StakeTo::<T>::mutate(netuid, delegator, |stake_to| {
+                if let Some(amount) = stake_to.remove(key) {
+                    stake_to.insert(new_key.clone(), amount);
+                }
+            });
+
+            if let Some(auto_compound) = AutoCompound::<T>::take(delegator, Some(key.clone())) {
+                AutoCompound::<T>::insert(delegator, Some(new_key.clone()), auto_compound);
+            }
+        }
+        StakeFrom::<T>::insert(netuid, new_key, stake_from);
+
+        true
+    }
+
+    /// Moves the stake, unlocking chunks and reward history owned by `key` to `new_key`.
+    fn rotate_staker_storage(
+        netuid: u16,
+        key: &T::AccountId,
+        new_key: &T::AccountId,
+    ) -> DispatchResult {
+        let stake_to: BTreeMap<T::AccountId, u64> = StakeTo::<T>::take(netuid, key)
+            .into_iter()
+            .map(|(module_key, amount)| {
+                let module_key = if &module_key == key {
+                    new_key.clone()
+                } else {
+                    module_key
+                };
+
+                StakeFrom::<T>::mutate(netuid, &module_key, |stake_from| {
+                    if let Some(amount) = stake_from.remove(key) {
+                        stake_from.insert(new_key.clone(), amount);
+                    }
+                });
+
+                (module_key, amount)
+            })
+            .collect();
+        if !stake_to.is_empty() {
+            StakeTo::<T>::insert(netuid, new_key, stake_to);
+        }
+
+        // Chunks unlocking at the same block are merged, and the rest must fit the chunk limit.
+        let chunks = Unlocking::<T>::take(netuid, key);
+        if !chunks.is_empty() {
+            Unlocking::<T>::try_mutate(netuid, new_key, |new_chunks| {
+                for chunk in chunks {
+                    match new_chunks.iter_mut().find(|c| c.unlock_block == chunk.unlock_block) {
+                        Some(existing) => {
+                            existing.amount = existing.amount.saturating_add(chunk.amount);
+                        }
+                        None => new_chunks.push(chunk),
+                    }
+                }
+                ensure!(
+                    new_chunks.len() <= MAX_UNLOCKING_CHUNKS,
+                    Error::<T>::TooManyUnlockingChunks
+                );
+                // chunks are kept in unlock order
+                new_chunks.sort_by_key(|chunk| chunk.unlock_block);
+                Ok::<(), DispatchError>(())
+            })?;
+        }
+
+        let history = RewardHistory::<T>::take(netuid, key);
+        if !history.is_empty() {
+            for record in &history {
+                RewardedAccounts::<T>::mutate(netuid, record.block, |accounts| {
+                    if !accounts.contains(new_key) {
+                        accounts.push(new_key.clone());
+                    }
+                });
+            }
+
+            RewardHistory::<T>::mutate(netuid, new_key, |new_history| {
+                let mut records: BTreeMap<u64, u64> =
+                    new_history.iter().map(|record| (record.block, record.amount)).collect();
+                for record in history {
+                    let amount = records.entry(record.block).or_default();
+                    *amount = amount.saturating_add(record.amount);
+                }
+
+                // Only the most recent records fit in the history.
+                let skip = records.len().saturating_sub(T::RewardHistoryDepth::get() as usize);
+                *new_history = sp_runtime::BoundedVec::truncate_from(
+                    records
+                        .into_iter()
+                        .skip(skip)
+                        .map(|(block, amount)| RewardRecord { block, amount })
+                        .collect(),
+                );
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Moves the subnet and its auction deposit to `new_key` if `key` founded it.
+    fn rotate_founder_storage(netuid: u16, key: &T::AccountId, new_key: &T::AccountId) {
+        if &Founder::<T>::get(netuid) == key {
+            Founder::<T>::insert(netuid, new_key);
+        }
+
+        SubnetDeposit::<T>::mutate(netuid, |deposit| {
+            if let Some((founder, _)) = deposit {
+                if *founder == *key {
+                    *founder = new_key.clone();
+                }
+            }
+        });
+    }
+
     pub fn does_module_name_exist(netuid: u16, name: &[u8]) -> bool {
         Name::<T>::iter_prefix_values(netuid).any(|existing| existing == name)
     }
@@ -137,10 +387,58 @@ impl<T: Config> Pallet<T> {
             address: Address::<T>::get(netuid, uid),
             metadata: Metadata::<T>::get(netuid, key),
             delegation_fee: DelegationFee::<T>::get(netuid, key),
-            controller: key.clone(),
+            controller: Self::module_controller(netuid, key),
         }
     }
 
+    /// Returns the controller of the module, which is the module key itself when none was set.
+    pub fn module_controller(netuid: u16, key: &T::AccountId) -> T::AccountId {
+        Controller::<T>::get(netuid, key).unwrap_or_else(|| key.clone())
+    }
+
+    /// Whether the module is managed by a controller other than its own key.
+    pub fn is_controlled(netuid: u16, key: &T::AccountId) -> bool {
+        Controller::<T>::get(netuid, key).is_some_and(|controller| &controller != key)
+    }
+
+    pub fn ensure_module_controller(
+        netuid: u16,
+        module_key: &T::AccountId,
+        who: &T::AccountId,
+    ) -> DispatchResult {
+        ensure!(
+            &Self::module_controller(netuid, module_key) == who,
+            Error::<T>::NotModuleController
+        );
+        Ok(())
+    }
+
+    /// Hands the management of the module's stake, delegation fee and registration over to
+    /// `controller`. Setting the module key itself removes the controller.
+    pub fn do_set_module_controller(
+        origin: T::RuntimeOrigin,
+        netuid: u16,
+        module_key: T::AccountId,
+        controller: T::AccountId,
+    ) -> DispatchResult {
+        let key = ensure_signed(origin)?;
+        ensure!(
+            Self::is_registered(netuid, &module_key),
+            Error::<T>::NotRegistered
+        );
+        Self::ensure_module_controller(netuid, &module_key, &key)?;
+
+        if controller == module_key {
+            Controller::<T>::remove(netuid, &module_key);
+        } else {
+            Controller::<T>::insert(netuid, &module_key, &controller);
+        }
+
+        Self::deposit_event(Event::ModuleControllerSet(netuid, module_key, controller));
+
+        Ok(())
+    }
+
     /// Appends the uid to the network (without increasing stake).
     pub fn append_module(
         netuid: u16,
@@ -267,6 +565,8 @@ impl<T: Config> Pallet<T> {
         // SWAP WEIGHTS
         Weights::<T>::insert(netuid, uid, Weights::<T>::get(netuid, replace_uid)); // Make uid - key association.
         Weights::<T>::remove(netuid, replace_uid); // Make uid - key association.
+        Self::swap_weight_history(netuid, uid, replace_uid);
+        WeightCommits::<T>::remove(netuid, &module_key);
 
         // HANDLE THE REGISTRATION BLOCK
         RegistrationBlock::<T>::insert(
@@ -302,6 +602,7 @@ impl<T: Config> Pallet<T> {
 
         // remove stake from old key and add to new key
         Self::remove_stake_from_storage(netuid, &module_key);
+        Controller::<T>::remove(netuid, &module_key);
 
         // 3. Remove the network if it is empty.
         let module_count = N::<T>::mutate(netuid, |v| {
@@ -341,4 +642,46 @@ impl<T: Config> Pallet<T> {
             weights,
         }
     }
+
+    /// Returns the metagraph of the modules with uids in `start_uid..start_uid + limit`. Subnet
+    /// wide vectors are read once, so this is much cheaper than querying module by module.
+    pub fn get_subnet_metagraph(
+        netuid: u16,
+        start_uid: u16,
+        limit: u16,
+    ) -> Vec<MetagraphModule<T>> {
+        let end_uid = start_uid.saturating_add(limit).min(N::<T>::get(netuid));
+
+        let emission = Emission::<T>::get(netuid);
+        let incentive = Incentive::<T>::get(netuid);
+        let dividends = Dividends::<T>::get(netuid);
+        let trust = Trust::<T>::get(netuid);
+        let rank = Rank::<T>::get(netuid);
+        let validator_permits = ValidatorPermits::<T>::get(netuid);
+        let last_update = LastUpdate::<T>::get(netuid);
+
+        (start_uid..end_uid)
+            .filter_map(|uid| {
+                let key = Self::get_key_for_uid(netuid, uid)?;
+                let idx = uid as usize;
+
+                Some(MetagraphModule {
+                    uid,
+                    name: Name::<T>::get(netuid, uid),
+                    address: Address::<T>::get(netuid, uid),
+                    stake: Stake::<T>::get(netuid, &key),
+                    emission: emission.get(idx).copied().unwrap_or_default(),
+                    incentive: incentive.get(idx).copied().unwrap_or_default(),
+                    dividends: dividends.get(idx).copied().unwrap_or_default(),
+                    trust: trust.get(idx).copied().unwrap_or_default(),
+                    rank: rank.get(idx).copied().unwrap_or_default(),
+                    validator_permit: validator_permits.get(idx).copied().unwrap_or_default(),
+                    last_update: last_update.get(idx).copied().unwrap_or_default(),
+                    registration_block: RegistrationBlock::<T>::get(netuid, uid),
+                    delegation_fee: DelegationFee::<T>::get(netuid, &key),
+                    key,
+                })
+            })
+            .collect()
+    }
 }
@@ -0,0 +1,85 @@
+use super::*;
+
+use crate::global::RateLimitedCall;
+use sp_runtime::transaction_validity::{InvalidTransaction, TransactionValidityError};
+
+impl<T: Config> Pallet<T> {
+    /// Returns the kind of rate limited call this is, if it is limited at all.
+    pub fn rate_limited_call(call: &Call<T>) -> Option<RateLimitedCall> {
+        match call {
+            Call::add_stake { .. } | Call::add_stake_multiple { .. } => {
+                Some(RateLimitedCall::AddStake)
+            }
+            Call::remove_stake { .. }
+            | Call::remove_stake_multiple { .. }
+            | Call::controller_remove_stake { .. } => Some(RateLimitedCall::RemoveStake),
+            Call::transfer_stake { .. } | Call::transfer_multiple { .. } => {
+                Some(RateLimitedCall::TransferStake)
+            }
+            Call::update_module { .. } | Call::controller_update_module { .. } => {
+                Some(RateLimitedCall::UpdateModule)
+            }
+            _ => None,
+        }
+    }
+
+    /// The limit of a kind of call, if the limits are enabled for it.
+    fn active_rate_limit(kind: RateLimitedCall) -> Option<u16> {
+        let config = RateLimitConfig::<T>::get();
+        let limit = config.limit(kind);
+        (config.interval != 0 && limit != 0).then_some(limit)
+    }
+
+    /// Ensures the account did not use up its calls of this kind for the current interval.
+    /// Returns the kind of the call when it counts towards a limit.
+    pub fn ensure_within_rate_limit(
+        who: &T::AccountId,
+        call: &Call<T>,
+    ) -> Result<Option<RateLimitedCall>, TransactionValidityError> {
+        let Some(kind) = Self::rate_limited_call(call) else {
+            return Ok(None);
+        };
+        let Some(limit) = Self::active_rate_limit(kind) else {
+            return Ok(None);
+        };
+
+        if RateLimitUsage::<T>::get(who, kind) >= limit {
+            return Err(InvalidTransaction::Custom(kind.error_code()).into());
+        }
+
+        Ok(Some(kind))
+    }
+
+    /// Counts a call that is being dispatched against the limit of its signer. The limited
+    /// dispatchables call this themselves, so calls wrapped in batches or multisigs count too.
+    pub fn consume_rate_limit(
+        origin: &T::RuntimeOrigin,
+        kind: RateLimitedCall,
+    ) -> dispatch::DispatchResult {
+        let who = ensure_signed(origin.clone())?;
+        let Some(limit) = Self::active_rate_limit(kind) else {
+            return Ok(());
+        };
+
+        ensure!(
+            RateLimitUsage::<T>::get(&who, kind) < limit,
+            Error::<T>::RateLimitExceeded
+        );
+        Self::count_rate_limited_call(&who, kind);
+
+        Ok(())
+    }
+
+    /// Adds a call to the counter of the account.
+    pub fn count_rate_limited_call(who: &T::AccountId, kind: RateLimitedCall) {
+        RateLimitUsage::<T>::mutate(who, kind, |calls| *calls = calls.saturating_add(1));
+    }
+
+    /// Resets the call counters of every account at the start of each interval.
+    pub fn reset_rate_limits(block_number: u64) {
+        let interval = RateLimitConfig::<T>::get().interval;
+        if interval != 0 && block_number.checked_rem(interval) == Some(0) {
+            let _ = RateLimitUsage::<T>::clear(u32::MAX, None);
+        }
+    }
+}
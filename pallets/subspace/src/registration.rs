@@ -1,4 +1,4 @@
-use crate::{module::ModuleChangeset, subnet::SubnetChangeset};
+use crate::{global::BurnDestination, module::ModuleChangeset, subnet::SubnetChangeset};
 
 use super::*;
 
@@ -6,10 +6,44 @@ use frame_support::{pallet_prelude::DispatchResult, LOG_TARGET};
 use frame_system::ensure_signed;
 
 use sp_core::{keccak_256, sha2_256, Get, H256, U256};
-use sp_runtime::MultiAddress;
+use sp_runtime::{DispatchError, MultiAddress};
 use sp_std::vec::Vec;
 use system::pallet_prelude::BlockNumberFor;
 
+/// What registering a new module does to the existing ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModuleSlot {
+    /// There is room for the module.
+    Free,
+    /// The module on `uid` of `netuid` is removed to make room.
+    Prune { netuid: u16, uid: u16 },
+    /// The subnet is full and every module on it is in immunity period.
+    Immune,
+}
+
+/// What a registration on a subnet would cost and change, as of the current block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegistrationPreview<T: Config> {
+    /// The first check the registration fails, `None` when it would go through.
+    pub error: Option<DispatchError>,
+    /// Taken from the registration stake.
+    pub burn: u64,
+    /// Stake the module needs on top of the burn.
+    pub min_stake: u64,
+    /// Whether the given stake covers the minimum stake and the burn.
+    pub enough_stake: bool,
+    pub registrations_left_this_block: u16,
+    pub registrations_left_this_interval: u16,
+    /// Whether the subnet doesn't exist yet and is created by the registration.
+    pub creates_subnet: bool,
+    /// The subnet removed to make room for the new one, when all subnet slots are in use.
+    pub replaced_subnet: Option<u16>,
+    pub immunity_period: u16,
+    pub module_slot: ModuleSlot,
+    /// Key of the module removed to make room.
+    pub pruned_key: Option<T::AccountId>,
+}
+
 impl<T: Config> Pallet<T> {
     // Used on extrinsics, can panic
     #[allow(clippy::arithmetic_side_effects)]
@@ -108,6 +142,7 @@ impl<T: Config> Pallet<T> {
         if current_burn > 0 {
             // if min burn is present, decrease the stake by the min burn
             Self::decrease_stake(netuid, &key, &module_key, current_burn);
+            Self::distribute_registration_burn(netuid, &module_key, current_burn);
         }
 
         // Make sure that the registration went through.
@@ -128,10 +163,46 @@ impl<T: Config> Pallet<T> {
         Ok(())
     }
 
+    /// Sends the burn taken from the registration stake to the configured destination. The stake
+    /// is already out of the total issuance, so it is only minted back when it is not destroyed.
+    fn distribute_registration_burn(netuid: u16, module_key: &T::AccountId, burn: u64) {
+        let destination = BurnConfig::<T>::get().destination;
+        let recipient = match destination {
+            BurnDestination::Destroy => None,
+            BurnDestination::DaoTreasury => Some(T::get_dao_treasury_address()),
+            BurnDestination::SubnetFounder => Some(Founder::<T>::get(netuid)),
+        };
+
+        if let Some(recipient) = recipient {
+            Self::add_balance_to_account(
+                &recipient,
+                Self::u64_to_balance(burn).unwrap_or_default(),
+            );
+        }
+
+        Self::deposit_event(Event::RegistrationBurned(
+            netuid,
+            module_key.clone(),
+            burn,
+            destination,
+        ));
+    }
+
     pub fn do_deregister(origin: T::RuntimeOrigin, netuid: u16) -> DispatchResult {
         // --- 1. Check that the caller has signed the transaction.
         let key = ensure_signed(origin)?;
 
+        // --- 2. Modules with a controller can only be deregistered by it.
+        ensure!(
+            !Self::is_controlled(netuid, &key),
+            Error::<T>::NotModuleController
+        );
+
+        Self::deregister_module(netuid, key)
+    }
+
+    /// Removes the module from the subnet, on behalf of the module key or its controller.
+    pub fn deregister_module(netuid: u16, key: T::AccountId) -> DispatchResult {
         ensure!(
             Self::key_registered(netuid, &key),
             Error::<T>::NotRegistered
@@ -325,21 +396,37 @@ impl<T: Config> Pallet<T> {
         stake: u64,
         changeset: SubnetChangeset<T>,
     ) -> Result<u16, sp_runtime::DispatchError> {
+        let target_subnet = Self::get_subnet_replaced_by_registration(stake)?;
+        if let Some(netuid) = target_subnet {
+            Self::forfeit_subnet_deposit(netuid);
+            Self::remove_subnet(netuid);
+        }
+
+        Self::add_subnet(changeset, target_subnet)
+    }
+
+    /// Returns the subnet a registration with `stake` removes to create a new one, `None` while
+    /// there are free subnet slots.
+    pub fn get_subnet_replaced_by_registration(stake: u64) -> Result<Option<u16>, DispatchError> {
         let num_subnets: u16 = TotalSubnets::<T>::get();
         let max_subnets: u16 = MaxAllowedSubnets::<T>::get();
 
         // if we have not reached the max number of subnets, then we can start a new one
-        let target_subnet = if num_subnets >= max_subnets {
-            let (min_stake_netuid, min_stake) = Self::get_least_staked_netuid();
-            // if the stake is greater than the least staked network, then we can start a new one
-            ensure!(stake > min_stake, Error::<T>::NotEnoughStakeToStartNetwork);
-            Self::remove_subnet(min_stake_netuid);
-            Some(min_stake_netuid)
-        } else {
-            None
-        };
+        if num_subnets < max_subnets {
+            return Ok(None);
+        }
 
-        Self::add_subnet(changeset, target_subnet)
+        // once the slot auction is enabled, it is the only way to replace a subnet
+        ensure!(
+            SubnetAuctionPeriod::<T>::get() == 0,
+            Error::<T>::SubnetSlotAuctionRequired
+        );
+
+        let (min_stake_netuid, min_stake) = Self::get_least_staked_netuid();
+        // if the stake is greater than the least staked network, then we can start a new one
+        ensure!(stake > min_stake, Error::<T>::NotEnoughStakeToStartNetwork);
+
+        Ok(Some(min_stake_netuid))
     }
 
     // returns the amount of total modules on the network
@@ -351,15 +438,24 @@ impl<T: Config> Pallet<T> {
     /// subnet is filled, deregister the least staked module on it, or if the max allowed modules on
     /// the network is reached, deregisters the least staked module on the least staked netuid.
     pub fn reserve_module_slot(netuid: u16) -> Option<()> {
+        match Self::get_module_slot(netuid) {
+            ModuleSlot::Free => Some(()),
+            ModuleSlot::Prune { netuid, uid } => {
+                Self::remove_module(netuid, uid);
+                Some(())
+            }
+            ModuleSlot::Immune => None,
+        }
+    }
+
+    /// Returns the module `reserve_module_slot` would remove to register a new one on the subnet.
+    pub fn get_module_slot(netuid: u16) -> ModuleSlot {
         if N::<T>::get(netuid) >= MaxAllowedUids::<T>::get(netuid) {
             // If we reach the max allowed modules for this subnet,
             // then we replace the lowest priority node in the current subnet
-            let lowest_uid = Self::get_lowest_uid(netuid, false);
-            if let Some(uid) = lowest_uid {
-                Self::remove_module(netuid, uid);
-                Some(())
-            } else {
-                None
+            match Self::get_lowest_uid(netuid, false) {
+                Some(uid) => ModuleSlot::Prune { netuid, uid },
+                None => ModuleSlot::Immune,
             }
         } else if Self::global_n_modules() >= MaxAllowedModules::<T>::get() {
             let (subnet_uid, _) = Self::get_least_staked_netuid();
@@ -369,10 +465,115 @@ impl<T: Config> Pallet<T> {
             // in this case we should ignore the immunity period,
             // Because if the lowest subnet has unreasonably high immunity period,
             // it could lead to exploitation of the network.
-            Self::remove_module(subnet_uid, module_uid);
-            Some(())
+            ModuleSlot::Prune {
+                netuid: subnet_uid,
+                uid: module_uid,
+            }
+        } else {
+            ModuleSlot::Free
+        }
+    }
+
+    /// Returns the module `reserve_module_slot` would remove to register on the subnet that takes
+    /// the slot of `removed_netuid`. The new subnet is empty and the modules of the removed one no
+    /// longer count towards the global limit.
+    fn get_module_slot_after_subnet_removal(removed_netuid: u16) -> ModuleSlot {
+        let global_n_modules = Self::global_n_modules().saturating_sub(N::<T>::get(removed_netuid));
+        if global_n_modules < MaxAllowedModules::<T>::get() {
+            return ModuleSlot::Free;
+        }
+
+        let least_staked = TotalStake::<T>::iter()
+            .filter(|(netuid, _)| *netuid != removed_netuid)
+            .min_by_key(|(_, stake)| *stake);
+        match least_staked {
+            Some((netuid, _)) => ModuleSlot::Prune {
+                netuid,
+                uid: Self::get_lowest_uid(netuid, true).unwrap_or(0),
+            },
+            None => ModuleSlot::Free,
+        }
+    }
+
+    /// Previews a registration with `stake` on the subnet, without writing anything, running the
+    /// checks of `do_register` that don't depend on the caller. Modules are pruned by
+    /// `get_lowest_uid`, which scores them by emission outside their immunity period.
+    pub fn get_registration_preview(netuid: u16, stake: u64) -> RegistrationPreview<T> {
+        let creates_subnet = !Self::if_subnet_exist(netuid);
+
+        let (burn, min_stake, max_registrations_per_interval, immunity_period) = if creates_subnet {
+            let defaults = DefaultSubnetParams::<T>::get();
+            (
+                BurnConfig::<T>::get().min_burn,
+                defaults.min_stake,
+                defaults.max_registrations_per_interval,
+                defaults.immunity_period,
+            )
         } else {
-            Some(())
+            (
+                Burn::<T>::get(netuid),
+                MinStake::<T>::get(netuid),
+                MaxRegistrationsPerInterval::<T>::get(netuid),
+                ImmunityPeriod::<T>::get(netuid),
+            )
+        };
+
+        let replaced_subnet = if creates_subnet {
+            Self::get_subnet_replaced_by_registration(stake)
+        } else {
+            Ok(None)
+        };
+
+        // `do_register` lets registrations through while the interval count is at most the max.
+        let registrations_left_this_interval = max_registrations_per_interval
+            .saturating_add(1)
+            .saturating_sub(RegistrationsThisInterval::<T>::get(netuid));
+        let registrations_left_this_block =
+            MaxRegistrationsPerBlock::<T>::get().saturating_sub(RegistrationsPerBlock::<T>::get());
+        let enough_stake = Self::enough_stake_to_register(min_stake, burn, stake);
+
+        let module_slot = match replaced_subnet {
+            Ok(Some(replaced_netuid)) => {
+                Self::get_module_slot_after_subnet_removal(replaced_netuid)
+            }
+            _ => Self::get_module_slot(netuid),
+        };
+        let pruned_key = match module_slot {
+            ModuleSlot::Prune { netuid, uid } => Self::get_key_for_uid(netuid, uid),
+            ModuleSlot::Free | ModuleSlot::Immune => None,
+        };
+
+        // The checks in the order `do_register` runs them.
+        let check = || -> Result<(), DispatchError> {
+            ensure!(
+                registrations_left_this_block > 0,
+                Error::<T>::TooManyRegistrationsPerBlock
+            );
+            replaced_subnet?;
+            ensure!(
+                registrations_left_this_interval > 0,
+                Error::<T>::TooManyRegistrationsPerInterval
+            );
+            ensure!(enough_stake, Error::<T>::NotEnoughStakeToRegister);
+            ensure!(
+                module_slot != ModuleSlot::Immune,
+                Error::<T>::NetworkIsImmuned
+            );
+            Ok(())
+        };
+
+        RegistrationPreview {
+            error: check().err(),
+            burn,
+            min_stake,
+            enough_stake,
+            registrations_left_this_block,
+            registrations_left_this_interval,
+            creates_subnet,
+            replaced_subnet: replaced_subnet.ok().flatten(),
+            immunity_period,
+            module_slot,
+            pruned_key,
         }
     }
 }
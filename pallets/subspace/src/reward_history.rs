@@ -0,0 +1,87 @@
+use super::*;
+
+impl<T: Config> Pallet<T> {
+    /// Appends the rewards distributed on the subnet's epoch to the history of each account, if
+    /// the subnet keeps one. Once `RewardHistoryDepth` epochs are kept, the records of the
+    /// oldest epoch are dropped from the accounts that earned on it.
+    pub fn record_rewards(netuid: u16, rewards: BTreeMap<T::AccountId, u64>) {
+        if !RewardHistoryEnabled::<T>::get(netuid) || T::RewardHistoryDepth::get() == 0 {
+            return;
+        }
+
+        let current_block = Self::get_current_block_number();
+
+        let mut epochs = RewardHistoryEpochs::<T>::get(netuid);
+        if epochs.is_full() {
+            let oldest = epochs.remove(0);
+            for key in RewardedAccounts::<T>::take(netuid, oldest) {
+                RewardHistory::<T>::mutate_exists(netuid, &key, |maybe_records| {
+                    let Some(records) = maybe_records else {
+                        return;
+                    };
+
+                    records.retain(|record| record.block > oldest);
+                    if records.is_empty() {
+                        *maybe_records = None;
+                    }
+                });
+            }
+        }
+
+        let mut rewarded = Vec::new();
+        for (key, amount) in rewards {
+            if amount == 0 {
+                continue;
+            }
+
+            RewardHistory::<T>::mutate(netuid, &key, |records| {
+                if records.is_full() {
+                    records.remove(0);
+                }
+
+                let _ = records.try_push(RewardRecord {
+                    block: current_block,
+                    amount,
+                });
+            });
+            rewarded.push(key);
+        }
+
+        if !rewarded.is_empty() {
+            RewardedAccounts::<T>::insert(netuid, current_block, rewarded);
+        }
+        let _ = epochs.try_push(current_block);
+        RewardHistoryEpochs::<T>::insert(netuid, epochs);
+    }
+
+    /// Clears the reward history of every account on the subnet.
+    pub fn clear_reward_history(netuid: u16) {
+        let _ = RewardHistory::<T>::clear_prefix(netuid, u32::MAX, None);
+        let _ = RewardedAccounts::<T>::clear_prefix(netuid, u32::MAX, None);
+        RewardHistoryEpochs::<T>::remove(netuid);
+    }
+
+    /// Returns the reward records of the key with blocks in `from_block..=to_block`, on the given
+    /// subnet or on every subnet when `None`.
+    pub fn get_reward_history(
+        key: &T::AccountId,
+        netuid: Option<u16>,
+        from_block: u64,
+        to_block: u64,
+    ) -> Vec<(u16, RewardRecord)> {
+        let netuids = match netuid {
+            Some(netuid) => vec![netuid],
+            None => Self::netuids(),
+        };
+
+        netuids
+            .into_iter()
+            .flat_map(|netuid| {
+                RewardHistory::<T>::get(netuid, key)
+                    .into_iter()
+                    .filter(|record| (from_block..=to_block).contains(&record.block))
+                    .map(move |record| (netuid, record))
+            })
+            .collect()
+    }
+}
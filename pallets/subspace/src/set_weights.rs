@@ -1,6 +1,32 @@
 use core::num::NonZeroU64;
 
 use super::*;
+use frame_support::storage::with_storage_layer;
+use sp_core::H256;
+use sp_runtime::DispatchError;
+
+/// Maximum amount of subnets a single `set_weights_multiple` call can set weights on.
+pub const MAX_SET_WEIGHTS_BATCH: usize = 128;
+
+/// Outcome of running the `set_weights` checks without writing anything, along with the limits
+/// the weights are checked against.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WeightsValidation {
+    /// The first check the weights fail, `None` when they would be accepted.
+    pub error: Option<DispatchError>,
+    /// Minimum amount of uids the weights must contain.
+    pub min_allowed_weights: u16,
+    /// Maximum amount of uids the weights can contain.
+    pub max_allowed_weights: u16,
+    /// Stake the key needs to set weights on the given amount of uids.
+    pub min_stake_for_weights: u64,
+    pub stake: u64,
+    /// `set_weights` calls the key made this epoch, and how many it is allowed, 0 for no limit.
+    pub set_weight_calls: u16,
+    pub max_set_weight_calls: u16,
+    /// The normalized weights that would be stored, empty when a check fails.
+    pub weights: Vec<(u16, u16)>,
+}
 
 impl<T: Config> Pallet<T> {
     // Returns true if the items contain duplicates.
@@ -9,8 +35,6 @@ impl<T: Config> Pallet<T> {
         items.iter().any(|item| !seen.insert(item))
     }
 
-    // Outside of the on_initalize hook, it's ok for this code to panic.
-    #[allow(clippy::arithmetic_side_effects, clippy::indexing_slicing)]
     pub fn do_set_weights(
         origin: T::RuntimeOrigin,
         netuid: u16,
@@ -20,30 +44,221 @@ impl<T: Config> Pallet<T> {
         // --- 1. Check the caller's signature. This is the key of a registered account.
         let key = ensure_signed(origin)?;
 
-        // --- 2. Check that the length of uid list and value list are equal for this network.
+        // --- 2. Check that the key can submit weights on this network.
+        Self::ensure_can_submit_weights(netuid, &key)?;
+
+        // --- 3. Subnets using commit-reveal only accept revealed weights.
         ensure!(
-            uids.len() == values.len(),
-            Error::<T>::WeightVecNotEqualSize
+            !CommitRevealWeights::<T>::get(netuid),
+            Error::<T>::CommitRevealEnabled
         );
 
-        // --- 3. Check to see if this is a valid network.
+        // --- 4. Validate and store the weights.
+        Self::finalize_weights(netuid, &key, uids, values)
+    }
+
+    /// Sets the weights of the caller on multiple subnets at once. Each entry is validated like a
+    /// `set_weights` call, and failing entries are reported instead of aborting the batch. Every
+    /// entry is charged like a `set_weights` call, failing ones always pay their fee.
+    pub fn do_set_weights_multiple(
+        origin: T::RuntimeOrigin,
+        weights: Vec<(u16, Vec<u16>, Vec<u16>)>,
+    ) -> dispatch::DispatchResult {
+        let key = ensure_signed(origin.clone())?;
+
+        ensure!(!weights.is_empty(), Error::<T>::EmptyWeightsBatch);
         ensure!(
-            Self::if_subnet_exist(netuid),
-            Error::<T>::NetworkDoesNotExist
+            weights.len() <= MAX_SET_WEIGHTS_BATCH,
+            Error::<T>::WeightsBatchTooLarge
+        );
+
+        let mut succeeded = Vec::new();
+        let mut failed = Vec::new();
+        for (netuid, uids, values) in weights {
+            // Roll back whatever a failing entry wrote before erroring.
+            let res = with_storage_layer(|| {
+                Self::ensure_can_submit_weights(netuid, &key)?;
+                ensure!(
+                    !CommitRevealWeights::<T>::get(netuid),
+                    Error::<T>::CommitRevealEnabled
+                );
+                Self::finalize_weights(netuid, &key, uids, values)
+            });
+
+            match res {
+                Ok(()) => {
+                    Self::charge_call_fee(&origin, CallType::SetWeights)?;
+                    succeeded.push(netuid);
+                }
+                Err(err) => {
+                    Self::pay_call_fee(&key, Self::call_fee(&CallType::SetWeights))?;
+                    failed.push((netuid, err));
+                }
+            }
+        }
+
+        ensure!(!succeeded.is_empty(), Error::<T>::NoWeightsSet);
+
+        Self::deposit_event(Event::WeightsSetMultiple(key, succeeded, failed));
+
+        Ok(())
+    }
+
+    /// Stores the hash of the weights the key will reveal later through `reveal_weights`.
+    pub fn do_commit_weights(
+        origin: T::RuntimeOrigin,
+        netuid: u16,
+        commit_hash: H256,
+    ) -> dispatch::DispatchResult {
+        let key = ensure_signed(origin)?;
+
+        Self::ensure_can_submit_weights(netuid, &key)?;
+        ensure!(
+            CommitRevealWeights::<T>::get(netuid),
+            Error::<T>::CommitRevealDisabled
         );
 
-        // --- 4. Check to see if the key is registered to the passed network.
+        // A new commit replaces the pending one.
+        let commit = WeightCommit {
+            hash: commit_hash,
+            commit_block: Self::get_current_block_number(),
+        };
+        WeightCommits::<T>::insert(netuid, &key, commit);
+
+        let uid = Self::get_uid_for_key(netuid, &key);
+        Self::deposit_event(Event::WeightsCommitted(netuid, uid));
+
+        Ok(())
+    }
+
+    /// Reveals the weights of a previous commit. The reveal is accepted once the epoch of the
+    /// commit has ended, and for `weight_reveal_period` epochs after it.
+    pub fn do_reveal_weights(
+        origin: T::RuntimeOrigin,
+        netuid: u16,
+        uids: Vec<u16>,
+        values: Vec<u16>,
+        salt: Vec<u8>,
+    ) -> dispatch::DispatchResult {
+        let key = ensure_signed(origin)?;
+
+        // The call was already counted when committing.
+        Self::check_can_submit_weights(netuid, &key)?;
+        ensure!(
+            CommitRevealWeights::<T>::get(netuid),
+            Error::<T>::CommitRevealDisabled
+        );
+
+        let commit = WeightCommits::<T>::get(netuid, &key).ok_or(Error::<T>::NoWeightCommit)?;
+
+        // Revealing in the epoch of the commit would let others copy the weights before the
+        // epoch runs.
+        let commit_epoch = Self::get_epoch_index(netuid, commit.commit_block);
+        let current_epoch = Self::get_epoch_index(netuid, Self::get_current_block_number());
+        ensure!(
+            current_epoch > commit_epoch,
+            Error::<T>::WeightRevealTooEarly
+        );
+        ensure!(
+            current_epoch
+                <= commit_epoch.saturating_add(WeightRevealPeriod::<T>::get(netuid) as u64),
+            Error::<T>::WeightRevealExpired
+        );
+
+        ensure!(
+            commit.hash == Self::hash_weights(&key, netuid, &uids, &values, &salt),
+            Error::<T>::InvalidWeightReveal
+        );
+
+        WeightCommits::<T>::remove(netuid, &key);
+
+        Self::finalize_weights(netuid, &key, uids, values)
+    }
+
+    /// Index of the epoch the block belongs to. An epoch ends on the block where
+    /// `blocks_until_next_epoch` reaches zero, before the extrinsics of that block.
+    pub fn get_epoch_index(netuid: u16, block_number: u64) -> u64 {
+        block_number
+            .saturating_add(netuid as u64)
+            .checked_div(Tempo::<T>::get(netuid) as u64)
+            .unwrap_or_default()
+    }
+
+    /// Hash validators have to commit to before revealing their weights. It binds the key and the
+    /// subnet, so a copied commit can't be revealed by another validator.
+    pub fn hash_weights(
+        key: &T::AccountId,
+        netuid: u16,
+        uids: &[u16],
+        values: &[u16],
+        salt: &[u8],
+    ) -> H256 {
+        H256(sp_core::blake2_256(
+            &(key, netuid, uids, values, salt).encode(),
+        ))
+    }
+
+    /// Runs every check of a `set_weights` call for the key without writing anything.
+    pub fn validate_weights(
+        netuid: u16,
+        key: &T::AccountId,
+        uids: Vec<u16>,
+        values: Vec<u16>,
+    ) -> WeightsValidation {
+        let min_stake_for_weights = MinWeightStake::<T>::get().saturating_mul(uids.len() as u64);
+
+        let result = Self::check_can_submit_weights(netuid, key)
+            .and_then(|()| {
+                ensure!(
+                    !CommitRevealWeights::<T>::get(netuid),
+                    Error::<T>::CommitRevealEnabled
+                );
+                Ok(())
+            })
+            .and_then(|()| Self::check_weights(netuid, key, &uids, values));
+        let (error, weights) = match result {
+            Ok(weights) => (None, weights),
+            Err(err) => (Some(err), Vec::new()),
+        };
+
+        WeightsValidation {
+            error,
+            min_allowed_weights: Self::get_min_allowed_weights(netuid),
+            max_allowed_weights: MaxAllowedWeights::<T>::get(netuid).min(N::<T>::get(netuid)),
+            min_stake_for_weights,
+            stake: Stake::<T>::get(netuid, key),
+            set_weight_calls: SetWeightCallsPerEpoch::<T>::get(netuid, key),
+            max_set_weight_calls: MaximumSetWeightCallsPerEpoch::<T>::get(netuid),
+            weights,
+        }
+    }
+
+    fn ensure_can_submit_weights(netuid: u16, key: &T::AccountId) -> dispatch::DispatchResult {
+        Self::check_can_submit_weights(netuid, key)?;
+
+        if MaximumSetWeightCallsPerEpoch::<T>::get(netuid) != 0 {
+            SetWeightCallsPerEpoch::<T>::mutate(netuid, key, |value| {
+                *value = value.saturating_add(1)
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks the key can submit weights on the network, without counting the call.
+    fn check_can_submit_weights(netuid: u16, key: &T::AccountId) -> dispatch::DispatchResult {
+        // Check to see if this is a valid network.
         ensure!(
-            Self::key_registered(netuid, &key),
-            Error::<T>::NotRegistered
+            Self::if_subnet_exist(netuid),
+            Error::<T>::NetworkDoesNotExist
         );
 
+        // Check to see if the key is registered to the passed network.
+        ensure!(Self::key_registered(netuid, key), Error::<T>::NotRegistered);
+
         let max_set_weights = MaximumSetWeightCallsPerEpoch::<T>::get(netuid);
         if max_set_weights != 0 {
-            let set_weight_uses = SetWeightCallsPerEpoch::<T>::mutate(netuid, &key, |value| {
-                *value = value.saturating_add(1);
-                *value
-            });
+            let set_weight_uses = SetWeightCallsPerEpoch::<T>::get(netuid, key).saturating_add(1);
 
             ensure!(
                 set_weight_uses <= max_set_weights,
@@ -51,19 +266,58 @@ impl<T: Config> Pallet<T> {
             );
         }
 
-        // --- 5. Get the module uid of associated key on network netuid.
-        let uid: u16 = Self::get_uid_for_key(netuid, &key);
+        Ok(())
+    }
+
+    fn finalize_weights(
+        netuid: u16,
+        key: &T::AccountId,
+        uids: Vec<u16>,
+        values: Vec<u16>,
+    ) -> dispatch::DispatchResult {
+        let zipped_weights = Self::check_weights(netuid, key, &uids, values)?;
+        let uid: u16 = Self::get_uid_for_key(netuid, key);
+
+        // --- 12. Set weights under netuid, uid double map entry.
+        Self::record_weights(netuid, uid, &zipped_weights);
+        Weights::<T>::insert(netuid, uid, zipped_weights);
 
-        // --- 6. Ensure the passed uids contain no duplicates.
-        ensure!(!Self::contains_duplicates(&uids), Error::<T>::DuplicateUids);
+        // --- 13. Set the activity for the weights on this network.
+        let current_block: u64 = Self::get_current_block_number();
+        Self::set_last_update_for_uid(netuid, uid, current_block);
 
-        // --- 7. Ensure that the passed uids are valid for the network.
+        // --- 14. Emit the tracking event.
+        Self::deposit_event(Event::WeightsSet(netuid, uid));
+
+        Ok(())
+    }
+
+    /// Validates the weights of the key and returns them normalized, as they would be stored.
+    fn check_weights(
+        netuid: u16,
+        key: &T::AccountId,
+        uids: &[u16],
+        values: Vec<u16>,
+    ) -> Result<Vec<(u16, u16)>, DispatchError> {
+        // --- 1. Check that the length of uid list and value list are equal for this network.
+        ensure!(
+            uids.len() == values.len(),
+            Error::<T>::WeightVecNotEqualSize
+        );
+
+        // --- 2. Get the module uid of associated key on network netuid.
+        let uid: u16 = Self::get_uid_for_key(netuid, key);
+
+        // --- 3. Ensure the passed uids contain no duplicates.
+        ensure!(!Self::contains_duplicates(uids), Error::<T>::DuplicateUids);
+
+        // --- 4. Ensure that the passed uids are valid for the network.
         ensure!(
             uids.iter().all(|&uid| Self::uid_exist_on_network(netuid, uid)),
             Error::<T>::InvalidUid
         );
 
-        // --- 8. Check the allowed length of uids.
+        // --- 5. Check the allowed length of uids.
         let min_allowed_length: usize = Self::get_min_allowed_weights(netuid) as usize;
         let max_allowed_length: usize =
             MaxAllowedWeights::<T>::get(netuid).min(N::<T>::get(netuid)) as usize;
@@ -72,44 +326,34 @@ impl<T: Config> Pallet<T> {
             Error::<T>::InvalidUidsLength
         );
 
-        // --- 9. Ensure the uid is not setting weights for itself.
+        // --- 6. Ensure the uid is not setting weights for itself.
         ensure!(!uids.contains(&uid), Error::<T>::NoSelfWeight);
 
-        // --- 10. Get the stake for the key.
-        let stake: u64 = Stake::<T>::get(netuid, &key);
+        // --- 7. Get the stake for the key.
+        let stake: u64 = Stake::<T>::get(netuid, key);
 
-        // --- 11. Check if the stake per weight is greater than the required minimum stake.
+        // --- 8. Check if the stake per weight is greater than the required minimum stake.
         let min_stake_per_weight: u64 = MinWeightStake::<T>::get();
-        let min_stake_for_weights: u64 = min_stake_per_weight * uids.len() as u64;
+        let min_stake_for_weights: u64 = min_stake_per_weight.saturating_mul(uids.len() as u64);
         ensure!(
             stake >= min_stake_for_weights,
             Error::<T>::NotEnoughStakePerWeight
         );
 
-        // --- 12. Ensure the key has enough stake to set weights.
+        // --- 9. Ensure the key has enough stake to set weights.
         ensure!(stake > 0, Error::<T>::NotEnoughStakeToSetWeights);
 
-        // --- 13. Normalize the weights.
+        // --- 10. Normalize the weights.
         let normalized_values = Self::normalize_weights(values);
 
-        // --- 14. Zip weights for sinking to storage map.
+        // --- 11. Zip weights for sinking to storage map.
         let zipped_weights: Vec<(u16, u16)> = uids
             .iter()
             .zip(normalized_values.iter())
             .map(|(&uid, &val)| (uid, val))
             .collect();
 
-        // --- 15. Set weights under netuid, uid double map entry.
-        Weights::<T>::insert(netuid, uid, zipped_weights);
-
-        // --- 16. Set the activity for the weights on this network.
-        let current_block: u64 = Self::get_current_block_number();
-        Self::set_last_update_for_uid(netuid, uid, current_block);
-
-        // --- 17. Emit the tracking event.
-        Self::deposit_event(Event::WeightsSet(netuid, uid));
-
-        Ok(())
+        Ok(zipped_weights)
     }
 
     // Returns true if the uid is set on the network.
@@ -137,16 +137,31 @@ impl<T: Config> Pallet<T> {
             Error::<T>::NotRegistered
         );
 
+        // --- 2.3 The stake of a module with a controller can only be moved by it.
+        ensure!(
+            !Self::is_controlled(netuid, &key),
+            Error::<T>::NotModuleController
+        );
+
         // --- 3. Check if the caller has enough stake in the old module
         ensure!(
             Self::has_enough_stake(netuid, &key, &module_key, amount),
             Error::<T>::NotEnoughStakeToWithdraw
         );
 
-        // --- 4. Remove stake from the source module and add it to the destination module
-        Self::do_remove_stake(origin.clone(), netuid, module_key.clone(), amount)?;
-        // don't allow zero stakes
-        Self::do_add_stake(origin.clone(), netuid, new_module_key, amount)?;
+        // --- 4. Move the stake from the source module to the destination module. The stake never
+        // leaves the subnet, so it does not have to go through the unbonding period.
+        Self::decrease_stake(netuid, &key, &module_key, amount);
+        Self::increase_stake(netuid, &key, &new_module_key, amount);
+
+        let current_block = Self::get_current_block_number();
+        Self::deposit_event(Event::StakeRemoved(
+            key.clone(),
+            module_key,
+            amount,
+            current_block,
+        ));
+        Self::deposit_event(Event::StakeAdded(key, new_module_key, amount));
 
         // --- 5. Done and ok
         Ok(())
@@ -234,12 +249,24 @@ impl<T: Config> Pallet<T> {
             Error::<T>::NotRegistered
         );
 
+        // --- 2.1 The stake of a module with a controller can only be withdrawn by it.
+        ensure!(
+            !Self::is_controlled(netuid, &key),
+            Error::<T>::NotModuleController
+        );
+
         // --- 3. We check that the caller has enough stake in the module.
         ensure!(
             Self::has_enough_stake(netuid, &key, &module_key, amount),
             Error::<T>::NotEnoughStakeToWithdraw
         );
 
+        // --- 3.1 Conviction votes keep part of the stake locked.
+        ensure!(
+            Self::can_remove_locked_stake(netuid, &key, amount),
+            Error::<T>::StakeLocked
+        );
+
         // --- 4. Make sure we can convert to balance
         let stake_to_be_added_as_currency = Self::u64_to_balance(amount);
         ensure!(
@@ -252,11 +279,12 @@ impl<T: Config> Pallet<T> {
         let balance_before_remove: u64 = Self::get_balance_u64(&key);
         let module_stake_before_remove: u64 = Stake::<T>::get(netuid, &module_key);
 
-        // --- 6. We remove the balance from the key.
+        // --- 6. We remove the stake from the module.
         Self::decrease_stake(netuid, &key, &module_key, amount);
 
-        // --- 7. We add the balancer to the key. If the above fails we will not credit this key.
-        Self::add_balance_to_account(&key, Self::u64_to_balance(amount).unwrap());
+        // --- 7. We credit the key, right away or once the unbonding period is over.
+        let unbonding_period = Self::get_unbonding_period(netuid);
+        let unlock_block = Self::unbond_stake(netuid, &key, amount, false)?;
 
         // --- 8. Check after values
         let stake_after_remove: u64 = Self::get_stake_to_module(netuid, &key, &module_key.clone());
@@ -269,8 +297,13 @@ impl<T: Config> Pallet<T> {
             stake_after_remove == stake_before_remove.saturating_sub(amount),
             Error::<T>::StakeNotRemoved
         );
+        let expected_balance = if unbonding_period == 0 {
+            balance_before_remove.saturating_add(amount)
+        } else {
+            balance_before_remove
+        };
         ensure!(
-            balance_after_remove == balance_before_remove.saturating_add(amount),
+            balance_after_remove == expected_balance,
             Error::<T>::BalanceNotAdded
         );
         ensure!(
@@ -278,12 +311,163 @@ impl<T: Config> Pallet<T> {
             Error::<T>::StakeNotRemoved
         );
 
-        Self::deposit_event(Event::StakeRemoved(key, module_key, amount));
+        Self::deposit_event(Event::StakeRemoved(key, module_key, amount, unlock_block));
 
         // --- 10. Done and ok.
         Ok(())
     }
 
+    /// Removes stake the module key holds on itself on behalf of its controller. The removed
+    /// amount is unbonded to the controller.
+    pub fn do_controller_remove_stake(
+        origin: T::RuntimeOrigin,
+        netuid: u16,
+        module_key: T::AccountId,
+        amount: u64,
+    ) -> dispatch::DispatchResult {
+        // --- 1. We check the transaction is signed by the module's controller.
+        let controller = ensure_signed(origin)?;
+        ensure!(
+            Self::is_registered(netuid, &module_key),
+            Error::<T>::NotRegistered
+        );
+        Self::ensure_module_controller(netuid, &module_key, &controller)?;
+
+        // --- 2. We check that the module has enough stake on itself.
+        ensure!(
+            Self::has_enough_stake(netuid, &module_key, &module_key, amount),
+            Error::<T>::NotEnoughStakeToWithdraw
+        );
+        ensure!(
+            Self::can_remove_locked_stake(netuid, &module_key, amount),
+            Error::<T>::StakeLocked
+        );
+        ensure!(
+            Self::u64_to_balance(amount).is_some(),
+            Error::<T>::CouldNotConvertToBalance
+        );
+
+        // --- 3. We remove the stake and credit the controller.
+        Self::decrease_stake(netuid, &module_key, &module_key, amount);
+        let unlock_block = Self::unbond_stake(netuid, &controller, amount, false)?;
+
+        Self::deposit_event(Event::StakeRemoved(
+            module_key.clone(),
+            module_key,
+            amount,
+            unlock_block,
+        ));
+
+        Ok(())
+    }
+
+    /// Releases every unlocking chunk of the caller that reached its unlock block.
+    pub fn do_withdraw_unbonded(origin: T::RuntimeOrigin) -> dispatch::DispatchResult {
+        // --- 1. We check the transaction is signed by the caller.
+        let key = ensure_signed(origin)?;
+
+        // --- 2. Collect the matured chunks on every subnet.
+        let current_block = Self::get_current_block_number();
+        let mut withdrawn: u64 = 0;
+        for netuid in Self::netuids() {
+            Unlocking::<T>::mutate_exists(netuid, &key, |maybe_chunks| {
+                let Some(chunks) = maybe_chunks else {
+                    return;
+                };
+
+                chunks.retain(|chunk| {
+                    if chunk.unlock_block <= current_block {
+                        withdrawn = withdrawn.saturating_add(chunk.amount);
+                        false
+                    } else {
+                        true
+                    }
+                });
+
+                if chunks.is_empty() {
+                    *maybe_chunks = None;
+                }
+            });
+        }
+
+        ensure!(withdrawn > 0, Error::<T>::NothingToWithdraw);
+
+        // --- 3. Credit the released amount to the key.
+        let balance =
+            Self::u64_to_balance(withdrawn).ok_or(Error::<T>::CouldNotConvertToBalance)?;
+        Self::add_balance_to_account(&key, balance);
+
+        Self::deposit_event(Event::UnbondedWithdrawn(key, withdrawn));
+
+        // --- 4. Done and ok.
+        Ok(())
+    }
+
+    /// Returns the number of blocks removed stake stays locked on the subnet. The global
+    /// unbonding period acts as a floor for every subnet.
+    pub fn get_unbonding_period(netuid: u16) -> u64 {
+        UnbondingPeriod::<T>::get(netuid).max(UnbondingPeriodGlobal::<T>::get())
+    }
+
+    /// Returns the pending unlocking chunks of the key on the subnet.
+    pub fn get_unlocking(netuid: u16, key: &T::AccountId) -> Vec<UnlockChunk> {
+        Unlocking::<T>::get(netuid, key)
+    }
+
+    /// Credits stake that left a module back to the key. Without an unbonding period the
+    /// balance is added right away, otherwise the amount is queued until the unlock block,
+    /// which is returned.
+    ///
+    /// When `force` is set and the queue is full, the amount is merged into the latest chunk
+    /// instead of failing. This is used when the chain itself removes the stake.
+    pub fn unbond_stake(
+        netuid: u16,
+        key: &T::AccountId,
+        amount: u64,
+        force: bool,
+    ) -> Result<u64, DispatchError> {
+        let current_block = Self::get_current_block_number();
+        let unbonding_period = Self::get_unbonding_period(netuid);
+
+        if unbonding_period == 0 {
+            let balance =
+                Self::u64_to_balance(amount).ok_or(Error::<T>::CouldNotConvertToBalance)?;
+            Self::add_balance_to_account(key, balance);
+            return Ok(current_block);
+        }
+
+        let unlock_block = current_block.saturating_add(unbonding_period);
+        Unlocking::<T>::try_mutate(netuid, key, |chunks| {
+            if let Some(chunk) = chunks.iter_mut().find(|c| c.unlock_block == unlock_block) {
+                chunk.amount = chunk.amount.saturating_add(amount);
+            } else if chunks.len() < MAX_UNLOCKING_CHUNKS {
+                chunks.push(UnlockChunk {
+                    amount,
+                    unlock_block,
+                });
+            } else {
+                ensure!(force, Error::<T>::TooManyUnlockingChunks);
+                // chunks are pushed in order, so the last one unlocks the latest
+                if let Some(chunk) = chunks.last_mut() {
+                    chunk.amount = chunk.amount.saturating_add(amount);
+                    chunk.unlock_block = chunk.unlock_block.max(unlock_block);
+                }
+            }
+
+            Ok(unlock_block)
+        })
+    }
+
+    /// Releases all pending unlocking chunks on the subnet, used when the subnet is removed.
+    pub fn release_unlocking(netuid: u16) {
+        for (key, chunks) in Unlocking::<T>::drain_prefix(netuid) {
+            let amount = chunks.iter().fold(0u64, |acc, chunk| acc.saturating_add(chunk.amount));
+            if let Some(balance) = Self::u64_to_balance(amount) {
+                Self::add_balance_to_account(&key, balance);
+            }
+        }
+    }
+
     /// Returns the total amount of stake in the staking table.
     pub fn get_total_subnet_stake(netuid: u16) -> u64 {
         TotalStake::<T>::get(netuid)
@@ -316,6 +500,66 @@ impl<T: Config> Pallet<T> {
         amount > 0 && Self::get_stake_to_module(netuid, key, module_key) >= amount
     }
 
+    /// Sets whether the caller's dividends from `module_key`, or from every module when `None`,
+    /// are restaked. Passing `None` as the setting clears it, falling back to the global setting
+    /// and then to compounding.
+    pub fn do_set_auto_compound(
+        origin: T::RuntimeOrigin,
+        module_key: Option<T::AccountId>,
+        auto_compound: Option<bool>,
+    ) -> dispatch::DispatchResult {
+        let key = ensure_signed(origin)?;
+
+        AutoCompound::<T>::set(&key, &module_key, auto_compound);
+
+        Self::deposit_event(Event::AutoCompoundSet(key, module_key, auto_compound));
+
+        Ok(())
+    }
+
+    /// Whether the delegator's dividends from the module are restaked. Defaults to `true`.
+    pub fn is_auto_compounding(delegator: &T::AccountId, module_key: &T::AccountId) -> bool {
+        AutoCompound::<T>::get(delegator, Some(module_key.clone()))
+            .or_else(|| AutoCompound::<T>::get(delegator, None::<T::AccountId>))
+            .unwrap_or(true)
+    }
+
+    /// Pays the delegator's dividends from the module, either restaking them or crediting them
+    /// to the delegator's free balance depending on its auto-compound setting.
+    pub fn distribute_delegator_dividends(
+        netuid: u16,
+        delegator: &T::AccountId,
+        module_key: &T::AccountId,
+        amount: u64,
+    ) {
+        if amount == 0 {
+            return;
+        }
+
+        if Self::is_auto_compounding(delegator, module_key) {
+            Self::increase_stake(netuid, delegator, module_key, amount);
+        } else {
+            Self::add_balance_to_account(
+                delegator,
+                Self::u64_to_balance(amount).unwrap_or_default(),
+            );
+        }
+    }
+
+    /// Whether removing `amount` from the key's stake on the subnet keeps it above the stake
+    /// locked by its conviction votes, both on the subnet and on global proposals.
+    pub fn can_remove_locked_stake(netuid: u16, key: &T::AccountId, amount: u64) -> bool {
+        let subnet_locked = T::get_locked_stake(key, Some(netuid));
+        let global_locked = T::get_locked_stake(key, None);
+        if subnet_locked == 0 && global_locked == 0 {
+            return true;
+        }
+
+        let subnet_stake = Self::get_account_stake(key, Some(netuid)).saturating_sub(amount);
+        let total_stake = Self::get_account_stake(key, None).saturating_sub(amount);
+        subnet_stake >= subnet_locked && total_stake >= global_locked
+    }
+
     pub fn get_stake_to_module(netuid: u16, key: &T::AccountId, module_key: &T::AccountId) -> u64 {
         Self::get_stake_to_vector(netuid, key)
             .into_iter()
@@ -417,20 +661,60 @@ impl<T: Config> Pallet<T> {
     }
 
     // Decreases the stake by the amount while decreasing other counters.
+    // The delegated stake goes through the unbonding period of the subnet.
     pub fn remove_stake_from_storage(netuid: u16, staked: &T::AccountId) {
         let stake_from_vector = Self::get_stake_from_vector(netuid, staked);
         for (staker, delegate_stake_amount) in stake_from_vector.iter() {
             Self::decrease_stake(netuid, staker, staked, *delegate_stake_amount);
-            Self::add_balance_to_account(
-                staker,
-                Self::u64_to_balance(*delegate_stake_amount).unwrap(),
-            );
+            // the module's own stake goes back to its controller
+            let beneficiary = if staker == staked {
+                Self::module_controller(netuid, staked)
+            } else {
+                staker.clone()
+            };
+            // forced unbonding never fails
+            let _ = Self::unbond_stake(netuid, &beneficiary, *delegate_stake_amount, true);
         }
 
         StakeFrom::<T>::remove(netuid, staked);
         Stake::<T>::remove(netuid, staked);
     }
 
+    /// Slashes `amount` from the stake under the module, taking from every staker in proportion
+    /// to their share. The slashed tokens are sent to the DAO treasury. Returns the amount that
+    /// was actually slashed.
+    pub fn slash_module_stake(netuid: u16, module_key: &T::AccountId, amount: u64) -> u64 {
+        let stake_from = Self::get_stake_from_vector(netuid, module_key);
+        let total_stake = stake_from.values().fold(0u64, |acc, stake| acc.saturating_add(*stake));
+        if total_stake == 0 || amount == 0 {
+            return 0;
+        }
+
+        let amount = amount.min(total_stake) as u128;
+        let mut slashed: u64 = 0;
+        for (staker, stake) in stake_from {
+            let cut = (stake as u128)
+                .saturating_mul(amount)
+                .checked_div(total_stake as u128)
+                .unwrap_or_default() as u64;
+            if cut == 0 {
+                continue;
+            }
+
+            Self::decrease_stake(netuid, &staker, module_key, cut);
+            slashed = slashed.saturating_add(cut);
+        }
+
+        if slashed > 0 {
+            Self::add_balance_to_account(
+                &T::get_dao_treasury_address(),
+                Self::u64_to_balance(slashed).unwrap_or_default(),
+            );
+        }
+
+        slashed
+    }
+
     pub fn add_balance_to_account(key: &T::AccountId, amount: BalanceOf<T>) {
         let _ = T::Currency::deposit_creating(key, amount); // Infallibe
     }
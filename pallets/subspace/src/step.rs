@@ -13,6 +13,9 @@ impl<T: Config> Pallet<T> {
         log::debug!("stepping block {block_number:?}");
 
         RegistrationsPerBlock::<T>::mutate(|val: &mut u16| *val = 0);
+        Self::reset_rate_limits(block_number);
+
+        Self::close_subnet_auction(block_number);
 
         let total_stake = Self::total_stake() as u128;
         let subnet_stake_threshold = SubnetStakeThreshold::<T>::get();
@@ -66,30 +69,61 @@ impl<T: Config> Pallet<T> {
                 }
             };
 
-            if netuid == 0 {
-                Self::linear_epoch(netuid, emission_to_drain)
-            } else if has_enough_stake_for_yuma() {
-                let res = with_storage_layer(|| {
-                    let Err(err) = yuma::YumaCalc::<T>::new(netuid, emission_to_drain).run() else {
-                        return Ok(());
-                    };
-
-                    log::error!(
-                        "\
+            match Self::get_subnet_consensus(netuid) {
+                SubnetConsensus::Linear => {
+                    Self::linear_epoch(netuid, emission_to_drain);
+                    Self::record_validator_epochs(netuid);
+                }
+                SubnetConsensus::Root => Self::root_epoch(netuid, emission_to_drain),
+                SubnetConsensus::Yuma if has_enough_stake_for_yuma() => {
+                    let res = with_storage_layer(|| {
+                        let output = match yuma::YumaCalc::<T>::new(netuid, emission_to_drain).run()
+                        {
+                            Ok(output) => output,
+                            Err(err) => {
+                                log::error!(
+                                    "\
 failed to run yuma consensus algorithm: {err:?}, skipping this block. \
 {emission_to_drain} tokens will be emitted on the next epoch.\
 "
-                    );
-                    Err("yuma failed")
-                });
-                if res.is_err() {
-                    continue;
+                                );
+                                return Err("yuma failed");
+                            }
+                        };
+
+                        let epoch = Self::get_epoch_index(netuid, block_number);
+                        Self::apply_slashes(netuid, epoch, output.slashes);
+
+                        let mut rewards: BTreeMap<T::AccountId, u64> = BTreeMap::new();
+                        for (account_key, amount) in output.emissions.into_values().flatten() {
+                            let reward = rewards.entry(account_key.0).or_default();
+                            *reward = reward.saturating_add(amount);
+                        }
+                        Self::record_rewards(netuid, rewards);
+                        Self::record_validator_epochs(netuid);
+
+                        Ok(())
+                    });
+                    if res.is_err() {
+                        continue;
+                    }
                 }
+                SubnetConsensus::Yuma => {}
             }
             PendingEmission::<T>::insert(netuid, 0);
         }
     }
 
+    /// Relays the whole emission of the subnet to its founder, without running any consensus.
+    pub fn root_epoch(netuid: u16, token_emission: u64) {
+        let founder_key = Founder::<T>::get(netuid);
+        Self::add_balance_to_account(
+            &founder_key,
+            Self::u64_to_balance(token_emission).unwrap_or_default(),
+        );
+        Self::record_rewards(netuid, [(founder_key, token_emission)].into());
+    }
+
     /// This function acts as the main function of the entire blockchain reward distribution.
     /// It calculates the dividends, the incentive, the weights, the bonds,
     /// the trust and the emission for the epoch.
@@ -248,7 +282,7 @@ failed to run yuma consensus algorithm: {err:?}, skipping this block. \
         netuid: u16,
         founder_key: &T::AccountId,
         uid_key_tuples: &[(u16, T::AccountId)],
-    ) -> Vec<u64> {
+    ) -> (Vec<u64>, BTreeMap<T::AccountId, u64>) {
         let n = incentive_emission_float.len();
         let mut incentive_emission: Vec<u64> =
             incentive_emission_float.iter().map(|e| e.to_num::<u64>()).collect();
@@ -264,6 +298,7 @@ failed to run yuma consensus algorithm: {err:?}, skipping this block. \
 
         let mut emission: Vec<u64> = vec![0; n];
         let mut emitted = 0u64;
+        let mut rewards: BTreeMap<T::AccountId, u64> = BTreeMap::new();
 
         for (module_uid, module_key) in uid_key_tuples.iter() {
             let owner_emission_incentive: u64 =
@@ -293,7 +328,14 @@ failed to run yuma consensus algorithm: {err:?}, skipping this block. \
                             .unwrap_or(0);
                     let to_module: u64 = delegation_fee.mul_floor(dividends_from_delegate);
                     let to_delegate: u64 = dividends_from_delegate.saturating_sub(to_module);
-                    Self::increase_stake(netuid, delegate_key, module_key, to_delegate);
+                    Self::distribute_delegator_dividends(
+                        netuid,
+                        delegate_key,
+                        module_key,
+                        to_delegate,
+                    );
+                    let reward = rewards.entry(delegate_key.clone()).or_default();
+                    *reward = reward.saturating_add(to_delegate);
                     emitted = emitted.saturating_add(to_delegate);
                     owner_dividends_emission = owner_dividends_emission.saturating_sub(to_delegate);
                 }
@@ -303,6 +345,8 @@ failed to run yuma consensus algorithm: {err:?}, skipping this block. \
                 owner_emission_incentive.saturating_add(owner_dividends_emission);
             if owner_emission > 0 {
                 Self::increase_stake(netuid, module_key, module_key, owner_emission);
+                let reward = rewards.entry(module_key.clone()).or_default();
+                *reward = reward.saturating_add(owner_emission);
                 emitted = emitted.saturating_add(owner_emission);
             }
         }
@@ -357,7 +401,7 @@ failed to run yuma consensus algorithm: {err:?}, skipping this block. \
             );
         }
 
-        emission
+        (emission, rewards)
     }
 
     fn process_emission(
@@ -372,7 +416,7 @@ failed to run yuma consensus algorithm: {err:?}, skipping this block. \
         let (incentive_emission_float, dividends_emission_float) =
             Self::calculate_emission_ratios(incentive, dividends, token_emission, netuid);
 
-        let emission = Self::calculate_emissions(
+        let (emission, rewards) = Self::calculate_emissions(
             &incentive_emission_float,
             &dividends_emission_float,
             founder_emission,
@@ -382,6 +426,7 @@ failed to run yuma consensus algorithm: {err:?}, skipping this block. \
         );
 
         Emission::<T>::insert(netuid, emission);
+        Self::record_rewards(netuid, rewards);
     }
 
     // TODO: disable this later, this function has proven to be correct
@@ -647,6 +692,16 @@ failed to run yuma consensus algorithm: {err:?}, skipping this block. \
             .unwrap_or(1000)
     }
 
+    /// Applies the slashes computed by yuma consensus. The slashed stake goes to the DAO treasury.
+    fn apply_slashes(netuid: u16, epoch: u64, slashes: Vec<yuma::Slash<T>>) {
+        for yuma::Slash { module_key, amount } in slashes {
+            let slashed = Self::slash_module_stake(netuid, &module_key.0, amount);
+            if slashed > 0 {
+                Self::deposit_event(Event::Slashed(netuid, module_key.0, slashed, epoch));
+            }
+        }
+    }
+
     pub fn get_ownership_ratios(
         netuid: u16,
         module_key: &T::AccountId,
@@ -1,18 +1,34 @@
 use core::marker::PhantomData;
 
+use sp_arithmetic::per_things::Percent;
 use sp_std::{borrow::Cow, collections::btree_map::BTreeMap};
 use substrate_fixed::types::{I32F32, I64F64, I96F32};
 
 use crate::{
-    math::*, vec, Active, Bonds, BondsMovingAverage, Config, Consensus, Dividends, Emission,
-    Founder, Incentive, Kappa, Keys, LastUpdate, MaxAllowedValidators, MaxWeightAge, Pallet,
-    PruningScores, Rank, Stake, Trust, Uids, ValidatorPermits, ValidatorTrust, Weights, N,
+    math::*, vec, Active, AlphaValues, Bonds, BondsMovingAverage, Config, Consensus, Dividends,
+    Emission, Founder, Incentive, Kappa, Keys, LastUpdate, LiquidAlphaEnabled,
+    MaxAllowedValidators, MaxWeightAge, Pallet, PruningScores, Rank, SlashRatio, SlashThreshold,
+    Stake, Trust, Uids, ValidatorPermits, ValidatorTrust, Weights, N,
 };
-use frame_support::ensure;
+use frame_support::{ensure, DebugNoBound, EqNoBound, PartialEqNoBound};
 use sp_std::vec::Vec;
 
 pub type EmissionMap<T> = BTreeMap<ModuleKey<T>, BTreeMap<AccountKey<T>, u64>>;
 
+/// Stake that should be taken from a module whose weights are too far from consensus.
+#[derive(DebugNoBound, PartialEqNoBound, EqNoBound)]
+pub struct Slash<T: Config> {
+    pub module_key: ModuleKey<T>,
+    pub amount: u64,
+}
+
+pub struct YumaOutput<T: Config> {
+    /// Emissions distributed per module key.
+    pub emissions: EmissionMap<T>,
+    /// Slashes that still have to be applied to the stake storage.
+    pub slashes: Vec<Slash<T>>,
+}
+
 pub struct YumaCalc<T: Config> {
     /// The amount of modules on the subnet
     module_count: u16,
@@ -34,6 +50,12 @@ pub struct YumaCalc<T: Config> {
     validator_forbids: Vec<bool>,
     max_allowed_validators: Option<u16>,
 
+    slash_ratio: Percent,
+    slash_threshold: Percent,
+
+    /// Low and high bonds alpha, when it depends on the agreement of each weight with consensus.
+    liquid_alpha: Option<(I32F32, I32F32)>,
+
     _pd: PhantomData<T>,
 }
 
@@ -43,6 +65,13 @@ impl<T: Config> YumaCalc<T> {
         let validator_forbids = validator_permits.iter().map(|&b| !b).collect();
 
         let founder_key = Founder::<T>::get(netuid);
+        let liquid_alpha = LiquidAlphaEnabled::<T>::get(netuid).then(|| {
+            let (alpha_low, alpha_high) = AlphaValues::<T>::get(netuid);
+            (
+                u16_proportion_to_fixed(alpha_low),
+                u16_proportion_to_fixed(alpha_high),
+            )
+        });
         let (to_be_emitted, founder_emission) =
             Pallet::<T>::calculate_founder_emission(netuid, to_be_emitted);
 
@@ -64,13 +93,18 @@ impl<T: Config> YumaCalc<T> {
             validator_permits,
             max_allowed_validators: MaxAllowedValidators::<T>::get(netuid),
 
+            slash_ratio: SlashRatio::<T>::get(netuid),
+            slash_threshold: SlashThreshold::<T>::get(netuid),
+
+            liquid_alpha,
+
             _pd: Default::default(),
         }
     }
 
     /// Runs the YUMA consensus calculation on the network and distributes the emissions. Returns a
-    /// map of emissions distributed per module key.
-    pub fn run(self) -> Result<EmissionMap<T>, YumaError> {
+    /// map of emissions distributed per module key and the slashes for validators out of consensus.
+    pub fn run(self) -> Result<YumaOutput<T>, YumaError> {
         log::debug!(
             "running yuma for netuid {}, will emit {} modules and {} to founder",
             self.netuid,
@@ -106,12 +140,22 @@ impl<T: Config> YumaCalc<T> {
         let active_stake = self.compute_active_stake(&inactive, &stake);
         log::trace!("final active stake: {active_stake:?}");
 
+        // Only validators that actually voted can be out of consensus.
+        let has_weights: Vec<bool> = weights
+            .as_ref()
+            .iter()
+            .map(|row| row.iter().any(|(_, weight)| *weight > I32F32::from_num(0)))
+            .collect();
+
         let ConsensusAndTrust {
             consensus,
             validator_trust,
             preranks,
         } = self.compute_consensus_and_trust(&mut weights, &active_stake);
 
+        let slashes = self.compute_slashes(&active, &has_weights, &new_permits, &validator_trust);
+        log::trace!("final slashes: {slashes:?}");
+
         let IncentivesAndTrust {
             incentives,
             ranks,
@@ -122,7 +166,7 @@ impl<T: Config> YumaCalc<T> {
             ema_bonds,
             dividends,
         } = self
-            .compute_bonds_and_dividends(&weights, &active_stake, &incentives)
+            .compute_bonds_and_dividends(&weights, &active_stake, &incentives, &consensus)
             .ok_or(YumaError::Other("bonds storage is broken"))?;
 
         let Emissions {
@@ -200,13 +244,13 @@ impl<T: Config> YumaCalc<T> {
             ));
         }
 
-        let distribute_emissions = self.distribute_emissions(result);
+        let emissions = self.distribute_emissions(result)?;
         log::debug!(
-            "finished yuma for {} with distributed: {distribute_emissions:?}",
+            "finished yuma for {} with distributed: {emissions:?}",
             self.netuid
         );
 
-        distribute_emissions
+        Ok(YumaOutput { emissions, slashes })
     }
 
     fn distribute_emissions(
@@ -226,7 +270,16 @@ impl<T: Config> YumaCalc<T> {
 
         for (module_key, server_emission, mut validator_emission) in result {
             let mut increase_stake = |account_key: &AccountKey<T>, amount: u64| {
-                Pallet::<T>::increase_stake(self.netuid, &account_key.0, &module_key.0, amount);
+                if account_key.0 == module_key.0 {
+                    Pallet::<T>::increase_stake(self.netuid, &account_key.0, &module_key.0, amount);
+                } else {
+                    Pallet::<T>::distribute_delegator_dividends(
+                        self.netuid,
+                        &account_key.0,
+                        &module_key.0,
+                        amount,
+                    );
+                }
 
                 let stake = emissions
                     .entry(module_key.clone())
@@ -388,6 +441,43 @@ impl<T: Config> YumaCalc<T> {
         }
     }
 
+    fn compute_slashes(
+        &self,
+        active: &[bool],
+        has_weights: &[bool],
+        validator_permits: &[bool],
+        validator_trust: &ValidatorTrustVal,
+    ) -> Vec<Slash<T>> {
+        if self.slash_ratio.deconstruct() == 0 || self.slash_threshold.deconstruct() == 0 {
+            return vec![];
+        }
+
+        let threshold = I32F32::from_num(self.slash_threshold.deconstruct())
+            .checked_div(I32F32::from_num(100))
+            .unwrap_or(I32F32::from_num(0));
+
+        validator_trust
+            .as_ref()
+            .iter()
+            .enumerate()
+            .filter(|(uid, trust)| {
+                let is_set = |flags: &[bool]| flags.get(*uid).copied().unwrap_or(false);
+                is_set(active)
+                    && is_set(has_weights)
+                    && is_set(validator_permits)
+                    && **trust < threshold
+            })
+            .filter_map(|(uid, _)| {
+                let module_key = Keys::<T>::try_get(self.netuid, uid as u16).ok()?;
+                let amount = self.slash_ratio.mul_floor(Stake::<T>::get(self.netuid, &module_key));
+                (amount > 0).then_some(Slash {
+                    module_key: ModuleKey(module_key),
+                    amount,
+                })
+            })
+            .collect()
+    }
+
     fn compute_incentive_and_trust(
         &self,
         weights: &WeightsVal,
@@ -420,6 +510,7 @@ impl<T: Config> YumaCalc<T> {
         weights: &WeightsVal,
         active_stake: &ActiveStake,
         incentives: &IncentivesVal,
+        consensus: &ConsensusVal,
     ) -> Option<BondsAndDividends> {
         // Access network bonds.
         let mut bonds = Pallet::<T>::get_bonds_sparse(self.netuid)?;
@@ -450,13 +541,25 @@ impl<T: Config> YumaCalc<T> {
         inplace_col_normalize_sparse(&mut bonds_delta, self.module_count); // sum_i b_ij = 1
         log::trace!("  normalized bonds delta: {bonds_delta:?}");
 
-        // Compute bonds moving average.
-        let bonds_moving_average = I64F64::from_num(BondsMovingAverage::<T>::get(self.netuid))
-            .checked_div(I64F64::from_num(1_000_000))
-            .unwrap_or(I64F64::from_num(0));
-        log::trace!("  bonds moving average: {bonds_moving_average}");
-        let alpha = I32F32::from_num(1).saturating_sub(I32F32::from_num(bonds_moving_average));
-        let mut ema_bonds = mat_ema_sparse(&bonds_delta, &bonds, alpha);
+        let mut ema_bonds = match self.liquid_alpha {
+            Some((alpha_low, alpha_high)) => {
+                // Compute bonds moving average with an alpha per bond.
+                let alpha = Self::compute_liquid_alpha(weights, consensus, alpha_low, alpha_high);
+                log::trace!("  liquid alpha: {alpha:?}");
+                mat_ema_alpha_sparse(&bonds_delta, &bonds, &alpha, alpha_low)
+            }
+            None => {
+                // Compute bonds moving average.
+                let bonds_moving_average =
+                    I64F64::from_num(BondsMovingAverage::<T>::get(self.netuid))
+                        .checked_div(I64F64::from_num(1_000_000))
+                        .unwrap_or(I64F64::from_num(0));
+                log::trace!("  bonds moving average: {bonds_moving_average}");
+                let alpha =
+                    I32F32::from_num(1).saturating_sub(I32F32::from_num(bonds_moving_average));
+                mat_ema_sparse(&bonds_delta, &bonds, alpha)
+            }
+        };
         log::trace!("  original ema bonds: {ema_bonds:?}");
 
         // Normalize EMA bonds.
@@ -481,6 +584,41 @@ impl<T: Config> YumaCalc<T> {
         })
     }
 
+    /// Interpolates the bonds alpha of each weight between `alpha_low` and `alpha_high` by how
+    /// much of the consensus weight of the module it reaches, so validators agreeing with
+    /// consensus build their bonds faster. Weights are clipped at consensus at this point.
+    fn compute_liquid_alpha(
+        weights: &WeightsVal,
+        consensus: &ConsensusVal,
+        alpha_low: I32F32,
+        alpha_high: I32F32,
+    ) -> Vec<Vec<(u16, I32F32)>> {
+        let zero = I32F32::from_num(0);
+        let one = I32F32::from_num(1);
+        let alpha_range = alpha_high.saturating_sub(alpha_low);
+
+        weights
+            .as_ref()
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|(j, weight)| {
+                        let agreement = match consensus.as_ref().get(*j as usize) {
+                            Some(consensus) if *consensus > zero => {
+                                weight.checked_div(*consensus).unwrap_or(one).min(one)
+                            }
+                            _ => zero,
+                        };
+                        (
+                            *j,
+                            alpha_low.saturating_add(alpha_range.saturating_mul(agreement)),
+                        )
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
     fn compute_emissions<'a>(
         &self,
         stake: &'a StakeVal,
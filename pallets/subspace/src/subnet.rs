@@ -46,6 +46,8 @@ impl<T: Config> SubnetChangeset<T> {
         TrustRatio::<T>::insert(netuid, self.params.trust_ratio);
         IncentiveRatio::<T>::insert(netuid, self.params.incentive_ratio);
         BondsMovingAverage::<T>::insert(netuid, self.params.bonds_ma);
+        LiquidAlphaEnabled::<T>::insert(netuid, self.params.liquid_alpha);
+        AlphaValues::<T>::insert(netuid, (self.params.alpha_low, self.params.alpha_high));
         TargetRegistrationsInterval::<T>::insert(netuid, self.params.target_registrations_interval);
         TargetRegistrationsPerInterval::<T>::insert(
             netuid,
@@ -57,6 +59,27 @@ impl<T: Config> SubnetChangeset<T> {
         );
 
         AdjustmentAlpha::<T>::insert(netuid, self.params.adjustment_alpha);
+        UnbondingPeriod::<T>::insert(netuid, self.params.unbonding_period);
+        SlashRatio::<T>::insert(netuid, self.params.slash_ratio);
+        SlashThreshold::<T>::insert(netuid, self.params.slash_threshold);
+
+        // Plaintext weights could have been copied, so Yuma must not consume them once
+        // commit-reveal is turned on.
+        if self.params.commit_reveal_weights && !CommitRevealWeights::<T>::get(netuid) {
+            let _ = Weights::<T>::clear_prefix(netuid, u32::MAX, None);
+        }
+        CommitRevealWeights::<T>::insert(netuid, self.params.commit_reveal_weights);
+        WeightRevealPeriod::<T>::insert(netuid, self.params.weight_reveal_period);
+        SubnetConsensusType::<T>::insert(netuid, self.params.consensus);
+        if !self.params.weight_history {
+            Pallet::<T>::clear_weight_history(netuid);
+        }
+        WeightHistoryEnabled::<T>::insert(netuid, self.params.weight_history);
+        if !self.params.reward_history {
+            Pallet::<T>::clear_reward_history(netuid);
+        }
+        RewardHistoryEnabled::<T>::insert(netuid, self.params.reward_history);
+
         if self.params.maximum_set_weight_calls_per_epoch == 0 {
             MaximumSetWeightCallsPerEpoch::<T>::remove(netuid);
         } else {
@@ -147,6 +170,27 @@ impl<T: Config> SubnetChangeset<T> {
             Error::<T>::InvalidAdjustmentAlpha
         );
 
+        ensure!(
+            params.unbonding_period <= MAX_UNBONDING_PERIOD,
+            Error::<T>::InvalidUnbondingPeriod
+        );
+
+        ensure!(
+            params.weight_reveal_period > 0,
+            Error::<T>::InvalidWeightRevealPeriod
+        );
+
+        ensure!(
+            params.alpha_low <= params.alpha_high,
+            Error::<T>::InvalidAlphaValues
+        );
+
+        // the global subnet funds the treasury through the linear epoch
+        ensure!(
+            netuid != Some(0) || params.consensus == SubnetConsensus::Linear,
+            Error::<T>::InvalidSubnetConsensus
+        );
+
         match Pallet::<T>::get_netuid_for_name(&params.name) {
             Some(id) if netuid.is_some_and(|netuid| netuid == id) => { /* subnet kept same name */ }
             Some(_) => return Err(Error::<T>::SubnetNameAlreadyExists.into()),
@@ -182,10 +226,21 @@ impl<T: Config> Pallet<T> {
             incentive_ratio: IncentiveRatio::<T>::get(netuid),
             maximum_set_weight_calls_per_epoch: MaximumSetWeightCallsPerEpoch::<T>::get(netuid),
             bonds_ma: BondsMovingAverage::<T>::get(netuid),
+            liquid_alpha: LiquidAlphaEnabled::<T>::get(netuid),
+            alpha_low: AlphaValues::<T>::get(netuid).0,
+            alpha_high: AlphaValues::<T>::get(netuid).1,
             target_registrations_interval: TargetRegistrationsInterval::<T>::get(netuid),
             target_registrations_per_interval: TargetRegistrationsPerInterval::<T>::get(netuid),
             max_registrations_per_interval: MaxRegistrationsPerInterval::<T>::get(netuid),
             adjustment_alpha: AdjustmentAlpha::<T>::get(netuid),
+            unbonding_period: UnbondingPeriod::<T>::get(netuid),
+            slash_ratio: SlashRatio::<T>::get(netuid),
+            slash_threshold: SlashThreshold::<T>::get(netuid),
+            commit_reveal_weights: CommitRevealWeights::<T>::get(netuid),
+            weight_reveal_period: WeightRevealPeriod::<T>::get(netuid),
+            consensus: Self::get_subnet_consensus(netuid),
+            weight_history: WeightHistoryEnabled::<T>::get(netuid),
+            reward_history: RewardHistoryEnabled::<T>::get(netuid),
             governance_config: T::get_subnet_governance_configuration(netuid),
         }
     }
@@ -203,11 +258,17 @@ impl<T: Config> Pallet<T> {
             None => TotalSubnets::<T>::get(),
         });
 
+        let mut changeset = changeset;
+        if netuid == 0 {
+            changeset.params.consensus = SubnetConsensus::Linear;
+        }
+
         let name = changeset.params.name.clone();
         changeset.apply(netuid)?;
         TotalSubnets::<T>::mutate(|n| *n = n.saturating_add(1));
         N::<T>::insert(netuid, 0);
         SubnetEmission::<T>::insert(netuid, 0);
+        SubnetRegistrationBlock::<T>::insert(netuid, Self::get_current_block_number());
 
         // Insert the minimum burn to the netuid,
         // to prevent free registrations the first target registration interval.
@@ -245,11 +306,13 @@ impl<T: Config> Pallet<T> {
         let _ = Uids::<T>::clear_prefix(netuid, u32::MAX, None);
         let _ = Keys::<T>::clear_prefix(netuid, u32::MAX, None);
         let _ = DelegationFee::<T>::clear_prefix(netuid, u32::MAX, None);
+        let _ = Controller::<T>::clear_prefix(netuid, u32::MAX, None);
 
         // --- 2. Remove consnesus vectors
         // ===============================
 
         let _ = Weights::<T>::clear_prefix(netuid, u32::MAX, None);
+        let _ = WeightCommits::<T>::clear_prefix(netuid, u32::MAX, None);
         Active::<T>::remove(netuid);
         Consensus::<T>::remove(netuid);
         Dividends::<T>::remove(netuid);
@@ -263,6 +326,7 @@ impl<T: Config> Pallet<T> {
         ValidatorTrust::<T>::remove(netuid);
         let _ = RegistrationBlock::<T>::clear_prefix(netuid, u32::MAX, None);
         SubnetEmission::<T>::remove(netuid);
+        SubnetRegistrationBlock::<T>::remove(netuid);
 
         // --- 3. Erase subnet parameters.
         // ===============================
@@ -280,13 +344,28 @@ impl<T: Config> Pallet<T> {
         IncentiveRatio::<T>::remove(netuid);
         MaximumSetWeightCallsPerEpoch::<T>::remove(netuid);
         BondsMovingAverage::<T>::remove(netuid);
+        LiquidAlphaEnabled::<T>::remove(netuid);
+        AlphaValues::<T>::remove(netuid);
         TargetRegistrationsInterval::<T>::remove(netuid);
         TargetRegistrationsPerInterval::<T>::remove(netuid);
         MaxRegistrationsPerInterval::<T>::remove(netuid);
         AdjustmentAlpha::<T>::remove(netuid);
+        UnbondingPeriod::<T>::remove(netuid);
+        SlashRatio::<T>::remove(netuid);
+        SlashThreshold::<T>::remove(netuid);
+        CommitRevealWeights::<T>::remove(netuid);
+        WeightRevealPeriod::<T>::remove(netuid);
+        SubnetConsensusType::<T>::remove(netuid);
+        WeightHistoryEnabled::<T>::remove(netuid);
+        Self::clear_weight_history(netuid);
+        RewardHistoryEnabled::<T>::remove(netuid);
+        Self::clear_reward_history(netuid);
 
         T::handle_subnet_removal(netuid);
 
+        // Subnets that did not lose their slot to another one get their deposit back.
+        Self::return_subnet_deposit(netuid);
+
         // --- 4 Adjust the total number of subnets. and remove the subnet from the list of subnets.
         // =========================================================================================
 
@@ -569,6 +648,16 @@ impl<T: Config> Pallet<T> {
         Self::netuids().iter().any(|&netuid| Uids::<T>::contains_key(netuid, key))
     }
 
+    /// Returns the consensus the subnet runs. Subnets created before the consensus could be
+    /// selected run linear on the global subnet and yuma everywhere else.
+    pub fn get_subnet_consensus(netuid: u16) -> SubnetConsensus {
+        SubnetConsensusType::<T>::get(netuid).unwrap_or(if netuid == 0 {
+            SubnetConsensus::Linear
+        } else {
+            SubnetConsensus::Yuma
+        })
+    }
+
     pub fn is_registered(netuid: u16, key: &T::AccountId) -> bool {
         Uids::<T>::contains_key(netuid, key)
     }
@@ -596,5 +685,8 @@ impl<T: Config> Pallet<T> {
         // --- 4. Remove all stake.
         Stake::<T>::remove_prefix(netuid, None);
         TotalStake::<T>::remove(netuid);
+
+        // --- 5. The subnet is gone, so pending unlocking chunks are released right away.
+        Self::release_unlocking(netuid);
     }
 }
@@ -0,0 +1,86 @@
+use super::*;
+
+use frame_support::traits::Get;
+use sp_runtime::BoundedVec;
+use sp_std::vec::Vec;
+
+impl<T: Config> Pallet<T> {
+    /// Appends the weights the module just set to its history, if the subnet keeps one.
+    pub fn record_weights(netuid: u16, uid: u16, weights: &[(u16, u16)]) {
+        if !WeightHistoryEnabled::<T>::get(netuid) {
+            return;
+        }
+
+        let record = WeightRecord {
+            block: Self::get_current_block_number(),
+            weights: weights.to_vec(),
+        };
+        WeightHistory::<T>::mutate(netuid, uid, |records| Self::push_bounded(records, record));
+    }
+
+    /// Appends the validator trust and dividends of the epoch that just ran to the history of
+    /// every module that set weights on the subnet. Only called once the consensus actually ran,
+    /// so skipped epochs leave no stale records.
+    pub fn record_validator_epochs(netuid: u16) {
+        if !WeightHistoryEnabled::<T>::get(netuid) {
+            return;
+        }
+
+        let block = Self::get_current_block_number();
+        let validator_trust = ValidatorTrust::<T>::get(netuid);
+        let dividends = Dividends::<T>::get(netuid);
+
+        for uid in Weights::<T>::iter_key_prefix(netuid) {
+            let record = ValidatorEpochRecord {
+                block,
+                validator_trust: validator_trust.get(uid as usize).copied().unwrap_or_default(),
+                dividends: dividends.get(uid as usize).copied().unwrap_or_default(),
+            };
+            ValidatorHistory::<T>::mutate(netuid, uid, |records| {
+                Self::push_bounded(records, record)
+            });
+        }
+    }
+
+    /// Drops the history of the removed uid and moves the history of the module that takes its
+    /// uid over, mirroring how `remove_module` swaps the weights.
+    pub fn swap_weight_history(netuid: u16, uid: u16, replace_uid: u16) {
+        let weights = WeightHistory::<T>::take(netuid, replace_uid);
+        let epochs = ValidatorHistory::<T>::take(netuid, replace_uid);
+
+        if uid == replace_uid {
+            return;
+        }
+
+        WeightHistory::<T>::insert(netuid, uid, weights);
+        ValidatorHistory::<T>::insert(netuid, uid, epochs);
+    }
+
+    /// Clears the weight and validator history of every module on the subnet.
+    pub fn clear_weight_history(netuid: u16) {
+        let _ = WeightHistory::<T>::clear_prefix(netuid, u32::MAX, None);
+        let _ = ValidatorHistory::<T>::clear_prefix(netuid, u32::MAX, None);
+    }
+
+    /// Returns the weights the module set and its validator epochs, oldest first.
+    pub fn get_weight_history(
+        netuid: u16,
+        uid: u16,
+    ) -> (Vec<WeightRecord>, Vec<ValidatorEpochRecord>) {
+        (
+            WeightHistory::<T>::get(netuid, uid).into_inner(),
+            ValidatorHistory::<T>::get(netuid, uid).into_inner(),
+        )
+    }
+
+    fn push_bounded<R, S: Get<u32>>(records: &mut BoundedVec<R, S>, record: R) {
+        if S::get() == 0 {
+            return;
+        }
+
+        if records.is_full() {
+            records.remove(0);
+        }
+        let _ = records.try_push(record);
+    }
+}
@@ -57,6 +57,15 @@ pub trait WeightInfo {
 	fn add_transfer_dao_treasury_proposal() -> Weight;
 	fn vote_proposal() -> Weight;
 	fn unvote_proposal() -> Weight;
+	fn withdraw_unbonded(s: u32, ) -> Weight;
+	fn commit_weights() -> Weight;
+	fn reveal_weights() -> Weight;
+	fn set_weights_multiple(n: u32, ) -> Weight;
+	fn set_auto_compound() -> Weight;
+	fn rotate_module_key(s: u32, ) -> Weight;
+	fn set_module_controller() -> Weight;
+	fn controller_update_module() -> Weight;
+	fn bid_subnet_slot() -> Weight;
 }
 
 /// Weights for `pallet_subspace` using the Substrate node and recommended hardware.
@@ -790,6 +799,66 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(1_u64))
 			.saturating_add(T::DbWeight::get().writes(1_u64))
 	}
+	// The weights below are estimates, they were not generated by the benchmark CLI yet. Run the
+	// command above for `withdraw_unbonded`, `commit_weights`, `reveal_weights`,
+	// `set_weights_multiple`, `set_auto_compound`, `rotate_module_key`, `set_module_controller`,
+	// `controller_update_module` and `bid_subnet_slot` to replace them. The generated weights of
+	// the calls that charge a call fee or count against a rate limit predate that storage, and
+	// have to be generated again along with them.
+	fn withdraw_unbonded(s: u32, ) -> Weight {
+		Weight::from_parts(20_000_000, 3593)
+			.saturating_add(Weight::from_parts(5_000_000, 0).saturating_mul(s.into()))
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(s.into())))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(s.into())))
+			.saturating_add(Weight::from_parts(0, 2600).saturating_mul(s.into()))
+	}
+	fn commit_weights() -> Weight {
+		Weight::from_parts(14_000_000, 3930)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	fn reveal_weights() -> Weight {
+		Weight::from_parts(33_000_000, 6500)
+			.saturating_add(T::DbWeight::get().reads(12_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	fn set_weights_multiple(n: u32, ) -> Weight {
+		Weight::from_parts(3_120_000, 0)
+			.saturating_add(Weight::from_parts(38_904_000, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads((11_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes((4_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 4658).saturating_mul(n.into()))
+	}
+	fn set_auto_compound() -> Weight {
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	fn rotate_module_key(s: u32, ) -> Weight {
+		Weight::from_parts(30_000_000, 4641)
+			.saturating_add(Weight::from_parts(40_000_000, 0).saturating_mul(s.into()))
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().reads((24_u64).saturating_mul(s.into())))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+			.saturating_add(T::DbWeight::get().writes((24_u64).saturating_mul(s.into())))
+			.saturating_add(Weight::from_parts(0, 5000).saturating_mul(s.into()))
+	}
+	fn set_module_controller() -> Weight {
+		Weight::from_parts(12_000_000, 3541)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	fn controller_update_module() -> Weight {
+		Weight::from_parts(50_000_000, 7052)
+			.saturating_add(T::DbWeight::get().reads(11_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	fn bid_subnet_slot() -> Weight {
+		Weight::from_parts(28_000_000, 4000)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
 }
 
 // For backwards compatibility and tests.
@@ -1522,4 +1591,64 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(1_u64))
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
 	}
+	// The weights below are estimates, they were not generated by the benchmark CLI yet. Run the
+	// command above for `withdraw_unbonded`, `commit_weights`, `reveal_weights`,
+	// `set_weights_multiple`, `set_auto_compound`, `rotate_module_key`, `set_module_controller`,
+	// `controller_update_module` and `bid_subnet_slot` to replace them. The generated weights of
+	// the calls that charge a call fee or count against a rate limit predate that storage, and
+	// have to be generated again along with them.
+	fn withdraw_unbonded(s: u32, ) -> Weight {
+		Weight::from_parts(20_000_000, 3593)
+			.saturating_add(Weight::from_parts(5_000_000, 0).saturating_mul(s.into()))
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().reads((2_u64).saturating_mul(s.into())))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(s.into())))
+			.saturating_add(Weight::from_parts(0, 2600).saturating_mul(s.into()))
+	}
+	fn commit_weights() -> Weight {
+		Weight::from_parts(14_000_000, 3930)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	fn reveal_weights() -> Weight {
+		Weight::from_parts(33_000_000, 6500)
+			.saturating_add(RocksDbWeight::get().reads(12_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	fn set_weights_multiple(n: u32, ) -> Weight {
+		Weight::from_parts(3_120_000, 0)
+			.saturating_add(Weight::from_parts(38_904_000, 0).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads((11_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes((4_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 4658).saturating_mul(n.into()))
+	}
+	fn set_auto_compound() -> Weight {
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn rotate_module_key(s: u32, ) -> Weight {
+		Weight::from_parts(30_000_000, 4641)
+			.saturating_add(Weight::from_parts(40_000_000, 0).saturating_mul(s.into()))
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().reads((24_u64).saturating_mul(s.into())))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+			.saturating_add(RocksDbWeight::get().writes((24_u64).saturating_mul(s.into())))
+			.saturating_add(Weight::from_parts(0, 5000).saturating_mul(s.into()))
+	}
+	fn set_module_controller() -> Weight {
+		Weight::from_parts(12_000_000, 3541)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn controller_update_module() -> Weight {
+		Weight::from_parts(50_000_000, 7052)
+			.saturating_add(RocksDbWeight::get().reads(11_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	fn bid_subnet_slot() -> Weight {
+		Weight::from_parts(28_000_000, 4000)
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
 }
\ No newline at end of file
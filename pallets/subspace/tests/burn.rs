@@ -2,9 +2,11 @@ mod mock;
 use frame_support::assert_ok;
 
 use mock::*;
+use pallet_governance_api::GovernanceApi;
 use pallet_subspace::{
-    global::BurnConfiguration, AdjustmentAlpha, Burn, MaxRegistrationsPerBlock,
-    TargetRegistrationsInterval, TargetRegistrationsPerInterval,
+    global::{BurnConfiguration, BurnDestination},
+    AdjustmentAlpha, Burn, MaxRegistrationsPerBlock, TargetRegistrationsInterval,
+    TargetRegistrationsPerInterval,
 };
 use sp_core::U256;
 
@@ -66,3 +68,70 @@ fn test_local_subnet_burn() {
         assert!(min_burn < subnet_one_burn && subnet_one_burn < max_burn);
     });
 }
+
+#[test]
+fn registration_burn_goes_to_configured_destination() {
+    new_test_ext().execute_with(|| {
+        step_block(1);
+
+        let set_destination = |destination| {
+            assert_ok!(BurnConfiguration::<Test> {
+                min_burn: to_nano(10),
+                destination,
+                ..BurnConfiguration::<Test>::default()
+            }
+            .apply());
+        };
+
+        let netuid = 0;
+        let founder = U256::from(0);
+        let treasury = Test::get_dao_treasury_address();
+        let stake = to_nano(50);
+
+        set_destination(BurnDestination::SubnetFounder);
+        assert_ok!(register_module(netuid, founder, stake));
+        let burn = Burn::<Test>::get(netuid);
+        assert_eq!(burn, to_nano(10));
+
+        let founder_balance = SubspaceModule::get_balance_u64(&founder);
+        assert_ok!(register_module(netuid, U256::from(1), stake));
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&founder),
+            founder_balance + burn
+        );
+        assert_eq!(get_stake_for_uid(netuid, 1), stake - burn);
+        System::assert_has_event(
+            pallet_subspace::Event::RegistrationBurned(
+                netuid,
+                U256::from(1),
+                burn,
+                BurnDestination::SubnetFounder,
+            )
+            .into(),
+        );
+
+        set_destination(BurnDestination::DaoTreasury);
+        assert_ok!(register_module(netuid, U256::from(2), stake));
+        assert_eq!(SubspaceModule::get_balance_u64(&treasury), burn);
+
+        set_destination(BurnDestination::Destroy);
+        let issuance = pallet_balances::TotalIssuance::<Test>::get();
+        assert_ok!(register_module(netuid, U256::from(3), stake));
+        // Only the free balance left after staking was minted.
+        assert_eq!(pallet_balances::TotalIssuance::<Test>::get(), issuance + 1);
+        assert_eq!(SubspaceModule::get_balance_u64(&treasury), burn);
+
+        // Recycled burns are minted back, so the issuance matches the existing balances.
+        let balances: u64 = [
+            founder,
+            U256::from(1),
+            U256::from(2),
+            U256::from(3),
+            treasury,
+        ]
+        .iter()
+        .map(SubspaceModule::get_balance_u64)
+        .sum();
+        assert_eq!(pallet_balances::TotalIssuance::<Test>::get(), balances);
+    });
+}
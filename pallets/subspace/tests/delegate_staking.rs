@@ -3,7 +3,7 @@ mod mock;
 use frame_support::assert_ok;
 use log::info;
 use mock::*;
-use pallet_subspace::{Emission, Tempo};
+use pallet_subspace::{AutoCompound, Emission, Tempo};
 use sp_core::U256;
 
 // /***********************************************************
@@ -134,3 +134,117 @@ fn test_ownership_ratio() {
         info!("stake_from_vector: {stake_from_vector:?}");
     });
 }
+
+#[test]
+fn test_delegator_auto_compound_toggle() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+        let stake_per_module: u64 = 1_000_000_000;
+        zero_min_burn();
+
+        register_n_modules(netuid, 3, stake_per_module);
+        Tempo::<Test>::insert(netuid, 1);
+
+        let keys = SubspaceModule::get_keys(netuid);
+        let voter_key = keys[0];
+        let miner_uids: Vec<u16> =
+            keys[1..].iter().map(|k| SubspaceModule::get_uid_for_key(netuid, k)).collect();
+
+        let compounding = U256::from(100);
+        let paid_out = U256::from(101);
+        let paid_out_on_module = U256::from(102);
+        for delegator in [compounding, paid_out, paid_out_on_module] {
+            add_balance(delegator, stake_per_module + 1);
+            assert_ok!(SubspaceModule::add_stake(
+                get_origin(delegator),
+                netuid,
+                voter_key,
+                stake_per_module,
+            ));
+        }
+
+        // the global setting is overridden by the per module one
+        assert_ok!(SubspaceModule::set_auto_compound(
+            get_origin(compounding),
+            None,
+            Some(false)
+        ));
+        assert_ok!(SubspaceModule::set_auto_compound(
+            get_origin(compounding),
+            Some(voter_key),
+            Some(true)
+        ));
+        assert_ok!(SubspaceModule::set_auto_compound(
+            get_origin(paid_out),
+            None,
+            Some(false)
+        ));
+        assert_ok!(SubspaceModule::set_auto_compound(
+            get_origin(paid_out_on_module),
+            Some(voter_key),
+            Some(false)
+        ));
+
+        assert!(SubspaceModule::is_auto_compounding(
+            &compounding,
+            &voter_key
+        ));
+        assert!(!SubspaceModule::is_auto_compounding(&paid_out, &voter_key));
+        assert!(!SubspaceModule::is_auto_compounding(
+            &paid_out_on_module,
+            &voter_key
+        ));
+        assert!(SubspaceModule::is_auto_compounding(
+            &paid_out_on_module,
+            &keys[1]
+        ));
+
+        let balances_before: Vec<u64> = [compounding, paid_out, paid_out_on_module]
+            .iter()
+            .map(SubspaceModule::get_balance_u64)
+            .collect();
+        let total_stake_before = SubspaceModule::get_total_subnet_stake(netuid);
+
+        assert_ok!(SubspaceModule::set_weights(
+            get_origin(voter_key),
+            netuid,
+            miner_uids.clone(),
+            vec![1; miner_uids.len()],
+        ));
+        step_epoch(netuid);
+
+        let compounding_stake =
+            SubspaceModule::get_stake_to_module(netuid, &compounding, &voter_key);
+        assert!(compounding_stake > stake_per_module);
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&compounding),
+            balances_before[0]
+        );
+
+        let mut paid = 0;
+        for (i, delegator) in [paid_out, paid_out_on_module].iter().enumerate() {
+            assert_eq!(
+                SubspaceModule::get_stake_to_module(netuid, delegator, &voter_key),
+                stake_per_module
+            );
+            let reward = SubspaceModule::get_balance_u64(delegator) - balances_before[i + 1];
+            assert_eq!(reward, compounding_stake - stake_per_module);
+            paid += reward;
+        }
+
+        // payouts to the free balance are not accounted as stake
+        let total_emissions: u64 = Emission::<Test>::get(netuid).iter().sum();
+        assert_eq!(
+            SubspaceModule::get_total_subnet_stake(netuid),
+            total_stake_before + total_emissions - paid
+        );
+
+        assert_ok!(SubspaceModule::set_auto_compound(
+            get_origin(paid_out),
+            None,
+            None
+        ));
+        assert!(!AutoCompound::<Test>::contains_key(paid_out, None::<U256>));
+        assert!(SubspaceModule::is_auto_compounding(&paid_out, &voter_key));
+    });
+}
@@ -1,19 +1,24 @@
 #![allow(non_camel_case_types)]
 
 use frame_support::{
-    assert_ok, parameter_types,
+    assert_ok,
+    dispatch::GetDispatchInfo,
+    parameter_types,
     traits::{Everything, Hooks},
+    weights::IdentityFee,
     PalletId,
 };
 use frame_system as system;
 use pallet_governance_api::*;
 use pallet_subspace::{
     Address, BurnConfig, Dividends, Emission, Incentive, LastUpdate, MaxRegistrationsPerBlock,
-    MaxRegistrationsPerInterval, Name, Stake, Tempo, N,
+    MaxRegistrationsPerInterval, Name, Stake, SubspaceSignedExtension, Tempo, N,
 };
+use pallet_transaction_payment::ChargeTransactionPayment;
 use sp_core::{H256, U256};
 use sp_runtime::{
-    traits::{AccountIdConversion, BlakeTwo256, IdentityLookup},
+    traits::{AccountIdConversion, BlakeTwo256, Dispatchable, IdentityLookup, SignedExtension},
+    transaction_validity::TransactionValidityError,
     BuildStorage, DispatchResult, Percent,
 };
 
@@ -26,7 +31,9 @@ frame_support::construct_runtime!(
     pub enum Test {
         System: frame_system,
         Balances: pallet_balances,
+        TransactionPayment: pallet_transaction_payment,
         SubspaceModule: pallet_subspace,
+        Utility: pallet_utility,
     }
 );
 
@@ -106,8 +113,26 @@ impl system::Config for Test {
     type PostTransactions = ();
 }
 
+impl pallet_transaction_payment::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type OnChargeTransaction = pallet_transaction_payment::CurrencyAdapter<Balances, ()>;
+    type OperationalFeeMultiplier = frame_support::traits::ConstU8<1>;
+    type WeightToFee = IdentityFee<Balance>;
+    type LengthToFee = IdentityFee<Balance>;
+    type FeeMultiplierUpdate = ();
+}
+
+impl pallet_utility::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type RuntimeCall = RuntimeCall;
+    type PalletsOrigin = OriginCaller;
+    type WeightInfo = ();
+}
+
 parameter_types! {
     pub const SubspacePalletId: PalletId = PalletId(*b"py/subsp");
+    pub const RewardHistoryDepth: u32 = 3;
+    pub const WeightHistoryDepth: u32 = 3;
 }
 
 impl pallet_subspace::Config for Test {
@@ -115,6 +140,8 @@ impl pallet_subspace::Config for Test {
     type Currency = Balances;
     type WeightInfo = ();
     type PalletId = SubspacePalletId;
+    type RewardHistoryDepth = RewardHistoryDepth;
+    type WeightHistoryDepth = WeightHistoryDepth;
 }
 
 impl GovernanceApi<<Test as frame_system::Config>::AccountId> for Test {
@@ -130,6 +157,10 @@ impl GovernanceApi<<Test as frame_system::Config>::AccountId> for Test {
         false
     }
 
+    fn get_locked_stake(_staker: &AccountId, _subnet_id: Option<u16>) -> u64 {
+        0
+    }
+
     fn update_delegating_voting_power(_delegator: &AccountId, _delegating: bool) -> DispatchResult {
         Ok(())
     }
@@ -157,6 +188,10 @@ impl GovernanceApi<<Test as frame_system::Config>::AccountId> for Test {
 
     fn handle_subnet_removal(_subnet_id: u16) {}
 
+    fn handle_key_rotation(_key: &AccountId, _new_key: &AccountId) -> DispatchResult {
+        Ok(())
+    }
+
     fn execute_application(_user_id: &AccountId) -> DispatchResult {
         Ok(())
     }
@@ -245,6 +280,33 @@ pub fn get_origin(key: U256) -> RuntimeOrigin {
     <<Test as frame_system::Config>::RuntimeOrigin>::signed(key)
 }
 
+pub type SignedExtra = (
+    ChargeTransactionPayment<Test>,
+    SubspaceSignedExtension<Test>,
+);
+
+// Runs the call through the signed extensions and dispatches it, like the runtime does.
+#[allow(dead_code)]
+pub fn dispatch_signed(
+    who: U256,
+    call: RuntimeCall,
+) -> Result<DispatchResult, TransactionValidityError> {
+    let extra: SignedExtra = (
+        ChargeTransactionPayment::from(0),
+        SubspaceSignedExtension::new(),
+    );
+    let info = call.get_dispatch_info();
+    extra.validate(&who, &call, &info, 0)?;
+    let pre = extra.pre_dispatch(&who, &call, &info, 0)?;
+
+    let result = call.dispatch(get_origin(who));
+    let post_info = result.unwrap_or_else(|err| err.post_info);
+    let result = result.map(|_| ()).map_err(|err| err.error);
+    SignedExtra::post_dispatch(Some(pre), &info, &post_info, 0, &result)?;
+
+    Ok(result)
+}
+
 #[allow(dead_code)]
 pub fn register_n_modules(netuid: u16, n: u16, stake: u64) {
     for i in 0..n {
@@ -2,15 +2,18 @@ mod mock;
 
 use std::collections::BTreeSet;
 
-use frame_support::{assert_err, assert_noop, assert_ok};
+use frame_support::{assert_err, assert_noop, assert_ok, BoundedVec};
 use mock::*;
 use sp_core::U256;
 
 use log::info;
 use pallet_subspace::{
-    Emission, Error, FloorDelegationFee, MaxAllowedModules, MaxAllowedSubnets, MaxAllowedUids,
-    MaxNameLength, MaxRegistrationsPerBlock, MinNameLength, MinStake, RegistrationsPerBlock, Stake,
-    SubnetGaps, SubnetNames, TotalSubnets, N,
+    registration::ModuleSlot, AutoCompound, Burn, Controller, DelegationFee, Emission, Error,
+    FloorDelegationFee, Founder, ImmunityPeriod, MaxAllowedModules, MaxAllowedSubnets,
+    MaxAllowedUids, MaxNameLength, MaxRegistrationsPerBlock, MinNameLength, MinStake,
+    RegistrationsPerBlock, RewardHistory, RewardRecord, RewardedAccounts, Stake, StakeFrom,
+    StakeTo, SubnetAuctionPeriod, SubnetBid, SubnetBids, SubnetGaps, SubnetNames, TotalSubnets,
+    UnlockChunk, Unlocking, N,
 };
 use sp_runtime::{DispatchResult, Percent};
 
@@ -629,3 +632,476 @@ fn new_subnets_on_removed_uids_register_modules_to_the_correct_netuids() {
         assert_eq!(N::<Test>::get(2), 1);
     });
 }
+
+#[test]
+fn rotate_module_key_keeps_uids_and_delegations() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+        System::set_block_number(1);
+
+        let key = U256::from(0);
+        let new_key = U256::from(1);
+        let other = U256::from(2);
+        let delegator = U256::from(3);
+        let stake = 1_000_000_000;
+
+        assert_ok!(register_module(0, key, stake));
+        assert_ok!(register_module(1, other, stake));
+        assert_ok!(register_module(1, key, stake));
+
+        add_balance(delegator, stake + 1);
+        assert_ok!(SubspaceModule::add_stake(
+            get_origin(delegator),
+            1,
+            key,
+            stake
+        ));
+        assert_ok!(SubspaceModule::set_auto_compound(
+            get_origin(delegator),
+            Some(key),
+            Some(false)
+        ));
+        assert_ok!(SubspaceModule::update_module(
+            get_origin(key),
+            1,
+            b"rotated".to_vec(),
+            b"0.0.0.0:30333".to_vec(),
+            Some(Percent::from_percent(10)),
+            None,
+        ));
+
+        let uids = [
+            SubspaceModule::get_uid_for_key(0, &key),
+            SubspaceModule::get_uid_for_key(1, &key),
+        ];
+        let total_stake = SubspaceModule::total_stake();
+
+        assert_noop!(
+            SubspaceModule::rotate_module_key(get_origin(key), 1, key, other),
+            Error::<Test>::ModuleKeyInUse
+        );
+        assert_noop!(
+            SubspaceModule::rotate_module_key(get_origin(new_key), 1, new_key, key),
+            Error::<Test>::NotRegistered
+        );
+
+        // Only the module key can rotate a module without a controller.
+        assert_noop!(
+            SubspaceModule::rotate_module_key(get_origin(other), 1, key, new_key),
+            Error::<Test>::NotModuleController
+        );
+
+        assert_ok!(SubspaceModule::rotate_module_key(
+            get_origin(key),
+            1,
+            key,
+            new_key
+        ));
+
+        for (netuid, uid) in [0, 1].into_iter().zip(uids) {
+            assert!(!SubspaceModule::is_registered(netuid, &key));
+            assert_eq!(SubspaceModule::get_uid_for_key(netuid, &new_key), uid);
+            assert_eq!(SubspaceModule::get_key_for_uid(netuid, uid), Some(new_key));
+            assert_eq!(
+                SubspaceModule::get_stake_to_module(netuid, &new_key, &new_key),
+                stake
+            );
+            assert!(StakeTo::<Test>::get(netuid, key).is_empty());
+            assert!(StakeFrom::<Test>::get(netuid, key).is_empty());
+        }
+
+        assert_eq!(Stake::<Test>::get(1, new_key), stake * 2);
+        assert_eq!(
+            DelegationFee::<Test>::get(1, new_key),
+            Percent::from_percent(10)
+        );
+        assert_eq!(
+            SubspaceModule::get_stake_to_module(1, &delegator, &new_key),
+            stake
+        );
+        assert_eq!(
+            StakeFrom::<Test>::get(1, new_key).get(&delegator),
+            Some(&stake)
+        );
+        assert_eq!(
+            AutoCompound::<Test>::get(delegator, Some(new_key)),
+            Some(false)
+        );
+        assert_eq!(SubspaceModule::total_stake(), total_stake);
+        assert_eq!(Founder::<Test>::get(0), new_key);
+        assert_eq!(Founder::<Test>::get(1), other);
+
+        System::assert_last_event(
+            pallet_subspace::Event::ModuleKeyRotated(key, new_key, vec![0, 1]).into(),
+        );
+    });
+}
+
+#[test]
+fn rotate_module_key_is_authorized_by_the_module_controller() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        let key = U256::from(0);
+        let new_key = U256::from(1);
+        let controller = U256::from(2);
+        let founder = U256::from(3);
+        let stake = to_nano(10);
+
+        assert_ok!(register_module(0, key, stake));
+        assert_ok!(register_module(1, founder, stake));
+        assert_ok!(register_module(1, key, stake));
+        assert_ok!(SubspaceModule::set_module_controller(
+            get_origin(key),
+            0,
+            key,
+            controller
+        ));
+
+        // A leaked module key can't move a controlled module.
+        assert_noop!(
+            SubspaceModule::rotate_module_key(get_origin(key), 0, key, new_key),
+            Error::<Test>::NotModuleController
+        );
+        // The controller must control the module on every subnet it is registered on.
+        assert_noop!(
+            SubspaceModule::rotate_module_key(get_origin(controller), 0, key, new_key),
+            Error::<Test>::NotModuleController
+        );
+
+        assert_ok!(SubspaceModule::set_module_controller(
+            get_origin(key),
+            1,
+            key,
+            controller
+        ));
+        assert_ok!(SubspaceModule::rotate_module_key(
+            get_origin(controller),
+            0,
+            key,
+            new_key
+        ));
+
+        for netuid in [0, 1] {
+            assert!(!SubspaceModule::is_registered(netuid, &key));
+            assert!(SubspaceModule::is_registered(netuid, &new_key));
+            assert_eq!(Controller::<Test>::get(netuid, new_key), Some(controller));
+            assert_eq!(Controller::<Test>::get(netuid, key), None);
+            assert_eq!(Stake::<Test>::get(netuid, new_key), stake);
+        }
+
+        // The module stays under the controller after the rotation.
+        assert_noop!(
+            SubspaceModule::rotate_module_key(get_origin(new_key), 0, new_key, key),
+            Error::<Test>::NotModuleController
+        );
+    });
+}
+
+#[test]
+fn rotate_module_key_merges_entries_of_the_new_key() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        let netuid = 0;
+        let key = U256::from(0);
+        let new_key = U256::from(1);
+        assert_ok!(register_module(netuid, key, to_nano(10)));
+
+        let chunk = |amount, unlock_block| UnlockChunk {
+            amount,
+            unlock_block,
+        };
+        Unlocking::<Test>::insert(netuid, key, vec![chunk(1, 10), chunk(2, 30)]);
+        Unlocking::<Test>::insert(netuid, new_key, vec![chunk(4, 20), chunk(8, 30)]);
+
+        let record = |block, amount| RewardRecord { block, amount };
+        RewardHistory::<Test>::insert(
+            netuid,
+            key,
+            BoundedVec::truncate_from(vec![record(1, 1), record(3, 2)]),
+        );
+        RewardHistory::<Test>::insert(
+            netuid,
+            new_key,
+            BoundedVec::truncate_from(vec![record(2, 4), record(3, 8), record(4, 16)]),
+        );
+
+        SubnetBids::<Test>::insert(
+            key,
+            SubnetBid {
+                name: BoundedVec::truncate_from(b"auctioned".to_vec()),
+                amount: 5,
+            },
+        );
+
+        assert_ok!(SubspaceModule::rotate_module_key(
+            get_origin(key),
+            netuid,
+            key,
+            new_key
+        ));
+
+        assert_eq!(Founder::<Test>::get(netuid), new_key);
+        assert_eq!(
+            Unlocking::<Test>::get(netuid, new_key),
+            vec![chunk(1, 10), chunk(4, 20), chunk(10, 30)]
+        );
+        assert!(!Unlocking::<Test>::contains_key(netuid, key));
+
+        // the mock keeps the last 3 epochs
+        assert_eq!(
+            RewardHistory::<Test>::get(netuid, new_key).into_inner(),
+            vec![record(2, 4), record(3, 10), record(4, 16)]
+        );
+        assert!(!RewardHistory::<Test>::contains_key(netuid, key));
+        assert!(RewardedAccounts::<Test>::get(netuid, 3).contains(&new_key));
+
+        assert!(!SubnetBids::<Test>::contains_key(key));
+        assert_eq!(
+            SubnetBids::<Test>::get(new_key).map(|bid| bid.amount),
+            Some(5)
+        );
+    });
+}
+
+#[test]
+fn rotate_module_key_keeps_the_unlocking_chunk_limit() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        let netuid = 0;
+        let key = U256::from(0);
+        let new_key = U256::from(1);
+        assert_ok!(register_module(netuid, key, to_nano(10)));
+
+        let chunks = |blocks: std::ops::Range<u64>| {
+            blocks
+                .map(|unlock_block| UnlockChunk {
+                    amount: 1,
+                    unlock_block,
+                })
+                .collect::<Vec<_>>()
+        };
+        Unlocking::<Test>::insert(netuid, key, chunks(0..20));
+        Unlocking::<Test>::insert(netuid, new_key, chunks(20..40));
+
+        assert_noop!(
+            SubspaceModule::rotate_module_key(get_origin(key), netuid, key, new_key),
+            Error::<Test>::TooManyUnlockingChunks
+        );
+    });
+}
+
+#[test]
+fn module_controller_owns_stake_fee_and_deregistration() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        let netuid = 0;
+        let key = U256::from(0);
+        let controller = U256::from(1);
+        let stake = 1_000_000_000;
+
+        assert_ok!(register_module(netuid, key, stake));
+        assert_noop!(
+            SubspaceModule::set_module_controller(get_origin(controller), netuid, key, controller),
+            Error::<Test>::NotModuleController
+        );
+        assert_ok!(SubspaceModule::set_module_controller(
+            get_origin(key),
+            netuid,
+            key,
+            controller
+        ));
+        assert_eq!(Controller::<Test>::get(netuid, key), Some(controller));
+        assert_eq!(
+            SubspaceModule::module_params(netuid, &key).controller,
+            controller
+        );
+
+        // the module key can still set its address, but nothing the controller owns
+        assert_ok!(SubspaceModule::update_module(
+            get_origin(key),
+            netuid,
+            b"module0".to_vec(),
+            b"1.1.1.1:30333".to_vec(),
+            None,
+            None,
+        ));
+        assert_noop!(
+            SubspaceModule::update_module(
+                get_origin(key),
+                netuid,
+                b"module0".to_vec(),
+                b"1.1.1.1:30333".to_vec(),
+                Some(Percent::from_percent(30)),
+                None,
+            ),
+            Error::<Test>::NotModuleController
+        );
+        assert_noop!(
+            SubspaceModule::remove_stake(get_origin(key), netuid, key, stake / 2),
+            Error::<Test>::NotModuleController
+        );
+        assert_noop!(
+            SubspaceModule::deregister(get_origin(key), netuid),
+            Error::<Test>::NotModuleController
+        );
+
+        assert_ok!(SubspaceModule::controller_update_module(
+            get_origin(controller),
+            netuid,
+            key,
+            b"module0".to_vec(),
+            b"1.1.1.1:30333".to_vec(),
+            Some(Percent::from_percent(30)),
+            None,
+        ));
+        assert_eq!(
+            DelegationFee::<Test>::get(netuid, key),
+            Percent::from_percent(30)
+        );
+
+        let balance_before = SubspaceModule::get_balance_u64(&controller);
+        assert_ok!(SubspaceModule::controller_remove_stake(
+            get_origin(controller),
+            netuid,
+            key,
+            stake / 2
+        ));
+        assert_eq!(Stake::<Test>::get(netuid, key), stake / 2);
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&controller),
+            balance_before + stake / 2
+        );
+
+        assert_ok!(SubspaceModule::controller_deregister(
+            get_origin(controller),
+            netuid,
+            key
+        ));
+        assert!(!SubspaceModule::is_registered(netuid, &key));
+        assert!(!Controller::<Test>::contains_key(netuid, key));
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&controller),
+            balance_before + stake
+        );
+    });
+}
+
+#[test]
+fn registration_preview_matches_the_next_registration() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        let netuid = 0;
+        register_n_modules(netuid, 3, to_nano(10));
+        MaxAllowedUids::<Test>::insert(netuid, 3);
+        ImmunityPeriod::<Test>::insert(netuid, 10);
+        MinStake::<Test>::insert(netuid, to_nano(1));
+        Burn::<Test>::insert(netuid, to_nano(2));
+
+        let preview = SubspaceModule::get_registration_preview(netuid, to_nano(2));
+        assert_eq!(
+            preview.error,
+            Some(Error::<Test>::NotEnoughStakeToRegister.into())
+        );
+        assert_eq!(preview.burn, to_nano(2));
+        assert_eq!(preview.min_stake, to_nano(1));
+        assert!(!preview.enough_stake);
+        assert!(!preview.creates_subnet);
+        assert_eq!(preview.immunity_period, 10);
+        assert_eq!(
+            preview.registrations_left_this_block,
+            MaxRegistrationsPerBlock::<Test>::get() - RegistrationsPerBlock::<Test>::get()
+        );
+
+        // Every module of the full subnet is still immune.
+        assert_eq!(preview.module_slot, ModuleSlot::Immune);
+        assert_eq!(preview.pruned_key, None);
+        assert_eq!(
+            SubspaceModule::get_registration_preview(netuid, to_nano(3)).error,
+            Some(Error::<Test>::NetworkIsImmuned.into())
+        );
+
+        step_block(10);
+        Burn::<Test>::insert(netuid, to_nano(2));
+
+        let preview = SubspaceModule::get_registration_preview(netuid, to_nano(3));
+        assert_eq!(preview.error, None);
+        assert!(preview.enough_stake);
+        let ModuleSlot::Prune {
+            netuid: pruned_netuid,
+            uid,
+        } = preview.module_slot
+        else {
+            panic!("a module should be pruned once the immunity period is over");
+        };
+        assert_eq!(pruned_netuid, netuid);
+        let pruned_key = preview.pruned_key.expect("the pruned module has a key");
+        assert_eq!(
+            SubspaceModule::get_key_for_uid(netuid, uid),
+            Some(pruned_key)
+        );
+
+        assert_ok!(register_module(netuid, U256::from(10), to_nano(3)));
+        assert!(!SubspaceModule::key_registered(netuid, &pruned_key));
+
+        let after = SubspaceModule::get_registration_preview(netuid, to_nano(3));
+        assert_eq!(
+            after.registrations_left_this_block,
+            preview.registrations_left_this_block - 1
+        );
+        assert_eq!(
+            after.registrations_left_this_interval,
+            preview.registrations_left_this_interval - 1
+        );
+
+        // Registering on a subnet that doesn't exist creates it with the default parameters.
+        let preview = SubspaceModule::get_registration_preview(5, to_nano(3));
+        assert!(preview.creates_subnet);
+        assert_eq!(preview.replaced_subnet, None);
+        assert_eq!(preview.module_slot, ModuleSlot::Free);
+    });
+}
+
+#[test]
+fn registration_preview_mirrors_subnet_replacement() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        MaxAllowedSubnets::<Test>::set(2);
+        assert_ok!(register_module(0, U256::from(0), to_nano(10)));
+        assert_ok!(register_module(1, U256::from(1), to_nano(5)));
+        MaxAllowedModules::<Test>::set(2);
+
+        // The new subnet needs more stake than the least staked one.
+        let preview = SubspaceModule::get_registration_preview(5, to_nano(3));
+        assert_eq!(
+            preview.error,
+            Some(Error::<Test>::NotEnoughStakeToStartNetwork.into())
+        );
+        assert_eq!(preview.replaced_subnet, None);
+
+        // The modules of the replaced subnet free their global slots.
+        let preview = SubspaceModule::get_registration_preview(5, to_nano(6));
+        assert_eq!(preview.error, None);
+        assert_eq!(preview.replaced_subnet, Some(1));
+        assert_eq!(preview.module_slot, ModuleSlot::Free);
+        assert_eq!(preview.pruned_key, None);
+
+        SubnetAuctionPeriod::<Test>::set(10);
+        let preview = SubspaceModule::get_registration_preview(5, to_nano(6));
+        assert_eq!(
+            preview.error,
+            Some(Error::<Test>::SubnetSlotAuctionRequired.into())
+        );
+        assert_eq!(preview.replaced_subnet, None);
+        SubnetAuctionPeriod::<Test>::set(0);
+
+        assert_ok!(register_module(5, U256::from(5), to_nano(6)));
+        assert_eq!(SubspaceModule::get_netuid_for_name(b"test5"), Some(1));
+        assert!(SubspaceModule::key_registered(0, &U256::from(0)));
+    });
+}
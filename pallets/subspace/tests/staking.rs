@@ -1,10 +1,14 @@
 mod mock;
 
-use frame_support::{assert_noop, assert_ok};
+use frame_support::{assert_err, assert_noop, assert_ok};
 use log::info;
 use mock::*;
-use pallet_subspace::{Error, MaxRegistrationsPerBlock};
+use pallet_subspace::{
+    global::{RateLimitConfiguration, RateLimitedCall},
+    Error, MaxRegistrationsPerBlock, RateLimitConfig, UnbondingPeriodGlobal, UnlockChunk,
+};
 use sp_core::U256;
+use sp_runtime::transaction_validity::{InvalidTransaction, TransactionValidityError};
 use substrate_fixed::types::I64F64;
 
 // /***********************************************************
@@ -432,3 +436,266 @@ fn test_unstake_zero() {
         );
     });
 }
+
+// /***********************************************************
+// 	staking::withdraw_unbonded() tests
+// ************************************************************/
+#[test]
+fn test_remove_stake_goes_through_unbonding() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+        let key = U256::from(0);
+        let stake_amount: u64 = to_nano(1_000);
+        let unbonding_period: u64 = 50;
+        zero_min_burn();
+
+        assert_ok!(register_module(netuid, key, stake_amount));
+        update_params!(netuid => { unbonding_period: unbonding_period });
+
+        let unlock_block = block_number() + unbonding_period;
+        assert_ok!(SubspaceModule::remove_stake(
+            get_origin(key),
+            netuid,
+            key,
+            stake_amount
+        ));
+
+        // the stake left the module, but the balance is still locked
+        assert_eq!(SubspaceModule::get_stake(netuid, &key), 0);
+        assert_eq!(SubspaceModule::get_balance_u64(&key), 1);
+        assert_eq!(
+            SubspaceModule::get_unlocking(netuid, &key),
+            vec![UnlockChunk {
+                amount: stake_amount,
+                unlock_block
+            }]
+        );
+
+        assert_noop!(
+            SubspaceModule::withdraw_unbonded(get_origin(key)),
+            Error::<Test>::NothingToWithdraw
+        );
+
+        step_block(unbonding_period as u16);
+
+        assert_ok!(SubspaceModule::withdraw_unbonded(get_origin(key)));
+        assert_eq!(SubspaceModule::get_balance_u64(&key), stake_amount + 1);
+        assert!(SubspaceModule::get_unlocking(netuid, &key).is_empty());
+    });
+}
+
+#[test]
+fn test_unbonding_global_period_is_a_floor() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+        let key = U256::from(0);
+        zero_min_burn();
+
+        assert_ok!(register_module(netuid, key, to_nano(1_000)));
+        update_params!(netuid => { unbonding_period: 10 });
+        UnbondingPeriodGlobal::<Test>::set(100);
+        assert_eq!(SubspaceModule::get_unbonding_period(netuid), 100);
+
+        UnbondingPeriodGlobal::<Test>::set(5);
+        assert_eq!(SubspaceModule::get_unbonding_period(netuid), 10);
+    });
+}
+
+#[test]
+fn test_transfer_stake_skips_unbonding() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+        let stake_amount: u64 = to_nano(1_000);
+        zero_min_burn();
+
+        register_n_modules(netuid, 2, stake_amount);
+        UnbondingPeriodGlobal::<Test>::set(100);
+
+        let keys: Vec<U256> = SubspaceModule::get_keys(netuid);
+        assert_ok!(SubspaceModule::transfer_stake(
+            get_origin(keys[0]),
+            netuid,
+            keys[0],
+            keys[1],
+            stake_amount,
+        ));
+
+        assert_eq!(SubspaceModule::get_stake(netuid, &keys[0]), 0);
+        assert_eq!(
+            SubspaceModule::get_stake(netuid, &keys[1]),
+            stake_amount * 2
+        );
+        assert!(SubspaceModule::get_unlocking(netuid, &keys[0]).is_empty());
+    });
+}
+
+#[test]
+fn test_deregister_does_not_bypass_unbonding() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+        let stake_amount: u64 = to_nano(1_000);
+        zero_min_burn();
+
+        register_n_modules(netuid, 2, stake_amount);
+        UnbondingPeriodGlobal::<Test>::set(100);
+
+        let key = U256::from(0);
+        assert_ok!(SubspaceModule::deregister(get_origin(key), netuid));
+
+        assert_eq!(SubspaceModule::get_balance_u64(&key), 1);
+        let unlocking = SubspaceModule::get_unlocking(netuid, &key);
+        assert_eq!(unlocking.len(), 1);
+        assert_eq!(unlocking[0].amount, stake_amount);
+    });
+}
+
+#[test]
+fn test_remove_subnet_releases_unlocking() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 1;
+        let stake_amount: u64 = to_nano(1_000);
+        zero_min_burn();
+
+        assert_ok!(register_module(0, U256::from(0), stake_amount));
+        assert_ok!(register_module(netuid, U256::from(1), stake_amount));
+        assert_ok!(register_module(netuid, U256::from(2), stake_amount));
+        update_params!(netuid => { unbonding_period: 100 });
+
+        let key = U256::from(1);
+        remove_stake(netuid, key, stake_amount);
+        assert_eq!(SubspaceModule::get_balance_u64(&key), 1);
+
+        SubspaceModule::remove_subnet(netuid);
+
+        assert_eq!(SubspaceModule::get_balance_u64(&key), stake_amount + 1);
+        assert!(SubspaceModule::get_unlocking(netuid, &key).is_empty());
+    });
+}
+
+#[test]
+fn test_invalid_unbonding_period() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+        zero_min_burn();
+
+        assert_ok!(register_module(netuid, U256::from(0), to_nano(1_000)));
+
+        let params = pallet_subspace::SubnetParams {
+            unbonding_period: pallet_subspace::MAX_UNBONDING_PERIOD + 1,
+            ..SubspaceModule::subnet_params(netuid)
+        };
+        assert_err!(
+            pallet_subspace::subnet::SubnetChangeset::<Test>::update(netuid, params).map(|_| ()),
+            Error::<Test>::InvalidUnbondingPeriod
+        );
+    });
+}
+
+#[test]
+fn staking_calls_are_rate_limited_per_account() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        // The limits are opt-in.
+        assert_eq!(RateLimitConfig::<Test>::get().interval, 0);
+
+        let key = U256::from(0);
+        let other = U256::from(1);
+        assert_ok!(register_module(0, key, to_nano(10)));
+        add_balance(key, to_nano(10));
+        add_balance(other, to_nano(10));
+
+        let add_stake = |amount| -> RuntimeCall {
+            SubspaceCall::add_stake {
+                netuid: 0,
+                module_key: key,
+                amount,
+            }
+            .into()
+        };
+        let remove_stake: RuntimeCall = SubspaceCall::remove_stake {
+            netuid: 0,
+            module_key: key,
+            amount: 1,
+        }
+        .into();
+
+        for _ in 0..20 {
+            assert_eq!(dispatch_signed(key, add_stake(1)), Ok(Ok(())));
+        }
+
+        RateLimitConfig::<Test>::set(RateLimitConfiguration {
+            interval: 10,
+            add_stake: 2,
+            remove_stake: 2,
+            ..Default::default()
+        });
+
+        for _ in 0..2 {
+            assert_eq!(dispatch_signed(key, add_stake(1)), Ok(Ok(())));
+        }
+
+        let rate_limited: TransactionValidityError =
+            InvalidTransaction::Custom(RateLimitedCall::AddStake.error_code()).into();
+        assert_eq!(dispatch_signed(key, add_stake(1)), Err(rate_limited));
+
+        // Other accounts and other kinds of calls have their own counters.
+        assert_eq!(dispatch_signed(key, remove_stake), Ok(Ok(())));
+        assert_eq!(dispatch_signed(other, add_stake(1)), Ok(Ok(())));
+
+        // Failed calls take up the account's calls too.
+        assert_eq!(
+            dispatch_signed(other, add_stake(to_nano(100))),
+            Ok(Err(Error::<Test>::NotEnoughBalanceToStake.into()))
+        );
+        assert_eq!(dispatch_signed(other, add_stake(1)), Err(rate_limited));
+
+        // The counters reset once the interval is over.
+        run_to_block(10);
+        assert_eq!(dispatch_signed(key, add_stake(1)), Ok(Ok(())));
+    });
+}
+
+#[test]
+fn rate_limits_apply_to_batched_calls() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        let key = U256::from(0);
+        assert_ok!(register_module(0, key, to_nano(10)));
+        add_balance(key, to_nano(10));
+
+        RateLimitConfig::<Test>::set(RateLimitConfiguration {
+            interval: 10,
+            add_stake: 2,
+            ..Default::default()
+        });
+
+        let add_stake: RuntimeCall = SubspaceCall::add_stake {
+            netuid: 0,
+            module_key: key,
+            amount: 1,
+        }
+        .into();
+        let stake_before = SubspaceModule::get_stake_to_module(0, &key, &key);
+
+        let batch = |calls| -> RuntimeCall { pallet_utility::Call::batch_all { calls }.into() };
+        assert_eq!(
+            dispatch_signed(key, batch(vec![add_stake.clone(); 3])),
+            Ok(Err(Error::<Test>::RateLimitExceeded.into()))
+        );
+        assert_eq!(
+            SubspaceModule::get_stake_to_module(0, &key, &key),
+            stake_before
+        );
+
+        assert_eq!(
+            dispatch_signed(key, batch(vec![add_stake.clone(); 2])),
+            Ok(Ok(()))
+        );
+        assert_eq!(
+            dispatch_signed(key, batch(vec![add_stake])),
+            Ok(Err(Error::<Test>::RateLimitExceeded.into()))
+        );
+    });
+}
@@ -1,14 +1,15 @@
 mod mock;
 
-use frame_support::assert_ok;
+use frame_support::{assert_err, assert_ok};
 use log::info;
 use mock::*;
 use pallet_governance_api::GovernanceApi;
 use pallet_subspace::{
-    global::BurnConfiguration, AdjustmentAlpha, Burn, BurnConfig, Dividends, Emission,
-    FounderShare, Incentive, MaxAllowedModules, MaxAllowedWeights, MaxRegistrationsPerBlock,
-    MinAllowedWeights, Stake, SubnetStakeThreshold, TargetRegistrationsInterval,
-    TargetRegistrationsPerInterval, Tempo, Trust, N,
+    global::BurnConfiguration, subnet::SubnetChangeset, AdjustmentAlpha, Burn, BurnConfig,
+    Dividends, Emission, Error, FounderShare, Incentive, MaxAllowedModules, MaxAllowedWeights,
+    MaxRegistrationsPerBlock, MinAllowedWeights, RewardHistory, RewardHistoryEnabled,
+    RewardHistoryEpochs, RewardedAccounts, Stake, SubnetConsensus, SubnetStakeThreshold,
+    TargetRegistrationsInterval, TargetRegistrationsPerInterval, Tempo, Trust, N,
 };
 use sp_core::U256;
 use sp_runtime::Percent;
@@ -166,6 +167,177 @@ fn test_dividends_same_stake() {
     });
 }
 
+#[test]
+fn test_reward_history() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+        zero_min_burn();
+
+        register_n_modules(netuid, 4, 10_000);
+        update_params!(netuid => { reward_history: true });
+        update_params(netuid, 1, 4, 0);
+
+        let keys = SubspaceModule::get_keys(netuid);
+        set_weights(netuid, keys[0], vec![2, 3], vec![2, 1]);
+
+        // the mock keeps the last 3 epochs
+        step_epoch(netuid);
+        let first_epoch_block = block_number();
+        step_block(4);
+
+        let uid = SubspaceModule::get_uid_for_key(netuid, &keys[2]) as usize;
+        let records = RewardHistory::<Test>::get(netuid, keys[2]);
+        assert_eq!(records.len(), 3);
+        assert!(records.iter().all(|record| record.block > first_epoch_block));
+        assert!(records.windows(2).all(|pair| pair[0].block < pair[1].block));
+
+        let latest = records.last().unwrap();
+        assert_eq!(latest.block, block_number());
+        assert_eq!(latest.amount, Emission::<Test>::get(netuid)[uid]);
+
+        let history =
+            SubspaceModule::get_reward_history(&keys[2], None, block_number() - 1, u64::MAX);
+        assert_eq!(history.len(), 2);
+        assert!(history.iter().all(|(n, _)| *n == netuid));
+        assert!(SubspaceModule::get_reward_history(&keys[2], Some(1), 0, u64::MAX).is_empty());
+    });
+}
+
+#[test]
+fn test_reward_history_is_opt_in() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+        zero_min_burn();
+
+        register_n_modules(netuid, 4, 10_000);
+        update_params(netuid, 1, 4, 0);
+
+        let keys = SubspaceModule::get_keys(netuid);
+        set_weights(netuid, keys[0], vec![2, 3], vec![2, 1]);
+
+        step_block(2);
+        assert_eq!(RewardHistory::<Test>::iter_prefix(netuid).count(), 0);
+
+        RewardHistoryEnabled::<Test>::insert(netuid, true);
+        step_block(2);
+        assert!(!RewardHistory::<Test>::get(netuid, keys[2]).is_empty());
+
+        // Turning the history off drops the records kept so far.
+        update_params(netuid, 100, 4, 1);
+        update_params!(netuid => { reward_history: false });
+        assert_eq!(RewardHistory::<Test>::iter_prefix(netuid).count(), 0);
+        assert_eq!(RewardedAccounts::<Test>::iter_prefix(netuid).count(), 0);
+        assert!(RewardHistoryEpochs::<Test>::get(netuid).is_empty());
+    });
+}
+
+#[test]
+fn test_reward_history_prunes_accounts_that_stopped_earning() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 0;
+        zero_min_burn();
+
+        register_n_modules(netuid, 1, 10_000);
+        update_params!(netuid => { reward_history: true });
+
+        let (early, late) = (U256::from(100), U256::from(101));
+        System::set_block_number(10);
+        SubspaceModule::record_rewards(netuid, [(early, 5)].into());
+
+        // the mock keeps the last 3 epochs
+        for block in [11, 12] {
+            System::set_block_number(block);
+            SubspaceModule::record_rewards(netuid, [(late, 5)].into());
+        }
+        assert_eq!(RewardHistory::<Test>::get(netuid, early).len(), 1);
+
+        System::set_block_number(13);
+        SubspaceModule::record_rewards(netuid, [(late, 5)].into());
+
+        assert!(!RewardHistory::<Test>::contains_key(netuid, early));
+        assert!(!RewardedAccounts::<Test>::contains_key(netuid, 10));
+        assert_eq!(
+            RewardHistoryEpochs::<Test>::get(netuid).into_inner(),
+            vec![11, 12, 13]
+        );
+        let blocks: Vec<_> = RewardHistory::<Test>::get(netuid, late)
+            .into_iter()
+            .map(|record| record.block)
+            .collect();
+        assert_eq!(blocks, vec![11, 12, 13]);
+    });
+}
+
+#[test]
+fn test_linear_consensus_on_any_subnet() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+        register_n_modules(0, 2, 10_000);
+
+        for consensus in [SubnetConsensus::Yuma, SubnetConsensus::Root] {
+            let params = pallet_subspace::SubnetParams {
+                consensus,
+                ..SubspaceModule::subnet_params(0)
+            };
+            assert_err!(
+                SubnetChangeset::<Test>::update(0, params).map(|_| ()),
+                Error::<Test>::InvalidSubnetConsensus
+            );
+        }
+
+        // a linear subnet other than the global one distributes like it
+        let netuid: u16 = 1;
+        register_n_modules(netuid, 4, 10_000);
+        update_params!(netuid => { tempo: 25, consensus: SubnetConsensus::Linear });
+        update_params(netuid, 25, 4, 0);
+
+        let keys = SubspaceModule::get_keys(netuid);
+        set_weights(netuid, keys[0], vec![2, 3], vec![2, 1]);
+
+        let stakes_before = get_stakes(netuid);
+        step_epoch(netuid);
+
+        let incentives = Incentive::<Test>::get(netuid);
+        let emissions = Emission::<Test>::get(netuid);
+        assert!(incentives[2] > incentives[3]);
+        for (uid, emission) in emissions.iter().enumerate() {
+            assert_eq!(get_stakes(netuid)[uid] - stakes_before[uid], *emission);
+        }
+    });
+}
+
+#[test]
+fn test_global_subnet_only_runs_linear_consensus() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+        let netuid: u16 = 0;
+        register_n_modules(netuid, 2, 10_000);
+
+        // the global subnet funds the treasury, so it only runs the linear consensus
+        for consensus in [
+            SubnetConsensus::Linear,
+            SubnetConsensus::Yuma,
+            SubnetConsensus::Root,
+        ] {
+            let params = pallet_subspace::SubnetParams {
+                consensus,
+                ..SubspaceModule::subnet_params(netuid)
+            };
+            let result = SubnetChangeset::<Test>::update(netuid, params).map(|_| ());
+            match consensus {
+                SubnetConsensus::Linear => assert_ok!(result),
+                SubnetConsensus::Yuma | SubnetConsensus::Root => {
+                    assert_err!(result, Error::<Test>::InvalidSubnetConsensus)
+                }
+            }
+        }
+        assert_eq!(
+            SubspaceModule::get_subnet_consensus(netuid),
+            SubnetConsensus::Linear
+        );
+    });
+}
+
 #[test]
 fn test_dividends_diff_stake() {
     new_test_ext().execute_with(|| {
@@ -1,10 +1,13 @@
 use crate::mock::*;
 use frame_support::assert_ok;
+use pallet_governance_api::GovernanceApi;
 use pallet_subspace::{
-    yuma::{AccountKey, EmissionMap, ModuleKey, YumaCalc},
-    Emission, FloorFounderShare, MaxRegistrationsPerBlock, Stake, UnitEmission, N,
+    yuma::{AccountKey, EmissionMap, ModuleKey, Slash, YumaCalc},
+    Emission, FloorFounderShare, MaxRegistrationsPerBlock, Stake, SubnetConsensus, Tempo,
+    UnitEmission, N,
 };
 use sp_core::U256;
+use sp_runtime::Percent;
 use std::collections::BTreeMap;
 mod mock;
 
@@ -75,7 +78,7 @@ fn test_1_graph() {
             vec![u16::MAX],
         ));
 
-        let emissions = YumaCalc::<Test>::new(netuid, ONE).run();
+        let emissions = YumaCalc::<Test>::new(netuid, ONE).run().map(|output| output.emissions);
         let offset = 1;
 
         assert_eq!(
@@ -153,7 +156,7 @@ fn test_10_graph() {
             ));
         }
 
-        let emissions = YumaCalc::<Test>::new(netuid, ONE).run();
+        let emissions = YumaCalc::<Test>::new(netuid, ONE).run().map(|output| output.emissions);
         let mut expected: EmissionMap<Test> = BTreeMap::new();
 
         // Check return values.
@@ -410,3 +413,258 @@ fn test_tempo_compound() {
         assert!(fast > slow);
     });
 }
+
+#[test]
+fn subnet_consensus_modes_distribute_emission() {
+    for consensus in [
+        SubnetConsensus::Linear,
+        SubnetConsensus::Yuma,
+        SubnetConsensus::Root,
+    ] {
+        new_test_ext().execute_with(|| {
+            zero_min_burn();
+
+            assert_ok!(register_module(0, U256::from(0), to_nano(1_000)));
+
+            let netuid: u16 = 1;
+            let key = U256::from(1);
+            let stake = to_nano(10_000);
+            assert_ok!(register_module(netuid, key, stake));
+            update_params!(netuid => { tempo: 25, consensus: consensus });
+            assert_eq!(SubspaceModule::get_subnet_consensus(netuid), consensus);
+
+            let balance = SubspaceModule::get_balance_u64(&key);
+            step_block(25);
+
+            let stake_gain = Stake::<Test>::get(netuid, key) - stake;
+            let balance_gain = SubspaceModule::get_balance_u64(&key) - balance;
+            assert!(
+                stake_gain + balance_gain > 0,
+                "{consensus:?} emitted nothing"
+            );
+
+            match consensus {
+                SubnetConsensus::Root => {
+                    assert_eq!(stake_gain, 0);
+                    assert!(Emission::<Test>::get(netuid).iter().all(|e| *e == 0));
+                }
+                SubnetConsensus::Linear | SubnetConsensus::Yuma => {
+                    assert!(stake_gain > 0, "{consensus:?} did not compound");
+                    assert!(Emission::<Test>::get(netuid).iter().sum::<u64>() > 0);
+                }
+            }
+        });
+    }
+}
+
+#[test]
+fn test_tempo_compound_in_every_consensus_mode() {
+    for consensus in [
+        SubnetConsensus::Linear,
+        SubnetConsensus::Yuma,
+        SubnetConsensus::Root,
+    ] {
+        new_test_ext().execute_with(|| {
+            const QUICK_TEMPO: u16 = 25;
+            const SLOW_TEMPO: u16 = 1000;
+            // Register the general subnet.
+            let netuid: u16 = 0;
+            let key = U256::from(0);
+            let stake_amount: u64 = to_nano(1_000);
+
+            // Make sure registration cost is not affected
+            zero_min_burn();
+
+            assert_ok!(register_module(netuid, key, stake_amount));
+
+            // Register the subnets, the important part of the tests starts here:
+            // FAST
+            let s_netuid: u16 = 1;
+            let s_key = U256::from(1);
+            let s_amount: u64 = to_nano(10_000);
+
+            assert_ok!(register_module(s_netuid, s_key, s_amount));
+            update_params!(s_netuid => { tempo: SLOW_TEMPO, consensus: consensus });
+
+            // SLOW
+            let f_netuid = 2;
+            // Now an honest actor will come, the goal is for him to accumulate more
+            let f_key = U256::from(3);
+            assert_ok!(register_module(f_netuid, f_key, s_amount));
+            // we will set a slower tempo
+            update_params!(f_netuid => { tempo: QUICK_TEMPO, consensus: consensus });
+            assert_eq!(SubspaceModule::get_subnet_consensus(f_netuid), consensus);
+
+            let f_balance = SubspaceModule::get_balance_u64(&f_key);
+
+            // we will now step, SLOW_TEMPO -> 1000 blocks
+            step_block(SLOW_TEMPO);
+
+            let fast = Stake::<Test>::get(f_netuid, f_key);
+            let slow = Stake::<Test>::get(s_netuid, s_key);
+
+            match consensus {
+                SubnetConsensus::Linear | SubnetConsensus::Yuma => {
+                    // faster tempo should have quicker compound rate
+                    assert!(fast > slow, "{consensus:?} did not compound faster");
+                }
+                SubnetConsensus::Root => {
+                    // the emission is relayed to the founder balance and never compounds
+                    assert_eq!((fast, slow), (s_amount, s_amount));
+                    assert!(SubspaceModule::get_balance_u64(&f_key) > f_balance);
+                    assert!(Emission::<Test>::get(f_netuid).iter().all(|e| *e == 0));
+                }
+            }
+        });
+    }
+}
+
+#[test]
+fn yuma_slashes_validators_out_of_consensus() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+        FloorFounderShare::<Test>::put(0);
+
+        // Register the general subnet.
+        assert_ok!(register_module(0, U256::from(0), to_nano(1)));
+
+        let netuid: u16 = 1;
+        let honest_1 = U256::from(1);
+        let honest_2 = U256::from(2);
+        let dishonest = U256::from(3);
+        let miner = U256::from(4);
+
+        assert_ok!(register_module(netuid, honest_1, to_nano(40)));
+        assert_ok!(register_module(netuid, honest_2, to_nano(40)));
+        assert_ok!(register_module(netuid, dishonest, to_nano(20)));
+        assert_ok!(register_module(netuid, miner, to_nano(1)));
+
+        run_to_block(1);
+
+        let honest_uid = SubspaceModule::get_uid_for_key(netuid, &honest_1);
+        let miner_uid = SubspaceModule::get_uid_for_key(netuid, &miner);
+
+        // Honest validators agree on the miner, the dishonest one votes for a validator.
+        set_weights(netuid, honest_1, vec![miner_uid], vec![1]);
+        set_weights(netuid, honest_2, vec![miner_uid], vec![1]);
+        set_weights(netuid, dishonest, vec![honest_uid], vec![1]);
+
+        // Slashing is disabled by default.
+        let output = YumaCalc::<Test>::new(netuid, ONE).run().unwrap();
+        assert!(output.slashes.is_empty());
+
+        update_params!(netuid => {
+            slash_ratio: Percent::from_percent(10),
+            slash_threshold: Percent::from_percent(50)
+        });
+
+        let dishonest_stake = Stake::<Test>::get(netuid, dishonest);
+        let output = YumaCalc::<Test>::new(netuid, ONE).run().unwrap();
+
+        assert_eq!(
+            output.slashes,
+            vec![Slash {
+                module_key: ModuleKey(dishonest),
+                amount: dishonest_stake / 10,
+            }]
+        );
+    });
+}
+
+#[test]
+fn epoch_slashes_validators_out_of_consensus() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+        FloorFounderShare::<Test>::put(0);
+
+        // Register the general subnet.
+        assert_ok!(register_module(0, U256::from(0), to_nano(1)));
+
+        let netuid: u16 = 1;
+        let honest_1 = U256::from(1);
+        let honest_2 = U256::from(2);
+        let dishonest = U256::from(3);
+        let miner = U256::from(4);
+        let treasury = <Test as GovernanceApi<AccountId>>::get_dao_treasury_address();
+
+        assert_ok!(register_module(netuid, honest_1, to_nano(40)));
+        assert_ok!(register_module(netuid, honest_2, to_nano(40)));
+        assert_ok!(register_module(netuid, dishonest, to_nano(20)));
+        assert_ok!(register_module(netuid, miner, to_nano(1)));
+        update_params!(netuid => {
+            slash_ratio: Percent::from_percent(10),
+            slash_threshold: Percent::from_percent(50)
+        });
+
+        run_to_block(1);
+
+        let honest_uid = SubspaceModule::get_uid_for_key(netuid, &honest_1);
+        let miner_uid = SubspaceModule::get_uid_for_key(netuid, &miner);
+
+        set_weights(netuid, honest_1, vec![miner_uid], vec![1]);
+        set_weights(netuid, honest_2, vec![miner_uid], vec![1]);
+        set_weights(netuid, dishonest, vec![honest_uid], vec![1]);
+
+        // The epoch of the subnet runs on the block before the one its tempo divides, because of
+        // the netuid offset.
+        let epoch_block = Tempo::<Test>::get(netuid) as u64 - 1;
+        run_to_block(epoch_block - 1);
+
+        let dishonest_stake = Stake::<Test>::get(netuid, dishonest);
+        let treasury_balance = SubspaceModule::get_balance_u64(&treasury);
+        step_block(1);
+
+        let slashed = dishonest_stake / 10;
+        let epoch = SubspaceModule::get_epoch_index(netuid, epoch_block);
+        assert_eq!(epoch, 1);
+        System::assert_has_event(
+            pallet_subspace::Event::Slashed(netuid, dishonest, slashed, epoch).into(),
+        );
+        assert_eq!(
+            Stake::<Test>::get(netuid, dishonest),
+            dishonest_stake - slashed
+        );
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&treasury),
+            treasury_balance + slashed
+        );
+    });
+}
+
+#[test]
+fn slash_module_stake_is_proportional_and_sent_to_treasury() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        let netuid: u16 = 0;
+        let module_key = U256::from(0);
+        let delegator = U256::from(1);
+        let treasury = <Test as GovernanceApi<AccountId>>::get_dao_treasury_address();
+
+        assert_ok!(register_module(netuid, module_key, to_nano(30)));
+        add_balance(delegator, to_nano(10) + 1);
+        assert_ok!(SubspaceModule::add_stake(
+            get_origin(delegator),
+            netuid,
+            module_key,
+            to_nano(10)
+        ));
+
+        let treasury_balance = SubspaceModule::get_balance_u64(&treasury);
+        let slashed = SubspaceModule::slash_module_stake(netuid, &module_key, to_nano(4));
+
+        assert_eq!(slashed, to_nano(4));
+        assert_eq!(
+            SubspaceModule::get_stake_to_module(netuid, &module_key, &module_key),
+            to_nano(27)
+        );
+        assert_eq!(
+            SubspaceModule::get_stake_to_module(netuid, &delegator, &module_key),
+            to_nano(9)
+        );
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&treasury),
+            treasury_balance + to_nano(4)
+        );
+    });
+}
@@ -0,0 +1,215 @@
+mod mock;
+
+use frame_support::{assert_err, assert_ok};
+use mock::*;
+use pallet_subspace::{
+    subnet::SubnetChangeset, yuma::YumaCalc, AlphaValues, Bonds, Consensus, Error,
+    FloorFounderShare, LiquidAlphaEnabled, ValidatorTrust, Weights,
+};
+use sp_core::U256;
+
+const ONE: u64 = to_nano(1);
+
+/// Registers an incumbent validator that already holds every bond on the miner and a newcomer
+/// with the same stake. Both agree with consensus and weight only the miner.
+fn setup_incumbent_and_newcomer(netuid: u16) -> (u16, u16, u16) {
+    zero_min_burn();
+    FloorFounderShare::<Test>::put(0);
+
+    // Register the general subnet.
+    assert_ok!(register_module(0, U256::from(0), to_nano(1)));
+
+    let incumbent = U256::from(1);
+    let newcomer = U256::from(2);
+    let miner = U256::from(3);
+
+    assert_ok!(register_module(netuid, incumbent, to_nano(50)));
+    assert_ok!(register_module(netuid, newcomer, to_nano(50)));
+    assert_ok!(register_module(netuid, miner, to_nano(1)));
+
+    run_to_block(1);
+
+    let incumbent_uid = SubspaceModule::get_uid_for_key(netuid, &incumbent);
+    let newcomer_uid = SubspaceModule::get_uid_for_key(netuid, &newcomer);
+    let miner_uid = SubspaceModule::get_uid_for_key(netuid, &miner);
+
+    set_weights(netuid, incumbent, vec![miner_uid], vec![1]);
+    set_weights(netuid, newcomer, vec![miner_uid], vec![1]);
+
+    Bonds::<Test>::insert(netuid, incumbent_uid, vec![(miner_uid, u16::MAX)]);
+
+    (incumbent_uid, newcomer_uid, miner_uid)
+}
+
+fn bond(netuid: u16, uid: u16, miner_uid: u16) -> u16 {
+    Bonds::<Test>::get(netuid, uid)
+        .into_iter()
+        .find(|(j, _)| *j == miner_uid)
+        .map_or(0, |(_, bond)| bond)
+}
+
+#[test]
+fn liquid_alpha_is_disabled_by_default() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 1;
+        let (incumbent_uid, newcomer_uid, miner_uid) = setup_incumbent_and_newcomer(netuid);
+        assert!(!LiquidAlphaEnabled::<Test>::get(netuid));
+
+        YumaCalc::<Test>::new(netuid, ONE).run().unwrap();
+
+        // The default moving average of 0.9 gives the newcomer 0.05 of the bonds against 0.95.
+        assert_eq!(bond(netuid, incumbent_uid, miner_uid), u16::MAX);
+        let newcomer_bond = bond(netuid, newcomer_uid, miner_uid);
+        assert!((3_400..3_500).contains(&newcomer_bond), "{newcomer_bond}");
+    });
+}
+
+#[test]
+fn liquid_alpha_speeds_up_bonds_of_validators_in_consensus() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 1;
+        let (incumbent_uid, newcomer_uid, miner_uid) = setup_incumbent_and_newcomer(netuid);
+
+        update_params!(netuid => {
+            liquid_alpha: true,
+            alpha_low: 6_553,
+            alpha_high: 58_981
+        });
+
+        YumaCalc::<Test>::new(netuid, ONE).run().unwrap();
+
+        // Full agreement with consensus uses the high alpha of 0.9, so the newcomer gets 0.45 of
+        // the bonds against 0.55.
+        assert_eq!(bond(netuid, incumbent_uid, miner_uid), u16::MAX);
+        let newcomer_bond = bond(netuid, newcomer_uid, miner_uid);
+        assert!((53_500..53_700).contains(&newcomer_bond), "{newcomer_bond}");
+    });
+}
+
+#[test]
+fn liquid_alpha_interpolates_partial_agreement() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 1;
+        let (incumbent_uid, newcomer_uid, miner_uid) = setup_incumbent_and_newcomer(netuid);
+
+        // The newcomer splits its weight, so it only gives the miner half of the consensus.
+        Weights::<Test>::insert(
+            netuid,
+            newcomer_uid,
+            vec![(miner_uid, u16::MAX), (incumbent_uid, u16::MAX)],
+        );
+        update_params!(netuid => {
+            liquid_alpha: true,
+            alpha_low: 6_553,
+            alpha_high: 58_981
+        });
+
+        YumaCalc::<Test>::new(netuid, ONE).run().unwrap();
+        assert_eq!(
+            Consensus::<Test>::get(netuid).get(miner_uid as usize),
+            Some(&u16::MAX)
+        );
+
+        // Half the consensus weight gives an alpha of 0.5, halfway between 0.1 and 0.9. The
+        // newcomer holds 1/3 of the bonds delta, so it gets 1/6 of the bonds against the 0.7 of the
+        // incumbent, between the 1/30 of the low alpha and the 0.3 of the high one.
+        assert_eq!(bond(netuid, incumbent_uid, miner_uid), u16::MAX);
+        let newcomer_bond = bond(netuid, newcomer_uid, miner_uid);
+        assert!((15_500..15_700).contains(&newcomer_bond), "{newcomer_bond}");
+    });
+}
+
+#[test]
+fn liquid_alpha_keeps_the_low_alpha_out_of_consensus() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 1;
+        let (incumbent_uid, newcomer_uid, miner_uid) = setup_incumbent_and_newcomer(netuid);
+
+        // The incumbent holds the majority of the stake and never weights itself, so the newcomer
+        // weighting only the incumbent is out of consensus and its old bond on the miner is stale.
+        increase_stake(netuid, U256::from(1), to_nano(50));
+        Weights::<Test>::insert(netuid, newcomer_uid, vec![(incumbent_uid, u16::MAX)]);
+        Bonds::<Test>::insert(netuid, newcomer_uid, vec![(miner_uid, u16::MAX)]);
+        update_params!(netuid => {
+            liquid_alpha: true,
+            alpha_low: 6_553,
+            alpha_high: 58_981
+        });
+
+        YumaCalc::<Test>::new(netuid, ONE).run().unwrap();
+        assert_eq!(
+            ValidatorTrust::<Test>::get(netuid).get(newcomer_uid as usize),
+            Some(&0)
+        );
+
+        // Both start with half of the bonds. The incumbent moves with the high alpha to 0.95, the
+        // newcomer only decays with the low alpha to 0.45 instead of 0.05.
+        assert_eq!(bond(netuid, incumbent_uid, miner_uid), u16::MAX);
+        let newcomer_bond = bond(netuid, newcomer_uid, miner_uid);
+        assert!((30_900..31_200).contains(&newcomer_bond), "{newcomer_bond}");
+    });
+}
+
+#[test]
+fn liquid_alpha_with_equal_bounds_matches_a_fixed_alpha() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 1;
+        let (incumbent_uid, newcomer_uid, miner_uid) = setup_incumbent_and_newcomer(netuid);
+
+        // A moving average of 0 and an alpha of 1 both replace the bonds with the new ones.
+        update_params!(netuid => { bonds_ma: 0 });
+        YumaCalc::<Test>::new(netuid, ONE).run().unwrap();
+        let expected = (
+            bond(netuid, incumbent_uid, miner_uid),
+            bond(netuid, newcomer_uid, miner_uid),
+        );
+        assert_eq!(expected, (u16::MAX, u16::MAX));
+
+        Bonds::<Test>::insert(netuid, incumbent_uid, vec![(miner_uid, u16::MAX)]);
+        Bonds::<Test>::remove(netuid, newcomer_uid);
+        update_params!(netuid => {
+            liquid_alpha: true,
+            alpha_low: u16::MAX,
+            alpha_high: u16::MAX
+        });
+        YumaCalc::<Test>::new(netuid, ONE).run().unwrap();
+        assert_eq!(
+            (
+                bond(netuid, incumbent_uid, miner_uid),
+                bond(netuid, newcomer_uid, miner_uid),
+            ),
+            expected
+        );
+    });
+}
+
+#[test]
+fn liquid_alpha_values_are_validated() {
+    new_test_ext().execute_with(|| {
+        let netuid: u16 = 1;
+        setup_incumbent_and_newcomer(netuid);
+
+        let params = pallet_subspace::SubnetParams {
+            liquid_alpha: true,
+            alpha_low: 40_000,
+            alpha_high: 30_000,
+            ..SubspaceModule::subnet_params(netuid)
+        };
+        assert_err!(
+            SubnetChangeset::<Test>::update(netuid, params).map(|_| ()),
+            Error::<Test>::InvalidAlphaValues
+        );
+
+        update_params!(netuid => {
+            liquid_alpha: true,
+            alpha_low: 30_000,
+            alpha_high: 40_000
+        });
+        assert!(LiquidAlphaEnabled::<Test>::get(netuid));
+        assert_eq!(AlphaValues::<Test>::get(netuid), (30_000, 40_000));
+
+        let params = SubspaceModule::subnet_params(netuid);
+        assert!(params.liquid_alpha);
+        assert_eq!((params.alpha_low, params.alpha_high), (30_000, 40_000));
+    });
+}
@@ -3,10 +3,13 @@ mod mock;
 use frame_support::{assert_err, assert_ok};
 use log::info;
 use mock::*;
+use pallet_governance_api::GovernanceApi;
 use pallet_subspace::{
-    Dividends, Error, FounderShare, MaxAllowedModules, MaxAllowedSubnets, MaxAllowedUids,
-    MaxRegistrationsPerBlock, MaximumSetWeightCallsPerEpoch, SubnetStakeThreshold, Tempo,
-    TotalSubnets, UnitEmission, N,
+    global::SubnetAuctionConfiguration, Dividends, Error, Founder, FounderShare, MaxAllowedModules,
+    MaxAllowedSubnets, MaxAllowedUids, MaxRegistrationsPerBlock, MaximumSetWeightCallsPerEpoch,
+    SubnetAuctionConfig, SubnetAuctionEnd, SubnetAuctionPeriod, SubnetBids, SubnetConsensus,
+    SubnetDeposit, SubnetRegistrationBlock, SubnetStakeThreshold, Tempo, TotalSubnets,
+    UnitEmission, N,
 };
 use sp_core::U256;
 use sp_runtime::Percent;
@@ -238,6 +241,17 @@ fn test_set_max_allowed_uids_shrinking() {
             params.target_registrations_per_interval,
             params.max_registrations_per_interval,
             params.adjustment_alpha,
+            params.unbonding_period,
+            params.slash_ratio,
+            params.slash_threshold,
+            params.commit_reveal_weights,
+            params.weight_reveal_period,
+            params.consensus,
+            params.weight_history,
+            params.reward_history,
+            params.liquid_alpha,
+            params.alpha_low,
+            params.alpha_high,
         );
         let global_params = SubspaceModule::global_params();
         info!("global params {:?}", global_params);
@@ -814,6 +828,17 @@ fn test_update_same_name() {
             params.target_registrations_per_interval,
             params.max_registrations_per_interval,
             params.adjustment_alpha,
+            params.unbonding_period,
+            params.slash_ratio,
+            params.slash_threshold,
+            params.commit_reveal_weights,
+            params.weight_reveal_period,
+            params.consensus,
+            params.weight_history,
+            params.reward_history,
+            params.liquid_alpha,
+            params.alpha_low,
+            params.alpha_high,
         );
         assert_ok!(result);
 
@@ -822,6 +847,81 @@ fn test_update_same_name() {
     });
 }
 
+#[test]
+fn test_founder_cannot_switch_to_root_consensus() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        assert_ok!(register_module(0, U256::from(0), to_nano(1_000)));
+
+        let netuid: u16 = 1;
+        let key = U256::from(1);
+        assert_ok!(register_module(netuid, key, to_nano(100_000)));
+
+        let update = |params: pallet_subspace::SubnetParams<Test>| {
+            SubspaceModule::update_subnet(
+                get_origin(key),
+                netuid,
+                params.founder,
+                params.founder_share,
+                params.immunity_period,
+                params.incentive_ratio,
+                params.max_allowed_uids,
+                params.max_allowed_weights,
+                params.min_allowed_weights,
+                params.max_weight_age,
+                params.min_stake,
+                params.name,
+                params.tempo,
+                params.trust_ratio,
+                params.maximum_set_weight_calls_per_epoch,
+                params.governance_config.vote_mode,
+                params.bonds_ma,
+                params.target_registrations_interval,
+                params.target_registrations_per_interval,
+                params.max_registrations_per_interval,
+                params.adjustment_alpha,
+                params.unbonding_period,
+                params.slash_ratio,
+                params.slash_threshold,
+                params.commit_reveal_weights,
+                params.weight_reveal_period,
+                params.consensus,
+                params.weight_history,
+                params.reward_history,
+                params.liquid_alpha,
+                params.alpha_low,
+                params.alpha_high,
+            )
+        };
+
+        let mut params = SubspaceModule::subnet_params(netuid);
+        params.consensus = SubnetConsensus::Root;
+        assert_err!(
+            update(params.clone()),
+            Error::<Test>::RootConsensusRequiresGovernance
+        );
+        assert_eq!(
+            SubspaceModule::get_subnet_consensus(netuid),
+            SubnetConsensus::Yuma
+        );
+
+        // governance can switch it, and the founder keeps updating the other params
+        update_params!(netuid => { consensus: SubnetConsensus::Root });
+        params.tempo = 30;
+        assert_ok!(update(params));
+        assert_eq!(Tempo::<Test>::get(netuid), 30);
+
+        let mut params = SubspaceModule::subnet_params(netuid);
+        params.consensus = SubnetConsensus::Linear;
+        assert_ok!(update(params));
+        assert_eq!(
+            SubspaceModule::get_subnet_consensus(netuid),
+            SubnetConsensus::Linear
+        );
+    });
+}
+
 #[test]
 fn test_set_weight_rate_limiting() {
     new_test_ext().execute_with(|| {
@@ -861,3 +961,210 @@ fn test_set_weight_rate_limiting() {
         assert_ok!(set_weights());
     });
 }
+
+#[test]
+fn test_subnet_metagraph_paging() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        let netuid = 0;
+        register_n_modules(netuid, 5, to_nano(10));
+
+        let page = SubspaceModule::get_subnet_metagraph(netuid, 1, 2);
+        assert_eq!(page.len(), 2);
+        for (module, uid) in page.iter().zip(1..) {
+            assert_eq!(module.uid, uid);
+            assert_eq!(module.key, U256::from(uid));
+            assert_eq!(module.name, format!("module{uid}").into_bytes());
+            assert_eq!(module.stake, get_stake_for_uid(netuid, uid));
+        }
+
+        // Pages are clamped to the number of modules in the subnet.
+        let last_page = SubspaceModule::get_subnet_metagraph(netuid, 4, 10);
+        assert_eq!(last_page.len(), 1);
+        assert_eq!(last_page[0].uid, 4);
+
+        assert!(SubspaceModule::get_subnet_metagraph(netuid, 5, 10).is_empty());
+        assert_eq!(
+            SubspaceModule::get_subnet_metagraph(netuid, 0, u16::MAX).len(),
+            5
+        );
+    });
+}
+
+#[test]
+fn subnet_slot_auction_replaces_least_staked_subnet() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+        step_block(1);
+
+        MaxAllowedSubnets::<Test>::set(3);
+        assert_ok!(register_module(0, U256::from(0), to_nano(10)));
+        assert_ok!(register_module(1, U256::from(1), to_nano(5)));
+        assert_ok!(register_module(2, U256::from(2), to_nano(20)));
+
+        let bid = |key: u32, name: &str, amount: u64| {
+            SubspaceModule::bid_subnet_slot(
+                get_origin(U256::from(key)),
+                name.as_bytes().to_vec().try_into().unwrap(),
+                amount,
+            )
+        };
+
+        assert_err!(
+            bid(10, "alpha", to_nano(30)),
+            Error::<Test>::SubnetAuctionDisabled
+        );
+        SubnetAuctionPeriod::<Test>::set(10);
+        SubnetAuctionConfig::<Test>::set(SubnetAuctionConfiguration {
+            min_bid: to_nano(20),
+            max_bids: 2,
+            immunity_period: 5,
+        });
+
+        // Subnets can no longer be replaced by registering with more stake.
+        assert_err!(
+            register_module(3, U256::from(3), to_nano(100)),
+            Error::<Test>::SubnetSlotAuctionRequired
+        );
+
+        add_balance(U256::from(10), to_nano(100));
+        add_balance(U256::from(11), to_nano(100));
+
+        assert_err!(
+            bid(10, "alpha", to_nano(10)),
+            Error::<Test>::SubnetBidBelowMinimum
+        );
+        assert_ok!(bid(10, "alpha", to_nano(30)));
+        let auction_end = SubnetAuctionEnd::<Test>::get().unwrap();
+        assert_eq!(auction_end, block_number() + 10);
+        assert_ok!(bid(11, "beta", to_nano(40)));
+        assert_err!(
+            bid(12, "gamma", to_nano(60)),
+            Error::<Test>::TooManySubnetBids
+        );
+        assert_err!(
+            bid(10, "alpha", to_nano(30)),
+            Error::<Test>::SubnetBidTooLow
+        );
+        assert_ok!(bid(10, "alpha", to_nano(50)));
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&U256::from(10)),
+            to_nano(50)
+        );
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&U256::from(11)),
+            to_nano(60)
+        );
+
+        run_to_block(auction_end);
+
+        // The highest bid takes the slot of the least staked subnet.
+        assert_eq!(SubspaceModule::get_netuid_for_name(b"alpha"), Some(1));
+        assert_eq!(Founder::<Test>::get(1), U256::from(10));
+        assert_eq!(N::<Test>::get(1), 0);
+        assert_eq!(TotalSubnets::<Test>::get(), 3);
+        assert_eq!(
+            SubnetDeposit::<Test>::get(1),
+            Some((U256::from(10), to_nano(50)))
+        );
+        assert_eq!(SubnetBids::<Test>::iter().count(), 0);
+        assert_eq!(SubnetAuctionEnd::<Test>::get(), None);
+        System::assert_has_event(
+            pallet_subspace::Event::SubnetAuctionWon(1, U256::from(10), to_nano(50)).into(),
+        );
+
+        // The losing bid is refunded.
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&U256::from(11)),
+            to_nano(100)
+        );
+        System::assert_has_event(
+            pallet_subspace::Event::SubnetBidRefunded(U256::from(11), to_nano(40)).into(),
+        );
+
+        // The deposit is returned once the founder removes the subnet in good standing.
+        assert_ok!(SubspaceModule::register(
+            get_origin(U256::from(10)),
+            b"alpha".to_vec(),
+            b"founder".to_vec(),
+            b"0.0.0.0:30333".to_vec(),
+            to_nano(10),
+            U256::from(10),
+            None,
+        ));
+        assert_ok!(SubspaceModule::deregister(get_origin(U256::from(10)), 1));
+        assert!(!SubspaceModule::if_subnet_exist(1));
+        assert_eq!(SubnetDeposit::<Test>::get(1), None);
+        System::assert_has_event(
+            pallet_subspace::Event::SubnetDepositReturned(1, U256::from(10), to_nano(50)).into(),
+        );
+    });
+}
+
+#[test]
+fn subnet_slot_auction_skips_immune_subnets_and_forfeits_to_treasury() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+        step_block(1);
+
+        MaxAllowedSubnets::<Test>::set(3);
+        assert_ok!(register_module(0, U256::from(0), to_nano(10)));
+        assert_ok!(register_module(1, U256::from(1), to_nano(5)));
+        assert_ok!(register_module(2, U256::from(2), to_nano(20)));
+
+        SubnetAuctionPeriod::<Test>::set(10);
+        SubnetAuctionConfig::<Test>::set(SubnetAuctionConfiguration {
+            min_bid: to_nano(10),
+            max_bids: 8,
+            immunity_period: 100,
+        });
+        for key in 10..13 {
+            add_balance(U256::from(key), to_nano(100));
+        }
+
+        let bid = |key: u32, name: &str, amount: u64| {
+            assert_ok!(SubspaceModule::bid_subnet_slot(
+                get_origin(U256::from(key)),
+                name.as_bytes().to_vec().try_into().unwrap(),
+                amount,
+            ));
+            run_to_block(SubnetAuctionEnd::<Test>::get().unwrap());
+        };
+
+        // Every subnet is still immune, so the winner is refunded.
+        bid(10, "alpha", to_nano(30));
+        assert_eq!(SubspaceModule::get_netuid_for_name(b"alpha"), None);
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&U256::from(10)),
+            to_nano(100)
+        );
+
+        run_to_block(block_number() + 100);
+        bid(10, "alpha", to_nano(30));
+        assert_eq!(SubspaceModule::get_netuid_for_name(b"alpha"), Some(1));
+        assert_eq!(SubnetRegistrationBlock::<Test>::get(1), block_number());
+
+        // The new subnet has no modules yet, but it is immune.
+        bid(11, "beta", to_nano(40));
+        assert_eq!(SubspaceModule::get_netuid_for_name(b"alpha"), Some(1));
+        assert_eq!(SubspaceModule::get_netuid_for_name(b"beta"), Some(2));
+
+        // Once the immunity is over, the empty subnet loses its slot and the deposit goes to the
+        // treasury.
+        run_to_block(block_number() + 100);
+        let treasury = Test::get_dao_treasury_address();
+        let treasury_balance = SubspaceModule::get_balance_u64(&treasury);
+        bid(12, "gamma", to_nano(40));
+        assert_eq!(SubspaceModule::get_netuid_for_name(b"gamma"), Some(1));
+        assert_eq!(SubspaceModule::get_netuid_for_name(b"alpha"), None);
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&U256::from(10)),
+            to_nano(70)
+        );
+        assert!(SubspaceModule::get_balance_u64(&treasury) >= treasury_balance + to_nano(30));
+        System::assert_has_event(
+            pallet_subspace::Event::SubnetDepositForfeited(1, U256::from(10), to_nano(30)).into(),
+        );
+    });
+}
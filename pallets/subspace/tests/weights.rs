@@ -1,8 +1,14 @@
 mod mock;
 use frame_support::{assert_err, assert_ok};
-use pallet_subspace::{Error, FloorFounderShare, MaxRegistrationsPerBlock, N};
+use pallet_governance_api::GovernanceApi;
+use pallet_subspace::{
+    global::FeeConfiguration, Dividends, Error, FeeConfig, FloorFounderShare,
+    MaxRegistrationsPerBlock, MaximumSetWeightCallsPerEpoch, MinWeightStake,
+    SetWeightCallsPerEpoch, SubnetStakeThreshold, ValidatorHistory, ValidatorTrust, WeightCommits,
+    WeightHistory, Weights, N,
+};
 use sp_core::U256;
-use sp_runtime::DispatchError;
+use sp_runtime::{transaction_validity::InvalidTransaction, DispatchError, Percent};
 
 use mock::*;
 
@@ -336,3 +342,544 @@ fn test_weight_age() {
         );
     });
 }
+
+/***************************
+  commit-reveal tests
+*****************************/
+
+fn register_commit_reveal_validators(netuid: u16) {
+    zero_min_burn();
+    assert_ok!(register_module(netuid, U256::from(0), to_nano(10)));
+    assert_ok!(register_module(netuid, U256::from(1), to_nano(10)));
+}
+
+#[test]
+fn test_commit_reveal_weights() {
+    new_test_ext().execute_with(|| {
+        let netuid = 0;
+        let validator = U256::from(1);
+        let (uids, values, salt) = (vec![0], vec![1], b"salt".to_vec());
+        register_commit_reveal_validators(netuid);
+
+        // Plaintext weights are dropped once commit-reveal is turned on.
+        set_weights(netuid, validator, uids.clone(), values.clone());
+        update_params!(netuid => { commit_reveal_weights: true });
+        assert!(Weights::<Test>::get(netuid, 1).is_empty());
+
+        assert_err!(
+            SubspaceModule::set_weights(
+                get_origin(validator),
+                netuid,
+                uids.clone(),
+                values.clone()
+            ),
+            Error::<Test>::CommitRevealEnabled
+        );
+        assert_err!(
+            SubspaceModule::reveal_weights(
+                get_origin(validator),
+                netuid,
+                uids.clone(),
+                values.clone(),
+                salt.clone()
+            ),
+            Error::<Test>::NoWeightCommit
+        );
+
+        let hash = SubspaceModule::hash_weights(&validator, netuid, &uids, &values, &salt);
+        assert_ok!(SubspaceModule::commit_weights(
+            get_origin(validator),
+            netuid,
+            hash
+        ));
+        assert!(Weights::<Test>::get(netuid, 1).is_empty());
+
+        step_epoch(netuid);
+
+        assert_err!(
+            SubspaceModule::reveal_weights(
+                get_origin(validator),
+                netuid,
+                uids.clone(),
+                values.clone(),
+                b"other salt".to_vec()
+            ),
+            Error::<Test>::InvalidWeightReveal
+        );
+
+        assert_ok!(SubspaceModule::reveal_weights(
+            get_origin(validator),
+            netuid,
+            uids,
+            values,
+            salt
+        ));
+        assert_eq!(Weights::<Test>::get(netuid, 1), vec![(0, u16::MAX)]);
+        assert!(WeightCommits::<Test>::get(netuid, validator).is_none());
+    });
+}
+
+#[test]
+fn test_reveal_weights_after_window_fails() {
+    new_test_ext().execute_with(|| {
+        let netuid = 0;
+        let validator = U256::from(1);
+        let (uids, values, salt) = (vec![0], vec![1], b"salt".to_vec());
+        register_commit_reveal_validators(netuid);
+
+        assert_err!(
+            SubspaceModule::commit_weights(get_origin(validator), netuid, Default::default()),
+            Error::<Test>::CommitRevealDisabled
+        );
+
+        update_params!(netuid => {
+            tempo: 25,
+            commit_reveal_weights: true,
+            weight_reveal_period: 1
+        });
+
+        let hash = SubspaceModule::hash_weights(&validator, netuid, &uids, &values, &salt);
+        assert_ok!(SubspaceModule::commit_weights(
+            get_origin(validator),
+            netuid,
+            hash
+        ));
+
+        // The commit can be revealed during the epoch after its own, until block 50.
+        step_block(50);
+
+        assert_err!(
+            SubspaceModule::reveal_weights(get_origin(validator), netuid, uids, values, salt),
+            Error::<Test>::WeightRevealExpired
+        );
+    });
+}
+
+#[test]
+fn test_reveal_weights_in_commit_epoch_fails() {
+    new_test_ext().execute_with(|| {
+        let netuid = 0;
+        let validator = U256::from(1);
+        let (uids, values, salt) = (vec![0], vec![1], b"salt".to_vec());
+        register_commit_reveal_validators(netuid);
+
+        update_params!(netuid => {
+            tempo: 25,
+            commit_reveal_weights: true,
+            weight_reveal_period: 1
+        });
+
+        let hash = SubspaceModule::hash_weights(&validator, netuid, &uids, &values, &salt);
+        assert_ok!(SubspaceModule::commit_weights(
+            get_origin(validator),
+            netuid,
+            hash
+        ));
+
+        // Last block of the epoch the weights were committed in.
+        step_block(24);
+        assert_err!(
+            SubspaceModule::reveal_weights(
+                get_origin(validator),
+                netuid,
+                uids.clone(),
+                values.clone(),
+                salt.clone()
+            ),
+            Error::<Test>::WeightRevealTooEarly
+        );
+        assert!(WeightCommits::<Test>::get(netuid, validator).is_some());
+
+        step_block(1);
+        assert_ok!(SubspaceModule::reveal_weights(
+            get_origin(validator),
+            netuid,
+            uids,
+            values,
+            salt
+        ));
+        assert_eq!(Weights::<Test>::get(netuid, 1), vec![(0, u16::MAX)]);
+    });
+}
+
+#[test]
+fn test_copied_weight_commit_cannot_be_revealed() {
+    new_test_ext().execute_with(|| {
+        let netuid = 0;
+        let validator = U256::from(1);
+        let copier = U256::from(2);
+        let (uids, values, salt) = (vec![0], vec![1], b"salt".to_vec());
+        register_commit_reveal_validators(netuid);
+        assert_ok!(register_module(netuid, copier, to_nano(10)));
+        update_params!(netuid => { commit_reveal_weights: true });
+
+        let hash = SubspaceModule::hash_weights(&validator, netuid, &uids, &values, &salt);
+        assert_ok!(SubspaceModule::commit_weights(
+            get_origin(validator),
+            netuid,
+            hash
+        ));
+        assert_ok!(SubspaceModule::commit_weights(
+            get_origin(copier),
+            netuid,
+            hash
+        ));
+
+        step_epoch(netuid);
+
+        assert_ok!(SubspaceModule::reveal_weights(
+            get_origin(validator),
+            netuid,
+            uids.clone(),
+            values.clone(),
+            salt.clone()
+        ));
+        // Replaying the revealed preimage doesn't match the copied commit of another key.
+        assert_err!(
+            SubspaceModule::reveal_weights(get_origin(copier), netuid, uids, values, salt),
+            Error::<Test>::InvalidWeightReveal
+        );
+        assert!(WeightCommits::<Test>::get(netuid, copier).is_some());
+    });
+}
+
+#[test]
+fn test_set_weights_multiple_reports_failed_entries() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        zero_min_burn();
+
+        let validator = U256::from(1);
+        for netuid in 0..2 {
+            assert_ok!(register_module(netuid, U256::from(0), to_nano(10)));
+            assert_ok!(register_module(netuid, validator, to_nano(10)));
+        }
+
+        assert_err!(
+            SubspaceModule::set_weights_multiple(get_origin(validator), vec![]),
+            Error::<Test>::EmptyWeightsBatch
+        );
+
+        assert_err!(
+            SubspaceModule::set_weights_multiple(
+                get_origin(validator),
+                vec![(2, vec![0], vec![1])]
+            ),
+            Error::<Test>::NoWeightsSet
+        );
+
+        assert_ok!(SubspaceModule::set_weights_multiple(
+            get_origin(validator),
+            vec![
+                (0, vec![0], vec![1]),
+                (1, vec![0, 0], vec![1, 1]),
+                (2, vec![0], vec![1]),
+            ]
+        ));
+
+        assert_eq!(Weights::<Test>::get(0, 1), vec![(0, u16::MAX)]);
+        assert!(Weights::<Test>::get(1, 1).is_empty());
+
+        System::assert_last_event(
+            pallet_subspace::Event::WeightsSetMultiple(
+                validator,
+                vec![0],
+                vec![
+                    (1, Error::<Test>::DuplicateUids.into()),
+                    (2, Error::<Test>::NetworkDoesNotExist.into()),
+                ],
+            )
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn weight_history_keeps_recent_weights_and_epochs() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        let netuid = 0;
+        register_n_modules(netuid, 3, to_nano(10));
+
+        // Nothing is kept before the subnet opts in.
+        set_weights(netuid, U256::from(0), vec![1, 2], vec![1, 1]);
+        assert!(WeightHistory::<Test>::get(netuid, 0).is_empty());
+
+        update_params!(netuid => { weight_history: true });
+
+        let mut expected = Vec::new();
+        for value in 1..=4 {
+            step_block(1);
+            set_weights(netuid, U256::from(0), vec![1, 2], vec![value, 1]);
+            expected.push((block_number(), Weights::<Test>::get(netuid, 0)));
+        }
+        set_weights(netuid, U256::from(2), vec![0, 1], vec![1, 1]);
+
+        // Only the last `WeightHistoryDepth` weight vectors are kept.
+        let (weights, epochs) = SubspaceModule::get_weight_history(netuid, 0);
+        let weights: Vec<_> = weights.into_iter().map(|r| (r.block, r.weights)).collect();
+        assert_eq!(weights, expected[1..]);
+        assert!(epochs.is_empty());
+
+        step_epoch(netuid);
+
+        let epochs = ValidatorHistory::<Test>::get(netuid, 0);
+        assert_eq!(epochs.len(), 1);
+        assert_eq!(epochs[0].dividends, Dividends::<Test>::get(netuid)[0]);
+        assert_eq!(
+            epochs[0].validator_trust,
+            ValidatorTrust::<Test>::get(netuid)[0]
+        );
+        assert!(ValidatorHistory::<Test>::get(netuid, 1).is_empty());
+
+        // The history follows the module that takes over the uid of a removed one.
+        let moved = WeightHistory::<Test>::get(netuid, 2);
+        assert_ok!(SubspaceModule::deregister(
+            get_origin(U256::from(0)),
+            netuid
+        ));
+        assert_eq!(WeightHistory::<Test>::get(netuid, 0), moved);
+        assert!(WeightHistory::<Test>::get(netuid, 2).is_empty());
+        assert!(ValidatorHistory::<Test>::get(netuid, 2).is_empty());
+
+        // Opting out clears the history.
+        update_params!(netuid => { weight_history: false });
+        assert!(WeightHistory::<Test>::get(netuid, 0).is_empty());
+        assert!(ValidatorHistory::<Test>::get(netuid, 0).is_empty());
+    });
+}
+
+#[test]
+fn weight_history_skips_epochs_without_consensus() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        assert_ok!(register_module(0, U256::from(100), to_nano(1_000)));
+
+        let netuid = 1;
+        register_n_modules(netuid, 3, to_nano(10));
+        update_params!(netuid => { weight_history: true });
+        set_weights(netuid, U256::from(0), vec![1, 2], vec![1, 1]);
+
+        // Below the stake threshold yuma does not run, so there is no epoch to record.
+        SubnetStakeThreshold::<Test>::set(Percent::from_percent(50));
+        step_epoch(netuid);
+        assert!(ValidatorHistory::<Test>::get(netuid, 0).is_empty());
+
+        SubnetStakeThreshold::<Test>::set(Percent::from_percent(0));
+        step_epoch(netuid);
+        let epochs = ValidatorHistory::<Test>::get(netuid, 0);
+        assert_eq!(epochs.len(), 1);
+        assert_eq!(epochs[0].dividends, Dividends::<Test>::get(netuid)[0]);
+    });
+}
+
+#[test]
+fn signed_extension_fees_are_refunded_or_sent_to_treasury() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        let netuid = 0;
+        register_n_modules(netuid, 3, to_nano(10));
+
+        FeeConfig::<Test>::set(FeeConfiguration {
+            set_weights_fee: 100,
+            transfer_multiple_fee: 50,
+            ..Default::default()
+        });
+
+        let key = U256::from(0);
+        let treasury = <Test as GovernanceApi<AccountId>>::get_dao_treasury_address();
+        add_balance(key, 1_000);
+        let balance = SubspaceModule::get_balance_u64(&key);
+        let treasury_balance = SubspaceModule::get_balance_u64(&treasury);
+
+        let set_weights_call = |values: Vec<u16>| -> RuntimeCall {
+            SubspaceCall::set_weights {
+                netuid,
+                uids: vec![1, 2],
+                weights: values,
+            }
+            .into()
+        };
+
+        // Weights that get set have their fee refunded.
+        assert_eq!(
+            dispatch_signed(key, set_weights_call(vec![1, 1])),
+            Ok(Ok(()))
+        );
+        assert_eq!(SubspaceModule::get_balance_u64(&key), balance);
+        assert_eq!(SubspaceModule::get_balance_u64(&treasury), treasury_balance);
+
+        // A failed call pays the fee to the treasury.
+        assert_eq!(
+            dispatch_signed(key, set_weights_call(vec![1])),
+            Ok(Err(Error::<Test>::WeightVecNotEqualSize.into()))
+        );
+        assert_eq!(SubspaceModule::get_balance_u64(&key), balance - 100);
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&treasury),
+            treasury_balance + 100
+        );
+
+        // Other calls with a fee always pay it.
+        let transfer: RuntimeCall = SubspaceCall::transfer_multiple {
+            destinations: vec![U256::from(1)],
+            amounts: vec![10],
+        }
+        .into();
+        assert_eq!(dispatch_signed(key, transfer), Ok(Ok(())));
+        assert_eq!(SubspaceModule::get_balance_u64(&key), balance - 160);
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&treasury),
+            treasury_balance + 150
+        );
+
+        // Accounts that can't pay the fee can't submit the call.
+        assert_eq!(
+            dispatch_signed(U256::from(99), set_weights_call(vec![1, 1])),
+            Err(InvalidTransaction::Payment.into())
+        );
+    });
+}
+
+#[test]
+fn set_weights_multiple_pays_the_fee_of_failed_entries() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        let netuid = 0;
+        register_n_modules(netuid, 3, to_nano(10));
+
+        FeeConfig::<Test>::set(FeeConfiguration {
+            set_weights_fee: 100,
+            ..Default::default()
+        });
+
+        let key = U256::from(0);
+        let treasury = <Test as GovernanceApi<AccountId>>::get_dao_treasury_address();
+        add_balance(key, 1_000);
+        let balance = SubspaceModule::get_balance_u64(&key);
+        let treasury_balance = SubspaceModule::get_balance_u64(&treasury);
+
+        let set_weights_multiple = |netuids: Vec<u16>| -> RuntimeCall {
+            SubspaceCall::set_weights_multiple {
+                weights: netuids
+                    .into_iter()
+                    .map(|netuid| (netuid, vec![1, 2], vec![1, 1]))
+                    .collect(),
+            }
+            .into()
+        };
+
+        // Only the entry on the missing subnet pays.
+        assert_eq!(
+            dispatch_signed(key, set_weights_multiple(vec![netuid, 5])),
+            Ok(Ok(()))
+        );
+        assert_eq!(SubspaceModule::get_balance_u64(&key), balance - 100);
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&treasury),
+            treasury_balance + 100
+        );
+
+        // A batch without a single weight set fails and pays for every entry.
+        assert_eq!(
+            dispatch_signed(key, set_weights_multiple(vec![5, 6])),
+            Ok(Err(Error::<Test>::NoWeightsSet.into()))
+        );
+        assert_eq!(SubspaceModule::get_balance_u64(&key), balance - 300);
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&treasury),
+            treasury_balance + 300
+        );
+    });
+}
+
+#[test]
+fn call_fees_are_charged_inside_batches() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        let netuid = 0;
+        register_n_modules(netuid, 3, to_nano(10));
+
+        FeeConfig::<Test>::set(FeeConfiguration {
+            set_weights_fee: 100,
+            refund_set_weights: false,
+            transfer_multiple_fee: 50,
+            ..Default::default()
+        });
+
+        let key = U256::from(0);
+        let treasury = <Test as GovernanceApi<AccountId>>::get_dao_treasury_address();
+        add_balance(key, to_nano(10));
+        let treasury_balance = SubspaceModule::get_balance_u64(&treasury);
+
+        let transfer: RuntimeCall = SubspaceCall::transfer_multiple {
+            destinations: vec![U256::from(1)],
+            amounts: vec![10],
+        }
+        .into();
+        let set_weights: RuntimeCall = SubspaceCall::set_weights {
+            netuid,
+            uids: vec![1, 2],
+            weights: vec![1, 1],
+        }
+        .into();
+        let batch: RuntimeCall = pallet_utility::Call::batch_all {
+            calls: vec![transfer.clone(), transfer, set_weights],
+        }
+        .into();
+
+        assert_eq!(dispatch_signed(key, batch), Ok(Ok(())));
+        assert_eq!(
+            SubspaceModule::get_balance_u64(&treasury),
+            treasury_balance + 200
+        );
+    });
+}
+
+#[test]
+fn validate_weights_explains_failures_without_writing() {
+    new_test_ext().execute_with(|| {
+        zero_min_burn();
+
+        let netuid = 0;
+        let key = U256::from(0);
+        register_n_modules(netuid, 3, to_nano(10));
+        MaximumSetWeightCallsPerEpoch::<Test>::insert(netuid, 5);
+        MinWeightStake::<Test>::set(to_nano(6));
+
+        let validation = SubspaceModule::validate_weights(netuid, &key, vec![1, 2], vec![1, 3]);
+        assert_eq!(
+            validation.error,
+            Some(Error::<Test>::NotEnoughStakePerWeight.into())
+        );
+        assert_eq!(validation.min_stake_for_weights, to_nano(12));
+        assert_eq!(validation.stake, to_nano(10));
+        assert_eq!(validation.max_allowed_weights, 3);
+        assert_eq!(validation.max_set_weight_calls, 5);
+        assert!(validation.weights.is_empty());
+
+        MinWeightStake::<Test>::set(to_nano(5));
+
+        let validation = SubspaceModule::validate_weights(netuid, &key, vec![1, 2], vec![1, 3]);
+        assert_eq!(validation.error, None);
+        assert_eq!(validation.weights, vec![(1, 16_383), (2, 49_151)]);
+
+        // Nothing is written, not even the call counter.
+        assert!(Weights::<Test>::get(netuid, 0).is_empty());
+        assert_eq!(SetWeightCallsPerEpoch::<Test>::get(netuid, key), 0);
+
+        // The dry run agrees with the call.
+        set_weights(netuid, key, vec![1, 2], vec![1, 3]);
+        assert_eq!(Weights::<Test>::get(netuid, 0), validation.weights);
+        assert_eq!(SetWeightCallsPerEpoch::<Test>::get(netuid, key), 1);
+        assert_eq!(
+            SubspaceModule::validate_weights(netuid, &key, vec![0, 1], vec![1, 1]).error,
+            Some(Error::<Test>::NoSelfWeight.into())
+        );
+    });
+}
@@ -11,6 +11,7 @@ use frame_support::{
     genesis_builder_helper::{build_config, create_default_config},
     pallet_prelude::Get,
 };
+use governance_runtime_api::{ProposalInfo, ProposalKind, ProposalStatus, ProposalTally};
 use pallet_aura::MinimumPeriodTimesTwo;
 use pallet_governance::{Curator, GeneralSubnetApplicationCost};
 use pallet_governance_api::GovernanceConfiguration;
@@ -32,7 +33,11 @@ use sp_runtime::{
 };
 use sp_std::prelude::*;
 use sp_version::RuntimeVersion;
-use subspace_runtime_api::{ModuleInfo, ModuleParams, ModuleStats};
+use subspace_runtime_api::{
+    MetagraphModule, ModuleInfo, ModuleParams, ModuleStats, PrunedModule, RegistrationPreview,
+    RewardRecord, SubnetMetagraph, ValidatorEpochRecord, WeightHistory, WeightRecord,
+    WeightsValidation,
+};
 
 #[cfg(feature = "std")]
 use sp_version::NativeVersion;
@@ -111,8 +116,13 @@ pub mod opaque {
 }
 
 pub type Migrations = (
+    // Imports the governance storage still held by subspace straight into the current layout, so
+    // it has to run ahead of `MigrationV1`.
     pallet_governance::migrations::InitialMigration<Runtime>,
     pallet_subspace::migrations::v11::MigrateToV11<Runtime>,
+    pallet_subspace::migrations::v12::MigrateToV12<Runtime>,
+    // Reads the migrated burn configuration, so it has to run after the subspace migrations.
+    pallet_governance::migrations::MigrationV1<Runtime>,
 );
 
 // To learn more about runtime versioning, see:
@@ -127,10 +137,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     //   `spec_version`, and `authoring_version` are the same between Wasm and native.
     // This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
     //   the compatible custom types.
-    spec_version: 117,
+    spec_version: 118,
     impl_version: 1,
     apis: RUNTIME_API_VERSIONS,
-    transaction_version: 1,
+    transaction_version: 2,
     state_version: 1,
 };
 
@@ -339,6 +349,8 @@ parameter_types! {
     pub const DepositFactor: Balance = (0) as Balance * 2_000 * 10_000 + (32 as Balance) * 100 * 10_000;
     pub const MaxSignatories: u32 = 100;
     pub const SubspacePalletId: PalletId = PalletId(*b"py/subsp");
+    pub const RewardHistoryDepth: u32 = 64;
+    pub const WeightHistoryDepth: u32 = 32;
 }
 
 impl pallet_multisig::Config for Runtime {
@@ -363,12 +375,68 @@ impl pallet_subspace::Config for Runtime {
     type Currency = Balances;
     type PalletId = SubspacePalletId;
     type WeightInfo = pallet_subspace::weights::SubstrateWeight<Runtime>;
+    type RewardHistoryDepth = RewardHistoryDepth;
+    type WeightHistoryDepth = WeightHistoryDepth;
+}
+
+pub struct GovernanceCallStakeThreshold;
+
+impl pallet_governance::CallStakeThreshold<RuntimeCall> for GovernanceCallStakeThreshold {
+    fn required_stake(call: &RuntimeCall) -> Percent {
+        let threshold = match call {
+            // Changes to the chain itself need the broadest support.
+            RuntimeCall::System(_) | RuntimeCall::Sudo(_) => Percent::from_percent(75),
+            RuntimeCall::SubspaceModule(_) | RuntimeCall::GovernanceModule(_) => {
+                Percent::from_percent(50)
+            }
+            _ => Percent::from_percent(60),
+        };
+
+        // Wrapped calls can't lower the threshold of the calls they dispatch.
+        let inner: &[RuntimeCall] = match call {
+            RuntimeCall::Utility(
+                pallet_utility::Call::batch { calls }
+                | pallet_utility::Call::batch_all { calls }
+                | pallet_utility::Call::force_batch { calls },
+            ) => calls,
+            RuntimeCall::Utility(
+                pallet_utility::Call::as_derivative { call, .. }
+                | pallet_utility::Call::dispatch_as { call, .. }
+                | pallet_utility::Call::with_weight { call, .. },
+            )
+            | RuntimeCall::Multisig(
+                pallet_multisig::Call::as_multi { call, .. }
+                | pallet_multisig::Call::as_multi_threshold_1 { call, .. },
+            ) => sp_std::slice::from_ref(call.as_ref()),
+            _ => &[],
+        };
+
+        inner.iter().map(Self::required_stake).fold(threshold, sp_std::cmp::max)
+    }
+}
+
+/// Calls accepted proposals dispatch as root. Runtime upgrades are only authorized by their code
+/// hash, so the code itself never has to fit in a proposal; anyone can then apply it through
+/// `System::apply_authorized_upgrade`.
+pub struct GovernanceRootCalls;
+
+impl frame_support::traits::Contains<RuntimeCall> for GovernanceRootCalls {
+    fn contains(call: &RuntimeCall) -> bool {
+        matches!(
+            call,
+            RuntimeCall::System(frame_system::Call::authorize_upgrade { .. })
+        )
+    }
 }
 
 impl pallet_governance::Config for Runtime {
     type PalletId = SubspacePalletId;
     type RuntimeEvent = RuntimeEvent;
     type Currency = Balances;
+    type ProposalOrigin = RuntimeOrigin;
+    type ProposalCall = RuntimeCall;
+    type CallStakeThreshold = GovernanceCallStakeThreshold;
+    type RootCalls = GovernanceRootCalls;
 }
 
 pub const WEIGHT_MILLISECS_PER_BLOCK: u64 = 2000;
@@ -452,6 +520,7 @@ pub type SignedExtra = (
     frame_system::CheckNonce<Runtime>,
     frame_system::CheckWeight<Runtime>,
     pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+    pallet_subspace::SubspaceSignedExtension<Runtime>,
 );
 
 // Unchecked extrinsic type as expected by this runtime.
@@ -693,6 +762,7 @@ impl_runtime_apis! {
         }
     }
 
+    #[api_version(2)]
     impl subspace_runtime_api::SubspaceRuntimeApi<Block> for Runtime {
         fn get_module_info(key: AccountId, netuid: u16) -> ModuleInfo {
             let stats = SubspaceModule::get_module_stats(netuid, &key);
@@ -716,6 +786,189 @@ impl_runtime_apis! {
                 }
             }
         }
+
+        fn get_subnet_metagraph(netuid: u16, start_uid: u16, limit: u16) -> SubnetMetagraph {
+            let modules = SubspaceModule::get_subnet_metagraph(netuid, start_uid, limit)
+                .into_iter()
+                .map(|module| MetagraphModule {
+                    uid: module.uid,
+                    key: module.key,
+                    name: module.name,
+                    address: module.address,
+                    stake: module.stake,
+                    emission: module.emission,
+                    incentive: module.incentive,
+                    dividends: module.dividends,
+                    trust: module.trust,
+                    rank: module.rank,
+                    validator_permit: module.validator_permit,
+                    last_update: module.last_update,
+                    registration_block: module.registration_block,
+                    delegation_fee: module.delegation_fee,
+                })
+                .collect();
+
+            SubnetMetagraph {
+                netuid,
+                module_count: pallet_subspace::N::<Runtime>::get(netuid),
+                modules,
+            }
+        }
+
+        fn get_reward_history(
+            key: AccountId,
+            netuid: Option<u16>,
+            from_block: u64,
+            to_block: u64,
+        ) -> Vec<RewardRecord> {
+            SubspaceModule::get_reward_history(&key, netuid, from_block, to_block)
+                .into_iter()
+                .map(|(netuid, record)| RewardRecord {
+                    netuid,
+                    block: record.block,
+                    amount: record.amount,
+                })
+                .collect()
+        }
+
+        fn get_weight_history(netuid: u16, uid: u16) -> WeightHistory {
+            let (weights, epochs) = SubspaceModule::get_weight_history(netuid, uid);
+
+            WeightHistory {
+                netuid,
+                uid,
+                weights: weights
+                    .into_iter()
+                    .map(|record| WeightRecord {
+                        block: record.block,
+                        weights: record.weights,
+                    })
+                    .collect(),
+                epochs: epochs
+                    .into_iter()
+                    .map(|record| ValidatorEpochRecord {
+                        block: record.block,
+                        validator_trust: record.validator_trust,
+                        dividends: record.dividends,
+                    })
+                    .collect(),
+            }
+        }
+
+        fn validate_weights(
+            netuid: u16,
+            key: AccountId,
+            uids: Vec<u16>,
+            values: Vec<u16>,
+        ) -> WeightsValidation {
+            let validation = SubspaceModule::validate_weights(netuid, &key, uids, values);
+
+            WeightsValidation {
+                error: validation
+                    .error
+                    .map(|err| <&'static str>::from(err).as_bytes().to_vec()),
+                min_allowed_weights: validation.min_allowed_weights,
+                max_allowed_weights: validation.max_allowed_weights,
+                min_stake_for_weights: validation.min_stake_for_weights,
+                stake: validation.stake,
+                set_weight_calls: validation.set_weight_calls,
+                max_set_weight_calls: validation.max_set_weight_calls,
+                weights: validation.weights,
+            }
+        }
+
+        fn get_registration_preview(netuid: u16, stake: u64) -> RegistrationPreview {
+            use pallet_subspace::registration::ModuleSlot;
+
+            let preview = SubspaceModule::get_registration_preview(netuid, stake);
+            let pruned_module = match (preview.module_slot, preview.pruned_key) {
+                (ModuleSlot::Prune { netuid, uid }, Some(key)) => {
+                    Some(PrunedModule { netuid, uid, key })
+                }
+                _ => None,
+            };
+
+            RegistrationPreview {
+                netuid,
+                error: preview
+                    .error
+                    .map(|err| <&'static str>::from(err).as_bytes().to_vec()),
+                burn: preview.burn,
+                min_stake: preview.min_stake,
+                enough_stake: preview.enough_stake,
+                registrations_left_this_block: preview.registrations_left_this_block,
+                registrations_left_this_interval: preview.registrations_left_this_interval,
+                creates_subnet: preview.creates_subnet,
+                replaced_subnet: preview.replaced_subnet,
+                immunity_period: preview.immunity_period,
+                subnet_immune: preview.module_slot == ModuleSlot::Immune,
+                pruned_module,
+            }
+        }
+    }
+
+    impl governance_runtime_api::GovernanceRuntimeApi<Block> for Runtime {
+        fn get_proposals(
+            status: Option<ProposalStatus>,
+            subnet_id: Option<u16>,
+        ) -> Vec<ProposalInfo<AccountId>> {
+            use pallet_governance::proposal::{ProposalKind as Kind, ProposalState as State};
+
+            let status = status.map(|status| match status {
+                ProposalStatus::Open => State::Open,
+                ProposalStatus::Accepted => State::Accepted,
+                ProposalStatus::Refused => State::Refused,
+                ProposalStatus::Expired => State::Expired,
+                ProposalStatus::Enacting => State::Enacting,
+                ProposalStatus::Canceled => State::Canceled,
+                ProposalStatus::EnactmentFailed => State::EnactmentFailed,
+            });
+
+            GovernanceModule::get_proposal_infos(status, subnet_id)
+                .into_iter()
+                .map(|info| ProposalInfo {
+                    id: info.id,
+                    proposer: info.proposer,
+                    subnet_id: info.subnet_id,
+                    kind: match info.kind {
+                        Kind::GlobalCustom => ProposalKind::GlobalCustom,
+                        Kind::GlobalParams => ProposalKind::GlobalParams,
+                        Kind::SubnetCustom => ProposalKind::SubnetCustom,
+                        Kind::SubnetParams => ProposalKind::SubnetParams,
+                        Kind::TransferDaoTreasury => ProposalKind::TransferDaoTreasury,
+                        Kind::RuntimeCall => ProposalKind::RuntimeCall,
+                    },
+                    data: info.data,
+                    metadata: info.metadata,
+                    status: match info.status {
+                        State::Open => ProposalStatus::Open,
+                        State::Accepted => ProposalStatus::Accepted,
+                        State::Refused => ProposalStatus::Refused,
+                        State::Expired => ProposalStatus::Expired,
+                        State::Enacting => ProposalStatus::Enacting,
+                        State::Canceled => ProposalStatus::Canceled,
+                        State::EnactmentFailed => ProposalStatus::EnactmentFailed,
+                    },
+                    proposal_cost: info.proposal_cost,
+                    creation_block: info.creation_block,
+                    expiration_block: info.expiration_block,
+                    votes_for: info.votes_for,
+                    votes_against: info.votes_against,
+                    stake_for: info.stake_for,
+                    stake_against: info.stake_against,
+                })
+                .collect()
+        }
+
+        fn get_proposal_tally(proposal_id: u64) -> Option<ProposalTally> {
+            GovernanceModule::proposal_tally(proposal_id).map(|tally| ProposalTally {
+                proposal_id,
+                stake_for: tally.stake_for,
+                stake_against: tally.stake_against,
+                voted_stake: tally.voted_stake,
+                minimal_stake_to_execute: tally.minimal_stake_to_execute,
+            })
+        }
     }
 
     #[cfg(feature = "runtime-benchmarks")]
@@ -810,6 +1063,10 @@ impl pallet_governance_api::GovernanceApi<<Runtime as frame_system::Config>::Acc
         GovernanceModule::is_delegating_voting_power(delegator)
     }
 
+    fn get_locked_stake(staker: &AccountId, subnet_id: Option<u16>) -> u64 {
+        GovernanceModule::get_locked_stake(staker, subnet_id)
+    }
+
     fn update_delegating_voting_power(delegator: &AccountId, delegating: bool) -> DispatchResult {
         GovernanceModule::update_delegating_voting_power(delegator, delegating)
     }
@@ -839,6 +1096,10 @@ impl pallet_governance_api::GovernanceApi<<Runtime as frame_system::Config>::Acc
         GovernanceModule::handle_subnet_removal(subnet_id);
     }
 
+    fn handle_key_rotation(key: &AccountId, new_key: &AccountId) -> DispatchResult {
+        GovernanceModule::rotate_voting_key(key, new_key)
+    }
+
     fn execute_application(user_id: &AccountId) -> DispatchResult {
         GovernanceModule::execute_application(user_id)
     }